@@ -0,0 +1,229 @@
+//! Calculation and verification of the hash of a Starknet block, as defined in
+//! <https://docs.starknet.io/documentation/architecture_and_concepts/Blocks/header/#block_hash>.
+
+#[cfg(test)]
+#[path = "block_hash_test.rs"]
+mod block_hash_test;
+
+use starknet_api::block::{Block, BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::hash::{pedersen_hash, pedersen_hash_array, StarkFelt, StarkHash};
+use starknet_api::transaction::{Event, Transaction, TransactionHash};
+use starknet_crypto::FieldElement;
+
+use crate::transaction_hash::ascii_as_felt;
+
+// The height of the Patricia-Merkle trees of the transaction and event commitments.
+const COMMITMENT_TREE_HEIGHT: u8 = 64;
+
+/// The versions of the block hash calculation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockHashVersion {
+    /// Blocks before Starknet 0.7.0. The hash contains the chain id, and zeros instead of the
+    /// sequencer address, timestamp and events.
+    Pre0_7,
+    /// Blocks of Starknet 0.7.0 and above. Only the signatures of invoke transactions are in the
+    /// transaction commitment.
+    V0_7,
+    /// Blocks of Starknet 0.11.1 and above. The signatures of declare and deploy account
+    /// transactions are in the transaction commitment too.
+    V0_11_1,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum BlockHashError {
+    #[error(
+        "Block {block_number} has hash {block_hash}, which doesn't match its calculated hash \
+         {calculated_block_hash}."
+    )]
+    Mismatch { block_number: BlockNumber, block_hash: BlockHash, calculated_block_hash: BlockHash },
+    #[error("Calculating the block hash of Starknet version {0} isn't supported.")]
+    UnsupportedVersion(String),
+}
+
+/// Recalculates the hash of the block and compares it to the hash in its header. Blocks before
+/// Starknet 0.9.1 don't report their version, so their hash is compared to all the versions of the
+/// calculation.
+pub fn verify_block_hash(
+    block: &Block,
+    starknet_version: &str,
+    chain_id: &ChainId,
+) -> Result<(), BlockHashError> {
+    let versions = block_hash_versions(starknet_version)?;
+    let mut calculated_block_hash = BlockHash::default();
+    for version in versions {
+        calculated_block_hash = calculate_block_hash(block, *version, chain_id);
+        if calculated_block_hash == block.header.block_hash {
+            return Ok(());
+        }
+    }
+    Err(BlockHashError::Mismatch {
+        block_number: block.header.block_number,
+        block_hash: block.header.block_hash,
+        calculated_block_hash,
+    })
+}
+
+/// Calculates the hash of the block according to the version of the calculation.
+pub fn calculate_block_hash(
+    block: &Block,
+    version: BlockHashVersion,
+    chain_id: &ChainId,
+) -> BlockHash {
+    let header = &block.header;
+    let body = &block.body;
+    let transaction_count = StarkFelt::from(body.transactions.len() as u128);
+    let transaction_commitment =
+        calculate_transaction_commitment(&body.transactions, &body.transaction_hashes, version);
+    let events: Vec<&Event> =
+        body.transaction_outputs.iter().flat_map(|output| output.events()).collect();
+    let zero = StarkFelt::default();
+
+    let elements = match version {
+        BlockHashVersion::Pre0_7 => vec![
+            StarkFelt::from(header.block_number.0 as u128),
+            header.state_root.0,
+            zero,
+            zero,
+            transaction_count,
+            transaction_commitment,
+            zero,
+            zero,
+            zero,
+            zero,
+            ascii_as_felt(&chain_id.0),
+            header.parent_hash.0,
+        ],
+        BlockHashVersion::V0_7 | BlockHashVersion::V0_11_1 => vec![
+            StarkFelt::from(header.block_number.0 as u128),
+            header.state_root.0,
+            *header.sequencer.0.key(),
+            StarkFelt::from(header.timestamp.0 as u128),
+            transaction_count,
+            transaction_commitment,
+            StarkFelt::from(events.len() as u128),
+            calculate_event_commitment(&events),
+            // The protocol version and extra data, which aren't in use.
+            zero,
+            zero,
+            header.parent_hash.0,
+        ],
+    };
+    BlockHash(pedersen_hash_array(&elements))
+}
+
+// Returns the versions of the block hash calculation a block of the Starknet version may use.
+fn block_hash_versions(
+    starknet_version: &str,
+) -> Result<&'static [BlockHashVersion], BlockHashError> {
+    if starknet_version.is_empty() {
+        return Ok(&[BlockHashVersion::V0_7, BlockHashVersion::Pre0_7]);
+    }
+    let version = starknet_version
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| BlockHashError::UnsupportedVersion(starknet_version.to_owned()))?;
+    if version < vec![0, 7] {
+        return Ok(&[BlockHashVersion::Pre0_7]);
+    }
+    // Starknet 0.13.2 changed the calculation of the block hash and the commitments.
+    if version >= vec![0, 13, 2] {
+        return Err(BlockHashError::UnsupportedVersion(starknet_version.to_owned()));
+    }
+    if version >= vec![0, 11, 1] {
+        return Ok(&[BlockHashVersion::V0_11_1]);
+    }
+    Ok(&[BlockHashVersion::V0_7])
+}
+
+// The leaves are the hashes of the transactions with their signatures. Before Starknet 0.11.1 only
+// invoke transactions have a signature in the commitment, and since then declare and deploy
+// account transactions too. The rest use the hash of an empty signature.
+fn calculate_transaction_commitment(
+    transactions: &[Transaction],
+    transaction_hashes: &[TransactionHash],
+    version: BlockHashVersion,
+) -> StarkHash {
+    let leaves: Vec<StarkHash> = transactions
+        .iter()
+        .zip(transaction_hashes)
+        .map(|(transaction, transaction_hash)| {
+            let signature = match (transaction, version) {
+                (Transaction::Invoke(invoke), _) => invoke.signature().0,
+                (Transaction::Declare(declare), BlockHashVersion::V0_11_1) => declare.signature().0,
+                (Transaction::DeployAccount(deploy_account), BlockHashVersion::V0_11_1) => {
+                    deploy_account.signature.0.clone()
+                }
+                _ => Vec::new(),
+            };
+            pedersen_hash(&transaction_hash.0, &pedersen_hash_array(&signature))
+        })
+        .collect();
+    calculate_commitment(&leaves)
+}
+
+fn calculate_event_commitment(events: &[&Event]) -> StarkHash {
+    let leaves: Vec<StarkHash> = events
+        .iter()
+        .map(|event| {
+            let keys: Vec<StarkFelt> = event.content.keys.iter().map(|key| key.0).collect();
+            pedersen_hash_array(&[
+                *event.from_address.0.key(),
+                pedersen_hash_array(&keys),
+                pedersen_hash_array(&event.content.data.0),
+            ])
+        })
+        .collect();
+    calculate_commitment(&leaves)
+}
+
+// Calculates the root of a Patricia-Merkle tree whose leaves are indexed by their position.
+fn calculate_commitment(leaves: &[StarkHash]) -> StarkHash {
+    if leaves.is_empty() {
+        return StarkHash::default();
+    }
+    let indexed_leaves: Vec<(u64, StarkHash)> =
+        leaves.iter().enumerate().map(|(index, leaf)| (index as u64, *leaf)).collect();
+    calculate_node(&indexed_leaves, COMMITMENT_TREE_HEIGHT).hash()
+}
+
+// A node of a Patricia-Merkle tree. An edge node is represented by the hash of the node at the
+// bottom of the edge and the path to it, other nodes by their hash and an empty path.
+struct Node {
+    bottom: StarkHash,
+    path: u128,
+    length: u8,
+}
+
+impl Node {
+    fn hash(&self) -> StarkHash {
+        if self.length == 0 {
+            return self.bottom;
+        }
+        let edge_hash = pedersen_hash(&self.bottom, &StarkFelt::from(self.path));
+        StarkFelt::from(FieldElement::from(edge_hash) + FieldElement::from(self.length))
+    }
+}
+
+// Calculates the node at the root of a subtree of the given height, from the non-empty leaves of
+// the subtree sorted by their index.
+fn calculate_node(leaves: &[(u64, StarkHash)], height: u8) -> Node {
+    if height == 0 {
+        return Node { bottom: leaves[0].1, path: 0, length: 0 };
+    }
+    let bit = 1_u64 << (height - 1);
+    let (left, right) = leaves.split_at(leaves.partition_point(|(index, _)| index & bit == 0));
+    if left.is_empty() || right.is_empty() {
+        let child = calculate_node(leaves, height - 1);
+        let direction = u128::from(left.is_empty());
+        return Node {
+            bottom: child.bottom,
+            path: child.path | (direction << child.length),
+            length: child.length + 1,
+        };
+    }
+    let left_hash = calculate_node(left, height - 1).hash();
+    let right_hash = calculate_node(right, height - 1).hash();
+    Node { bottom: pedersen_hash(&left_hash, &right_hash), path: 0, length: 0 }
+}
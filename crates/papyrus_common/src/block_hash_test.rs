@@ -0,0 +1,176 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::Block;
+use starknet_api::core::ChainId;
+use starknet_api::hash::{pedersen_hash, StarkFelt, StarkHash};
+use starknet_api::stark_felt;
+use starknet_api::transaction::{
+    DeclareTransaction, DeployAccountTransaction, InvokeTransaction, Transaction, TransactionHash,
+    TransactionSignature,
+};
+use starknet_client::reader::Block as ClientBlock;
+use starknet_crypto::FieldElement;
+use test_utils::get_absolute_path;
+
+use crate::block_hash::{
+    calculate_block_hash, calculate_commitment, verify_block_hash, BlockHashError, BlockHashVersion,
+};
+
+fn get_block_and_version() -> (Block, String) {
+    let path = get_absolute_path("crates/starknet_client/resources/reader/block.json");
+    let client_block: ClientBlock =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    client_block.to_starknet_api_block_and_version().unwrap()
+}
+
+// The hash of an edge node: the hash of the bottom node and the path, plus the length.
+fn edge_hash(bottom: StarkHash, path: u128, length: u8) -> StarkHash {
+    StarkFelt::from(
+        FieldElement::from(pedersen_hash(&bottom, &StarkFelt::from(path)))
+            + FieldElement::from(length),
+    )
+}
+
+#[test]
+fn commitment() {
+    let leaf_0 = stark_felt!("0x10");
+    let leaf_1 = stark_felt!("0x11");
+    let leaf_2 = stark_felt!("0x12");
+
+    assert_eq!(calculate_commitment(&[]), StarkHash::default());
+    // A single leaf at index 0 is at the bottom of an edge from the root along the zero path.
+    assert_eq!(calculate_commitment(&[leaf_0]), edge_hash(leaf_0, 0, 64));
+    // Leaves 0 and 1 are the children of a binary node at the bottom of the tree.
+    assert_eq!(
+        calculate_commitment(&[leaf_0, leaf_1]),
+        edge_hash(pedersen_hash(&leaf_0, &leaf_1), 0, 63)
+    );
+    // Leaf 2 is at the bottom of an edge of length 1 from the binary node above leaves 2 and 3.
+    assert_eq!(
+        calculate_commitment(&[leaf_0, leaf_1, leaf_2]),
+        edge_hash(pedersen_hash(&pedersen_hash(&leaf_0, &leaf_1), &edge_hash(leaf_2, 0, 1)), 0, 62)
+    );
+}
+
+// A block with a declare and a deploy account transaction, both signed. The transactions of
+// block.json were edited to cover all the transaction types, so the hash it records isn't the hash
+// of its content, and the tests compare the versions of the calculation with each other.
+fn get_block_with_signatures() -> Block {
+    let (mut block, _) = get_block_and_version();
+    let signature = TransactionSignature(vec![stark_felt!("0x1"), stark_felt!("0x2")]);
+    let Some(Transaction::Declare(DeclareTransaction::V1(declare))) =
+        block.body.transactions.get_mut(2)
+    else {
+        panic!("The third transaction of block.json should be a declare transaction of version 1.");
+    };
+    declare.signature = signature.clone();
+    block.body.transactions.push(Transaction::DeployAccount(DeployAccountTransaction {
+        signature,
+        ..DeployAccountTransaction::default()
+    }));
+    block.body.transaction_hashes.push(TransactionHash(stark_felt!("0x3")));
+    block
+}
+
+// Before Starknet 0.11.1 only the signatures of invoke transactions are in the transaction
+// commitment, and since then the signatures of declare and deploy account transactions too.
+#[test]
+fn signatures_in_transaction_commitment() {
+    let chain_id = ChainId("SN_MAIN".to_owned());
+    let block = get_block_with_signatures();
+    let mut unsigned_block = block.clone();
+    for transaction in &mut unsigned_block.body.transactions {
+        match transaction {
+            Transaction::Declare(DeclareTransaction::V1(declare)) => {
+                declare.signature = TransactionSignature::default();
+            }
+            Transaction::DeployAccount(deploy_account) => {
+                deploy_account.signature = TransactionSignature::default();
+            }
+            _ => {}
+        }
+    }
+
+    let hash = |block: &Block, version| calculate_block_hash(block, version, &chain_id);
+    assert_eq!(hash(&block, BlockHashVersion::V0_7), hash(&unsigned_block, BlockHashVersion::V0_7));
+    assert_ne!(
+        hash(&block, BlockHashVersion::V0_11_1),
+        hash(&unsigned_block, BlockHashVersion::V0_11_1)
+    );
+    // Without signatures of declare and deploy account transactions the versions agree.
+    assert_eq!(
+        hash(&unsigned_block, BlockHashVersion::V0_7),
+        hash(&unsigned_block, BlockHashVersion::V0_11_1)
+    );
+
+    // The signatures of invoke transactions are in the commitment of both versions.
+    let mut block_without_invoke_signature = unsigned_block.clone();
+    let Some(Transaction::Invoke(InvokeTransaction::V0(invoke))) =
+        block_without_invoke_signature.body.transactions.get_mut(1)
+    else {
+        panic!("The second transaction of block.json should be an invoke transaction.");
+    };
+    assert!(!invoke.signature.0.is_empty());
+    invoke.signature = TransactionSignature::default();
+    for version in [BlockHashVersion::V0_7, BlockHashVersion::V0_11_1] {
+        assert_ne!(hash(&unsigned_block, version), hash(&block_without_invoke_signature, version));
+    }
+}
+
+// The versions of the calculation a block is compared to, by its Starknet version.
+#[test]
+fn verify_by_starknet_version() {
+    let chain_id = ChainId("SN_MAIN".to_owned());
+    let block = get_block_with_signatures();
+    let block_with_hash = |version| {
+        let mut block = block.clone();
+        block.header.block_hash = calculate_block_hash(&block, version, &chain_id);
+        block
+    };
+    let v0_11_1_block = block_with_hash(BlockHashVersion::V0_11_1);
+    let v0_7_block = block_with_hash(BlockHashVersion::V0_7);
+    let pre_0_7_block = block_with_hash(BlockHashVersion::Pre0_7);
+
+    for starknet_version in ["0.11.1", "0.12.0", "0.13.1"] {
+        verify_block_hash(&v0_11_1_block, starknet_version, &chain_id).unwrap();
+        assert_matches!(
+            verify_block_hash(&v0_7_block, starknet_version, &chain_id),
+            Err(BlockHashError::Mismatch { calculated_block_hash, .. })
+                if calculated_block_hash == v0_11_1_block.header.block_hash
+        );
+    }
+    for starknet_version in ["0.9.1", "0.11.0", "0.11.0.2"] {
+        verify_block_hash(&v0_7_block, starknet_version, &chain_id).unwrap();
+        assert_matches!(
+            verify_block_hash(&v0_11_1_block, starknet_version, &chain_id),
+            Err(BlockHashError::Mismatch { .. })
+        );
+    }
+    verify_block_hash(&pre_0_7_block, "0.6.2", &chain_id).unwrap();
+    // Blocks without a version are from before Starknet 0.9.1, and are compared to the versions of
+    // the calculation of that time.
+    verify_block_hash(&v0_7_block, "", &chain_id).unwrap();
+    verify_block_hash(&pre_0_7_block, "", &chain_id).unwrap();
+    assert_matches!(
+        verify_block_hash(&v0_11_1_block, "", &chain_id),
+        Err(BlockHashError::Mismatch { .. })
+    );
+
+    // The chain id is part of the calculation before Starknet 0.7.0.
+    assert_ne!(
+        calculate_block_hash(&block, BlockHashVersion::Pre0_7, &ChainId("SN_GOERLI".to_owned())),
+        pre_0_7_block.header.block_hash
+    );
+}
+
+#[test]
+fn unsupported_versions() {
+    let chain_id = ChainId("SN_MAIN".to_owned());
+    let (block, _) = get_block_and_version();
+    for version in ["0.13.2", "unknown"] {
+        assert_eq!(
+            verify_block_hash(&block, version, &chain_id),
+            Err(BlockHashError::UnsupportedVersion(version.to_owned()))
+        );
+    }
+}
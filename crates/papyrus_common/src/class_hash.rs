@@ -0,0 +1,409 @@
+//! Calculation and verification of the hashes of Starknet classes: the class hash of Cairo 0
+//! (deprecated) and Sierra classes, and the compiled class hash of CASM classes. See
+//! <https://docs.starknet.io/documentation/architecture_and_concepts/Smart_Contracts/class-hash/>.
+
+#[cfg(test)]
+#[path = "class_hash_test.rs"]
+mod class_hash_test;
+
+use std::io;
+
+use cairo_lang_starknet::casm_contract_class::{CasmContractClass, CasmContractEntryPoint};
+use serde::Serialize;
+use serde_json::ser::Formatter;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::deprecated_contract_class::{
+    ContractClass as DeprecatedContractClass, ContractClassAbiEntry,
+    EntryPointType as DeprecatedEntryPointType,
+};
+use starknet_api::hash::{pedersen_hash_array, StarkFelt, StarkHash};
+use starknet_api::state::{ContractClass, EntryPointType};
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+
+use crate::transaction_hash::ascii_as_felt;
+
+const SIERRA_CLASS_PREFIX: &str = "CONTRACT_CLASS_V0.1.0";
+const COMPILED_CLASS_PREFIX: &str = "COMPILED_CLASS_V1";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClassHashError {
+    #[error("Class {class_hash} doesn't match its calculated hash {calculated_class_hash}.")]
+    ClassHashMismatch { class_hash: ClassHash, calculated_class_hash: ClassHash },
+    #[error(
+        "The compiled class of class {class_hash} has hash {compiled_class_hash}, which doesn't \
+         match its calculated hash {calculated_compiled_class_hash}."
+    )]
+    CompiledClassHashMismatch {
+        class_hash: ClassHash,
+        compiled_class_hash: CompiledClassHash,
+        calculated_compiled_class_hash: CompiledClassHash,
+    },
+    #[error("Invalid program of class {class_hash}: {reason}.")]
+    InvalidProgram { class_hash: ClassHash, reason: String },
+    #[error("Invalid compiled class of class {class_hash}: {reason}.")]
+    InvalidCompiledClass { class_hash: ClassHash, reason: String },
+}
+
+/// Recalculates the hash of the Sierra class and compares it to the given hash.
+pub fn verify_class_hash(
+    class: &ContractClass,
+    class_hash: &ClassHash,
+) -> Result<(), ClassHashError> {
+    let calculated_class_hash = calculate_class_hash(class);
+    if calculated_class_hash != *class_hash {
+        return Err(ClassHashError::ClassHashMismatch {
+            class_hash: *class_hash,
+            calculated_class_hash,
+        });
+    }
+    Ok(())
+}
+
+/// Recalculates the hash of the Cairo 0 class and compares it to the given hash.
+pub fn verify_deprecated_class_hash(
+    class: &DeprecatedContractClass,
+    class_hash: &ClassHash,
+) -> Result<(), ClassHashError> {
+    let calculated_class_hash = calculate_deprecated_class_hash(class)
+        .map_err(|reason| ClassHashError::InvalidProgram { class_hash: *class_hash, reason })?;
+    if calculated_class_hash != *class_hash {
+        return Err(ClassHashError::ClassHashMismatch {
+            class_hash: *class_hash,
+            calculated_class_hash,
+        });
+    }
+    Ok(())
+}
+
+/// Recalculates the compiled class hash of the CASM class of the given class and compares it to
+/// the given compiled class hash.
+pub fn verify_compiled_class_hash(
+    compiled_class: &CasmContractClass,
+    class_hash: &ClassHash,
+    compiled_class_hash: &CompiledClassHash,
+) -> Result<(), ClassHashError> {
+    let calculated_compiled_class_hash =
+        calculate_compiled_class_hash(compiled_class).map_err(|reason| {
+            ClassHashError::InvalidCompiledClass { class_hash: *class_hash, reason }
+        })?;
+    if calculated_compiled_class_hash != *compiled_class_hash {
+        return Err(ClassHashError::CompiledClassHashMismatch {
+            class_hash: *class_hash,
+            compiled_class_hash: *compiled_class_hash,
+            calculated_compiled_class_hash,
+        });
+    }
+    Ok(())
+}
+
+/// Calculates the hash of a Sierra class.
+pub fn calculate_class_hash(class: &ContractClass) -> ClassHash {
+    let entry_points_hash = |entry_point_type| {
+        let felts: Vec<FieldElement> = class
+            .entry_point_by_type
+            .get(&entry_point_type)
+            .into_iter()
+            .flatten()
+            .flat_map(|entry_point| {
+                [
+                    FieldElement::from(entry_point.selector.0),
+                    FieldElement::from(entry_point.function_idx.0),
+                ]
+            })
+            .collect();
+        poseidon_hash_many(&felts)
+    };
+    let sierra_program: Vec<FieldElement> =
+        class.sierra_program.iter().map(|felt| FieldElement::from(*felt)).collect();
+    let hash = poseidon_hash_many(&[
+        FieldElement::from(ascii_as_felt(SIERRA_CLASS_PREFIX)),
+        entry_points_hash(EntryPointType::External),
+        entry_points_hash(EntryPointType::L1Handler),
+        entry_points_hash(EntryPointType::Constructor),
+        FieldElement::from(starknet_keccak(class.abi.as_bytes())),
+        poseidon_hash_many(&sierra_program),
+    ]);
+    ClassHash(StarkFelt::from(hash))
+}
+
+/// Calculates the compiled class hash of a CASM class. Returns the reason if a value of the class
+/// doesn't fit in a field element.
+pub fn calculate_compiled_class_hash(
+    compiled_class: &CasmContractClass,
+) -> Result<CompiledClassHash, String> {
+    let entry_points_hash = |entry_points: &[CasmContractEntryPoint]| {
+        let mut felts = Vec::with_capacity(entry_points.len() * 3);
+        for entry_point in entry_points {
+            let builtins: Vec<FieldElement> = entry_point
+                .builtins
+                .iter()
+                .map(|builtin| FieldElement::from(ascii_as_felt(builtin)))
+                .collect();
+            felts.extend([
+                big_uint_as_felt(&entry_point.selector.to_bytes_be())?,
+                FieldElement::from(entry_point.offset),
+                poseidon_hash_many(&builtins),
+            ]);
+        }
+        Ok::<_, String>(poseidon_hash_many(&felts))
+    };
+    let bytecode = compiled_class
+        .bytecode
+        .iter()
+        .map(|value| big_uint_as_felt(&value.value.to_bytes_be()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let entry_points = &compiled_class.entry_points_by_type;
+    let hash = poseidon_hash_many(&[
+        FieldElement::from(ascii_as_felt(COMPILED_CLASS_PREFIX)),
+        entry_points_hash(&entry_points.external)?,
+        entry_points_hash(&entry_points.l1_handler)?,
+        entry_points_hash(&entry_points.constructor)?,
+        poseidon_hash_many(&bytecode),
+    ]);
+    Ok(CompiledClassHash(StarkFelt::from(hash)))
+}
+
+/// Calculates the hash of a Cairo 0 class. Returns the reason if the program of the class isn't
+/// valid.
+pub fn calculate_deprecated_class_hash(
+    class: &DeprecatedContractClass,
+) -> Result<ClassHash, String> {
+    let entry_points_hash = |entry_point_type| {
+        let felts: Vec<StarkFelt> = class
+            .entry_points_by_type
+            .get(&entry_point_type)
+            .into_iter()
+            .flatten()
+            .flat_map(|entry_point| {
+                [entry_point.selector.0, StarkFelt::from(entry_point.offset.0 as u128)]
+            })
+            .collect();
+        pedersen_hash_array(&felts)
+    };
+    let builtins = class
+        .program
+        .builtins
+        .as_array()
+        .ok_or("the builtins aren't an array")?
+        .iter()
+        .map(|builtin| builtin.as_str().map(ascii_as_felt).ok_or("a builtin isn't a string"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let program_data = class
+        .program
+        .data
+        .as_array()
+        .ok_or("the program data isn't an array")?
+        .iter()
+        .map(|felt| {
+            felt.as_str()
+                .and_then(|felt| StarkFelt::try_from(felt).ok())
+                .ok_or("the program data contains an invalid field element")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let hash = pedersen_hash_array(&[
+        // The API version.
+        StarkFelt::default(),
+        entry_points_hash(DeprecatedEntryPointType::External),
+        entry_points_hash(DeprecatedEntryPointType::L1Handler),
+        entry_points_hash(DeprecatedEntryPointType::Constructor),
+        pedersen_hash_array(&builtins),
+        hinted_class_hash(class).map_err(|err| err.to_string())?,
+        pedersen_hash_array(&program_data),
+    ]);
+    Ok(ClassHash(hash))
+}
+
+// The Keccak hash of the ABI and the program without the debug info, serialized as the JSON that
+// the Python implementation of Starknet produces.
+fn hinted_class_hash(class: &DeprecatedContractClass) -> serde_json::Result<StarkHash> {
+    let mut program = serde_json::to_value(&class.program)?;
+    let program_object = program.as_object_mut().expect("A program should be a JSON object.");
+    program_object.insert("debug_info".to_owned(), Value::Null);
+    match program_object.get_mut("attributes") {
+        Some(Value::Array(attributes)) if !attributes.is_empty() => {
+            // Attributes of old programs may have empty scopes and no flow tracking data, which
+            // aren't part of the hash.
+            for attribute in attributes.iter_mut().filter_map(Value::as_object_mut) {
+                if attribute
+                    .get("accessible_scopes")
+                    .and_then(Value::as_array)
+                    .is_some_and(|accessible_scopes| accessible_scopes.is_empty())
+                {
+                    attribute.remove("accessible_scopes");
+                }
+                if attribute.get("flow_tracking_data").is_some_and(Value::is_null) {
+                    attribute.remove("flow_tracking_data");
+                }
+            }
+        }
+        _ => {
+            program_object.remove("attributes");
+        }
+    }
+    // Programs that were compiled before Cairo 0.10.0 have no compiler version, and their hash
+    // uses the old format of named tuples, "(a : felt)" instead of "(a: felt)".
+    if matches!(program_object.get("compiler_version"), None | Some(Value::Null)) {
+        program_object.remove("compiler_version");
+        for key in ["identifiers", "reference_manager"] {
+            if let Some(value) = program_object.get_mut(key) {
+                add_space_before_colon_in_named_tuples(value);
+            }
+        }
+    }
+
+    let abi = match &class.abi {
+        Some(abi) => Value::Array(abi.iter().map(abi_entry_as_value).collect::<Result<_, _>>()?),
+        None => Value::Null,
+    };
+    let mut hashed_object = serde_json::Map::new();
+    hashed_object.insert("abi".to_owned(), abi);
+    hashed_object.insert("program".to_owned(), program);
+
+    let mut serialized = Vec::new();
+    let mut serializer =
+        serde_json::Serializer::with_formatter(&mut serialized, PythonJsonFormatter);
+    SortedJson { value: &Value::Object(hashed_object), position: JsonPosition::Root }
+        .serialize(&mut serializer)?;
+    Ok(starknet_keccak(&serialized))
+}
+
+// The event and struct ABI entries are serialized without their type, add it back.
+fn abi_entry_as_value(entry: &ContractClassAbiEntry) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(entry)?;
+    let entry_type = match entry {
+        ContractClassAbiEntry::Event(_) => "event",
+        ContractClassAbiEntry::Struct(_) => "struct",
+        ContractClassAbiEntry::Function(_) => return Ok(value),
+    };
+    if let Some(object) = value.as_object_mut() {
+        object.insert("type".to_owned(), Value::String(entry_type.to_owned()));
+    }
+    Ok(value)
+}
+
+fn add_space_before_colon_in_named_tuples(value: &mut Value) {
+    match value {
+        Value::Array(values) => values.iter_mut().for_each(add_space_before_colon_in_named_tuples),
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(string) if key == "cairo_type" || key == "value" => {
+                        // Types that already have the old format would otherwise get two spaces.
+                        *string = string.replace(": ", " : ").replace("  :", " :");
+                    }
+                    _ => add_space_before_colon_in_named_tuples(value),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Where a JSON value is in the hashed object, to find the hints of the program.
+#[derive(Clone, Copy, PartialEq)]
+enum JsonPosition {
+    Root,
+    Program,
+    Hints,
+    Other,
+}
+
+// Serializes a JSON value with the keys of its objects sorted, like Python's json.dumps with
+// sort_keys. The hints of the program are keyed by their PC, which Python sorts as numbers.
+struct SortedJson<'a> {
+    value: &'a Value,
+    position: JsonPosition,
+}
+
+impl Serialize for SortedJson<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Object(object) => {
+                let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+                if self.position == JsonPosition::Hints {
+                    entries.sort_by_key(|(key, _)| key.parse::<u64>().ok());
+                } else {
+                    entries.sort_by_key(|(key, _)| *key);
+                }
+                serializer.collect_map(entries.into_iter().map(|(key, value)| {
+                    let position = match (self.position, key.as_str()) {
+                        (JsonPosition::Root, "program") => JsonPosition::Program,
+                        (JsonPosition::Program, "hints") => JsonPosition::Hints,
+                        _ => JsonPosition::Other,
+                    };
+                    (key, SortedJson { value, position })
+                }))
+            }
+            Value::Array(values) => serializer.collect_seq(
+                values.iter().map(|value| SortedJson { value, position: JsonPosition::Other }),
+            ),
+            _ => self.value.serialize(serializer),
+        }
+    }
+}
+
+// Formats JSON like Python's json.dumps with its default arguments: with a space after the
+// separators and with the non-ASCII characters escaped.
+struct PythonJsonFormatter;
+
+impl Formatter for PythonJsonFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+
+    fn write_string_fragment<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        for char in fragment.chars() {
+            if char.is_ascii() && char != '\x7f' {
+                writer.write_all(&[char as u8])?;
+            } else {
+                for code_unit in char.encode_utf16(&mut [0; 2]) {
+                    write!(writer, "\\u{code_unit:04x}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// The Keccak-256 hash of the data, truncated to 250 bits.
+pub(crate) fn starknet_keccak(data: &[u8]) -> StarkHash {
+    let mut hash: [u8; 32] = Keccak256::digest(data).into();
+    hash[0] &= 0x03;
+    StarkHash::new(hash).expect("A 250 bits hash should fit in a field element.")
+}
+
+// The compiled class comes from the central source, so its values might not be field elements.
+fn big_uint_as_felt(bytes: &[u8]) -> Result<FieldElement, String> {
+    FieldElement::from_byte_slice_be(bytes)
+        .map_err(|_| format!("the value 0x{} doesn't fit in a field element", hex::encode(bytes)))
+}
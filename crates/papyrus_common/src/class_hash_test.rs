@@ -0,0 +1,112 @@
+use std::io::Read;
+
+use assert_matches::assert_matches;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use cairo_lang_starknet::contract_class::ContractClass as CairoLangContractClass;
+use num_bigint::BigUint;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::state::ContractClass;
+use starknet_client::reader::ContractClass as ClientContractClass;
+use test_utils::get_absolute_path;
+
+use crate::class_hash::{
+    calculate_class_hash, calculate_compiled_class_hash, calculate_deprecated_class_hash,
+    starknet_keccak, verify_class_hash, verify_compiled_class_hash, verify_deprecated_class_hash,
+    ClassHashError,
+};
+
+// The contract class of a declare transaction of the gateway client tests, with its program
+// decompressed.
+fn read_declared_class(file_name: &str, program_field: &str) -> (Value, Value) {
+    let path = get_absolute_path(&format!("crates/starknet_client/resources/writer/{file_name}"));
+    let declare: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let mut class = declare["contract_class"].clone();
+    let compressed_program = base64::decode(class[program_field].as_str().unwrap()).unwrap();
+    let mut program = String::new();
+    flate2::read::GzDecoder::new(compressed_program.as_slice())
+        .read_to_string(&mut program)
+        .unwrap();
+    class[program_field] = serde_json::from_str(&program).unwrap();
+    (declare, class)
+}
+
+#[test]
+fn deprecated_class_hash() {
+    // The class of a declare transaction on Goerli, whose hash was calculated by the gateway.
+    let (_, class) = read_declared_class("declare_v1.json", "program");
+    let class: DeprecatedContractClass = serde_json::from_value(class).unwrap();
+    let class_hash =
+        ClassHash(stark_felt!("0x32ba0c2c5aa132c795a7ffa58057c36283dedf7bc0bfbf5687ba52fd317f56d"));
+
+    assert_eq!(calculate_deprecated_class_hash(&class).unwrap(), class_hash);
+    verify_deprecated_class_hash(&class, &class_hash).unwrap();
+    assert_matches!(
+        verify_deprecated_class_hash(&class, &ClassHash(stark_felt!("0x1"))),
+        Err(ClassHashError::ClassHashMismatch { calculated_class_hash, .. })
+            if calculated_class_hash == class_hash
+    );
+}
+
+#[test]
+fn compiled_class_hash() {
+    // The class of a declare transaction, with the compiled class hash calculated by the client.
+    let (declare, mut class) = read_declared_class("declare_v2.json", "sierra_program");
+    class.as_object_mut().unwrap().remove("abi");
+    let class: CairoLangContractClass = serde_json::from_value(class).unwrap();
+    let compiled_class = CasmContractClass::from_contract_class(class, false).unwrap();
+    let compiled_class_hash = CompiledClassHash(
+        StarkFelt::try_from(declare["compiled_class_hash"].as_str().unwrap()).unwrap(),
+    );
+
+    assert_eq!(calculate_compiled_class_hash(&compiled_class).unwrap(), compiled_class_hash);
+    let class_hash = ClassHash(stark_felt!("0x1"));
+    verify_compiled_class_hash(&compiled_class, &class_hash, &compiled_class_hash).unwrap();
+    assert_matches!(
+        verify_compiled_class_hash(
+            &compiled_class,
+            &class_hash,
+            &CompiledClassHash(stark_felt!("0x1"))
+        ),
+        Err(ClassHashError::CompiledClassHashMismatch { .. })
+    );
+
+    // A value at or above the field prime isn't a field element.
+    let mut invalid_compiled_class = compiled_class.clone();
+    invalid_compiled_class.bytecode[0].value = BigUint::from_bytes_be(&[0xff; 32]);
+    assert_matches!(
+        verify_compiled_class_hash(&invalid_compiled_class, &class_hash, &compiled_class_hash),
+        Err(ClassHashError::InvalidCompiledClass { class_hash: hash, .. }) if hash == class_hash
+    );
+}
+
+#[test]
+fn class_hash() {
+    // The class of the declare transaction of version 2 in transaction_hash.json, whose hash
+    // contains the class hash and is signed by the declaring account.
+    let (_, class) = read_declared_class("declare_v2.json", "sierra_program");
+    let class: ClientContractClass = serde_json::from_value(class).unwrap();
+    let class = ContractClass::from(class);
+    let class_hash =
+        ClassHash(stark_felt!("0x124b7288f783dede8d8874c3f6f2745141c4e54999fa203e7a1312af2f2c6f2"));
+
+    assert_eq!(calculate_class_hash(&class), class_hash);
+    verify_class_hash(&class, &class_hash).unwrap();
+    let class_with_other_abi = ContractClass { abi: format!("{} ", class.abi), ..class };
+    assert_matches!(
+        verify_class_hash(&class_with_other_abi, &class_hash),
+        Err(ClassHashError::ClassHashMismatch { .. })
+    );
+}
+
+#[test]
+fn starknet_keccak_of_selector() {
+    assert_eq!(
+        starknet_keccak(b"constructor"),
+        stark_felt!("0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194")
+    );
+}
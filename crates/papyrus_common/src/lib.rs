@@ -1,3 +1,8 @@
+pub mod block_hash;
+pub mod class_hash;
+pub mod pending_data;
+pub mod transaction_hash;
+
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber};
@@ -0,0 +1,23 @@
+use starknet_api::block::{BlockBody, BlockHash, BlockTimestamp, GasPrice};
+use starknet_api::core::ContractAddress;
+use starknet_api::state::StateDiff;
+
+/// The block that is currently being constructed by the sequencer, together with its state diff.
+/// The sync keeps the latest pending data in memory and the gateway serves it for the `pending`
+/// block tag.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PendingData {
+    pub block: PendingBlock,
+    /// Includes the definitions of the classes declared in the pending block.
+    pub state_diff: StateDiff,
+}
+
+/// The header fields that are known before the block is closed, and the body of the block.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PendingBlock {
+    pub parent_block_hash: BlockHash,
+    pub gas_price: GasPrice,
+    pub timestamp: BlockTimestamp,
+    pub sequencer_address: ContractAddress,
+    pub body: BlockBody,
+}
@@ -0,0 +1,265 @@
+//! Calculation and verification of the hashes of Starknet transactions, as defined in
+//! <https://docs.starknet.io/documentation/architecture_and_concepts/Blocks/transactions/>.
+
+#[cfg(test)]
+#[path = "transaction_hash_test.rs"]
+mod transaction_hash_test;
+
+use starknet_api::core::{calculate_contract_address, ChainId, ContractAddress};
+use starknet_api::hash::{pedersen_hash_array, StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    DeclareTransaction, DeployAccountTransaction, DeployTransaction, InvokeTransaction,
+    L1HandlerTransaction, Transaction, TransactionHash,
+};
+use starknet_api::StarknetApiError;
+
+// The selector of the constructor entry point, sn_keccak("constructor").
+const CONSTRUCTOR_ENTRY_POINT_SELECTOR: &str =
+    "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194";
+
+#[derive(thiserror::Error, Debug)]
+pub enum TransactionHashError {
+    #[error(
+        "Transaction {transaction_hash} doesn't match its calculated hash \
+         {calculated_transaction_hash}."
+    )]
+    Mismatch { transaction_hash: TransactionHash, calculated_transaction_hash: TransactionHash },
+    #[error("Failed to calculate the address of the deployed contract: {0}")]
+    ContractAddress(StarknetApiError),
+}
+
+/// Recalculates the hash of the transaction and compares it to the given hash. Transactions of old
+/// Starknet versions may have been hashed with a deprecated calculation, so their hash is compared
+/// to all the calculations that apply to the transaction.
+pub fn verify_transaction_hash(
+    transaction: &Transaction,
+    transaction_hash: &TransactionHash,
+    chain_id: &ChainId,
+) -> Result<(), TransactionHashError> {
+    let calculated_transaction_hashes = calculate_transaction_hashes(transaction, chain_id)?;
+    if calculated_transaction_hashes.contains(transaction_hash) {
+        return Ok(());
+    }
+    Err(TransactionHashError::Mismatch {
+        transaction_hash: *transaction_hash,
+        calculated_transaction_hash: calculated_transaction_hashes[0],
+    })
+}
+
+/// Calculates the hash of the transaction with the current calculation of its type.
+pub fn calculate_transaction_hash(
+    transaction: &Transaction,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, TransactionHashError> {
+    Ok(calculate_transaction_hashes(transaction, chain_id)?[0])
+}
+
+// Returns the hashes of the transaction according to all the calculations that apply to it, the
+// current calculation first.
+fn calculate_transaction_hashes(
+    transaction: &Transaction,
+    chain_id: &ChainId,
+) -> Result<Vec<TransactionHash>, TransactionHashError> {
+    let chain_id = ascii_as_felt(&chain_id.0);
+    let hashes = match transaction {
+        Transaction::Declare(declare) => vec![declare_hash(declare, chain_id)],
+        Transaction::Deploy(deploy) => deploy_hashes(deploy, chain_id)?,
+        Transaction::DeployAccount(deploy_account) => {
+            vec![deploy_account_hash(deploy_account, chain_id)?]
+        }
+        Transaction::Invoke(invoke) => invoke_hashes(invoke, chain_id),
+        Transaction::L1Handler(l1_handler) => l1_handler_hashes(l1_handler, chain_id),
+    };
+    Ok(hashes.into_iter().map(TransactionHash).collect())
+}
+
+fn declare_hash(declare: &DeclareTransaction, chain_id: StarkFelt) -> StarkHash {
+    let prefix = ascii_as_felt("declare");
+    let version = declare.version().0;
+    let sender_address = *declare.sender_address().0.key();
+    let max_fee = StarkFelt::from(declare.max_fee().0);
+    let class_hash = declare.class_hash().0;
+    let nonce = declare.nonce().0;
+    let zero = StarkFelt::default();
+    match declare {
+        // Declare V0 has no nonce, and the class hash is added after the chain id.
+        DeclareTransaction::V0(_) => pedersen_hash_array(&[
+            prefix,
+            version,
+            sender_address,
+            zero,
+            pedersen_hash_array(&[]),
+            max_fee,
+            chain_id,
+            class_hash,
+        ]),
+        DeclareTransaction::V1(_) => pedersen_hash_array(&[
+            prefix,
+            version,
+            sender_address,
+            zero,
+            pedersen_hash_array(&[class_hash]),
+            max_fee,
+            chain_id,
+            nonce,
+        ]),
+        DeclareTransaction::V2(declare_v2) => pedersen_hash_array(&[
+            prefix,
+            version,
+            sender_address,
+            zero,
+            pedersen_hash_array(&[class_hash]),
+            max_fee,
+            chain_id,
+            nonce,
+            declare_v2.compiled_class_hash.0,
+        ]),
+    }
+}
+
+fn deploy_hashes(
+    deploy: &DeployTransaction,
+    chain_id: StarkFelt,
+) -> Result<Vec<StarkHash>, TransactionHashError> {
+    let prefix = ascii_as_felt("deploy");
+    let contract_address = *calculate_contract_address(
+        deploy.contract_address_salt,
+        deploy.class_hash,
+        &deploy.constructor_calldata,
+        ContractAddress::default(),
+    )
+    .map_err(TransactionHashError::ContractAddress)?
+    .0
+    .key();
+    let constructor_selector = StarkFelt::try_from(CONSTRUCTOR_ENTRY_POINT_SELECTOR)
+        .expect("The constructor selector should be a valid field element.");
+    let calldata_hash = pedersen_hash_array(&deploy.constructor_calldata.0);
+    Ok(vec![
+        pedersen_hash_array(&[
+            prefix,
+            deploy.version.0,
+            contract_address,
+            constructor_selector,
+            calldata_hash,
+            // Deploy transactions have no fee.
+            StarkFelt::default(),
+            chain_id,
+        ]),
+        // The deprecated calculation, without the version and the fee.
+        pedersen_hash_array(&[
+            prefix,
+            contract_address,
+            constructor_selector,
+            calldata_hash,
+            chain_id,
+        ]),
+    ])
+}
+
+fn deploy_account_hash(
+    deploy_account: &DeployAccountTransaction,
+    chain_id: StarkFelt,
+) -> Result<StarkHash, TransactionHashError> {
+    let contract_address = *calculate_contract_address(
+        deploy_account.contract_address_salt,
+        deploy_account.class_hash,
+        &deploy_account.constructor_calldata,
+        ContractAddress::default(),
+    )
+    .map_err(TransactionHashError::ContractAddress)?
+    .0
+    .key();
+    let mut calldata = vec![deploy_account.class_hash.0, deploy_account.contract_address_salt.0];
+    calldata.extend(deploy_account.constructor_calldata.0.iter());
+    Ok(pedersen_hash_array(&[
+        ascii_as_felt("deploy_account"),
+        deploy_account.version.0,
+        contract_address,
+        StarkFelt::default(),
+        pedersen_hash_array(&calldata),
+        StarkFelt::from(deploy_account.max_fee.0),
+        chain_id,
+        deploy_account.nonce.0,
+    ]))
+}
+
+fn invoke_hashes(invoke: &InvokeTransaction, chain_id: StarkFelt) -> Vec<StarkHash> {
+    let prefix = ascii_as_felt("invoke");
+    match invoke {
+        InvokeTransaction::V0(invoke_v0) => {
+            let contract_address = *invoke_v0.contract_address.0.key();
+            let calldata_hash = pedersen_hash_array(&invoke_v0.calldata.0);
+            vec![
+                pedersen_hash_array(&[
+                    prefix,
+                    StarkFelt::from(0_u8),
+                    contract_address,
+                    invoke_v0.entry_point_selector.0,
+                    calldata_hash,
+                    StarkFelt::from(invoke_v0.max_fee.0),
+                    chain_id,
+                ]),
+                // The deprecated calculation, without the version and the fee.
+                pedersen_hash_array(&[
+                    prefix,
+                    contract_address,
+                    invoke_v0.entry_point_selector.0,
+                    calldata_hash,
+                    chain_id,
+                ]),
+            ]
+        }
+        InvokeTransaction::V1(invoke_v1) => vec![pedersen_hash_array(&[
+            prefix,
+            StarkFelt::from(1_u8),
+            *invoke_v1.sender_address.0.key(),
+            StarkFelt::default(),
+            pedersen_hash_array(&invoke_v1.calldata.0),
+            StarkFelt::from(invoke_v1.max_fee.0),
+            chain_id,
+            invoke_v1.nonce.0,
+        ])],
+    }
+}
+
+fn l1_handler_hashes(l1_handler: &L1HandlerTransaction, chain_id: StarkFelt) -> Vec<StarkHash> {
+    let contract_address = *l1_handler.contract_address.0.key();
+    let calldata_hash = pedersen_hash_array(&l1_handler.calldata.0);
+    let zero = StarkFelt::default();
+    vec![
+        pedersen_hash_array(&[
+            ascii_as_felt("l1_handler"),
+            l1_handler.version.0,
+            contract_address,
+            l1_handler.entry_point_selector.0,
+            calldata_hash,
+            zero,
+            chain_id,
+            l1_handler.nonce.0,
+        ]),
+        // Before Starknet 0.8.0 the nonce wasn't part of the hash.
+        pedersen_hash_array(&[
+            ascii_as_felt("l1_handler"),
+            l1_handler.version.0,
+            contract_address,
+            l1_handler.entry_point_selector.0,
+            calldata_hash,
+            zero,
+            chain_id,
+        ]),
+        // Before Starknet 0.7.0 L1 handlers were hashed as deprecated invoke transactions.
+        pedersen_hash_array(&[
+            ascii_as_felt("invoke"),
+            contract_address,
+            l1_handler.entry_point_selector.0,
+            calldata_hash,
+            chain_id,
+        ]),
+    ]
+}
+
+// Encodes a short ASCII string as a field element, as done for the prefixes and the chain id.
+pub(crate) fn ascii_as_felt(ascii: &str) -> StarkFelt {
+    StarkFelt::try_from(format!("0x{}", hex::encode(ascii)).as_str())
+        .expect("A short string should fit in a field element.")
+}
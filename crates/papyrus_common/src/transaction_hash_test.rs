@@ -0,0 +1,65 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+use starknet_api::core::ChainId;
+use starknet_api::transaction::{DeclareTransaction, Transaction, TransactionHash};
+use starknet_crypto::{verify, FieldElement};
+use test_utils::get_absolute_path;
+
+use crate::transaction_hash::{verify_transaction_hash, TransactionHashError};
+
+// Transactions of the test networks together with the hashes the gateways reported for them,
+// except for the declare of version 2, whose hash is checked by its signature.
+#[derive(Deserialize)]
+struct TransactionWithHash {
+    chain_id: ChainId,
+    transaction: Transaction,
+    transaction_hash: TransactionHash,
+}
+
+fn read_transactions() -> Vec<TransactionWithHash> {
+    let path = get_absolute_path("crates/papyrus_common/resources/transaction_hash.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn verify_hashes_of_transactions() {
+    let transactions = read_transactions();
+    assert!(transactions.iter().any(|tx| matches!(tx.transaction, Transaction::Declare(_))));
+    for TransactionWithHash { chain_id, transaction, transaction_hash } in transactions {
+        verify_transaction_hash(&transaction, &transaction_hash, &chain_id).unwrap();
+        assert_matches!(
+            verify_transaction_hash(&transaction, &transaction_hash, &ChainId("SN_MAIN".to_owned())),
+            Err(TransactionHashError::Mismatch { transaction_hash: hash, .. }) if hash == transaction_hash
+        );
+    }
+}
+
+// The declare transactions of version 1 and 2 were sent by the same account. The hash of the
+// declare of version 2 wasn't reported by a gateway, so it's checked by the signature of the
+// account as well, with the key that signed the reported hash of the declare of version 1.
+#[test]
+fn declare_hashes_are_signed_by_sender() {
+    let sender_public_key = FieldElement::from_hex_be(
+        "0x406a640b3b70dad390d661c088df1fbaeb5162a07d57cf29ba794e2b0e3c804",
+    )
+    .unwrap();
+    let mut n_signed_declares = 0;
+    for TransactionWithHash { transaction, transaction_hash, .. } in read_transactions() {
+        let signature = match transaction {
+            Transaction::Declare(DeclareTransaction::V1(declare)) => declare.signature,
+            Transaction::Declare(DeclareTransaction::V2(declare)) => declare.signature,
+            _ => continue,
+        };
+        let [r, s] = signature.0[..] else { panic!("Expecting a signature of two elements.") };
+        assert!(verify(
+            &sender_public_key,
+            &FieldElement::from(transaction_hash.0),
+            &FieldElement::from(r),
+            &FieldElement::from(s)
+        )
+        .unwrap());
+        n_signed_declares += 1;
+    }
+    assert_eq!(n_signed_declares, 2);
+}
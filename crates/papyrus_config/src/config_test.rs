@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::env;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use assert_matches::assert_matches;
@@ -10,7 +11,11 @@ use serde_json::json;
 use test_utils::get_absolute_path;
 
 use crate::command::{get_command_matches, update_config_map_by_command_args};
-use crate::converters::deserialize_milliseconds_to_duration;
+use crate::converters::{
+    deserialize_milliseconds_to_duration, deserialize_optional_number, deserialize_optional_path,
+    deserialize_optional_string, serialize_optional_number, serialize_optional_path,
+    serialize_optional_string,
+};
 use crate::dumping::{
     append_sub_config_name, combine_config_map_and_pointers, ser_param, SerializeConfig,
 };
@@ -135,6 +140,91 @@ fn test_update_dumped_config() {
     assert_eq!(Duration::from_millis(1234), loaded_config.a);
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct OptionalNumberConfig {
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    some_number: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    none_number: Option<u64>,
+}
+
+impl SerializeConfig for OptionalNumberConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param("some_number", &serialize_optional_number(&self.some_number), "Some."),
+            ser_param("none_number", &serialize_optional_number(&self.none_number), "None."),
+        ])
+    }
+}
+
+#[test]
+fn test_optional_number() {
+    let command = Command::new("Testing");
+    let mut dumped_config = OptionalNumberConfig { some_number: Some(5), none_number: None }.dump();
+    assert_eq!(json!("5"), dumped_config["some_number"].value);
+    assert_eq!(json!(""), dumped_config["none_number"].value);
+    let loaded_config: OptionalNumberConfig = load(&dumped_config).unwrap();
+    assert_eq!(loaded_config, OptionalNumberConfig { some_number: Some(5), none_number: None });
+
+    let args = vec!["Testing", "--none_number", "7"];
+    let args: Vec<String> = args.into_iter().map(|s| s.to_owned()).collect();
+    let arg_matches = get_command_matches(&dumped_config, command, args).unwrap();
+    update_config_map_by_command_args(&mut dumped_config, &arg_matches).unwrap();
+    let loaded_config: OptionalNumberConfig = load(&dumped_config).unwrap();
+    assert_eq!(loaded_config.none_number, Some(7));
+
+    dumped_config.get_mut("some_number").unwrap().value = json!("five");
+    assert!(load::<OptionalNumberConfig>(&dumped_config).is_err());
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct OptionalStringConfig {
+    #[serde(deserialize_with = "deserialize_optional_string")]
+    some_string: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_string")]
+    none_string: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_path")]
+    some_path: Option<PathBuf>,
+    #[serde(deserialize_with = "deserialize_optional_path")]
+    none_path: Option<PathBuf>,
+}
+
+impl SerializeConfig for OptionalStringConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param("some_string", &serialize_optional_string(&self.some_string), "Some."),
+            ser_param("none_string", &serialize_optional_string(&self.none_string), "None."),
+            ser_param("some_path", &serialize_optional_path(&self.some_path), "Some."),
+            ser_param("none_path", &serialize_optional_path(&self.none_path), "None."),
+        ])
+    }
+}
+
+#[test]
+fn test_optional_string_and_path() {
+    let config = OptionalStringConfig {
+        some_string: Some("a string".to_owned()),
+        none_string: None,
+        some_path: Some(PathBuf::from("/a/path.json")),
+        none_path: None,
+    };
+    let mut dumped_config = config.dump();
+    assert_eq!(json!("a string"), dumped_config["some_string"].value);
+    assert_eq!(json!(""), dumped_config["none_string"].value);
+    assert_eq!(json!("/a/path.json"), dumped_config["some_path"].value);
+    assert_eq!(json!(""), dumped_config["none_path"].value);
+    let loaded_config: OptionalStringConfig = load(&dumped_config).unwrap();
+    assert_eq!(loaded_config, config);
+
+    let args = vec!["Testing", "--none_string", "set", "--none_path", "relative/path"];
+    let args: Vec<String> = args.into_iter().map(|s| s.to_owned()).collect();
+    let arg_matches = get_command_matches(&dumped_config, Command::new("Testing"), args).unwrap();
+    update_config_map_by_command_args(&mut dumped_config, &arg_matches).unwrap();
+    let loaded_config: OptionalStringConfig = load(&dumped_config).unwrap();
+    assert_eq!(loaded_config.none_string, Some("set".to_owned()));
+    assert_eq!(loaded_config.none_path, Some(PathBuf::from("relative/path")));
+}
+
 #[test]
 fn test_pointers_flow() {
     let config_map = BTreeMap::from([
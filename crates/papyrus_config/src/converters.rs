@@ -32,6 +32,9 @@
 //! ```
 
 use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
 use serde::de::Error;
@@ -77,3 +80,58 @@ where
     }
     Ok(Some(map))
 }
+
+/// Serializes an optional number to a string, where None is an empty string.
+pub fn serialize_optional_number<T: Display>(optional_number: &Option<T>) -> String {
+    match optional_number {
+        None => "".to_owned(),
+        Some(number) => number.to_string(),
+    }
+}
+
+/// Deserializes an optional number from a string, where an empty string is None.
+pub fn deserialize_optional_number<'de, D, T>(de: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    if raw_str.is_empty() {
+        return Ok(None);
+    }
+    raw_str.parse().map(Some).map_err(D::Error::custom)
+}
+
+/// Serializes an optional string to a string, where None is an empty string.
+pub fn serialize_optional_string(optional_string: &Option<String>) -> String {
+    optional_string.clone().unwrap_or_default()
+}
+
+/// Deserializes an optional string from a string, where an empty string is None.
+pub fn deserialize_optional_string<'de, D>(de: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    if raw_str.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(raw_str))
+}
+
+/// Serializes an optional path to a string, where None is an empty string.
+pub fn serialize_optional_path(optional_path: &Option<PathBuf>) -> String {
+    match optional_path {
+        None => "".to_owned(),
+        Some(path) => path.display().to_string(),
+    }
+}
+
+/// Deserializes an optional path from a string, where an empty string is None.
+pub fn deserialize_optional_path<'de, D>(de: D) -> Result<Option<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(deserialize_optional_string(de)?.map(PathBuf::from))
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use indexmap::IndexMap;
+use papyrus_storage::body::BodyStorageWriter;
+use papyrus_storage::compiled_class::CasmStorageWriter;
+use papyrus_storage::header::{HeaderStorageWriter, StarknetVersion};
+use papyrus_storage::state::StateStorageWriter;
+use papyrus_storage::test_utils::get_test_storage;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{Block, BlockHash, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::state::{StateDiff, ThinStateDiff};
+use starknet_client::reader::{GenericContractClass, StarknetFeederGatewayClient, StarknetReader};
+use starknet_client::{RetryConfig, StarknetError, StarknetErrorCode};
+use tempfile::TempDir;
+use test_utils::{get_test_block, get_test_state_diff};
+
+use crate::app;
+
+const NODE_VERSION: &str = "NODE VERSION";
+const STARKNET_VERSION: &str = "0.12.0";
+
+// Stores a block with its state diff and the compiled classes of its declared classes, and serves
+// the storage. Returns the URL of the server along with the stored data.
+async fn setup() -> (String, Block, StateDiff, TempDir) {
+    let ((storage_reader, mut storage_writer), temp_dir) = get_test_storage();
+    let mut block = get_test_block(4, Some(2), None, None);
+    block.header.block_hash = BlockHash(stark_felt!("0x1"));
+    let state_diff = get_test_state_diff();
+    let mut txn = storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &block.header)
+        .unwrap()
+        .update_starknet_version(&BlockNumber(0), &StarknetVersion(STARKNET_VERSION.to_owned()))
+        .unwrap()
+        .append_body(BlockNumber(0), block.body.clone())
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff.clone(), IndexMap::new())
+        .unwrap();
+    for class_hash in state_diff.declared_classes.keys() {
+        txn = txn.append_casm(class_hash, &CasmContractClass::default()).unwrap();
+    }
+    txn.commit().unwrap();
+
+    let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .serve(app(storage_reader).into_make_service());
+    let url = format!("http://{}/", server.local_addr());
+    tokio::spawn(server);
+    (url, block, state_diff, temp_dir)
+}
+
+fn client(url: &str) -> StarknetFeederGatewayClient {
+    let retry_config = RetryConfig {
+        retry_base_millis: 1,
+        retry_max_delay_millis: 10,
+        max_retries: 1,
+        jitter: false,
+        endpoint_max_retries: HashMap::new(),
+        circuit_breaker_failure_threshold: 0,
+        circuit_breaker_cooldown_millis: 0,
+    };
+    StarknetFeederGatewayClient::new(url, None, NODE_VERSION, retry_config).unwrap()
+}
+
+#[tokio::test]
+async fn serve_block() {
+    let (url, block, _state_diff, _temp_dir) = setup().await;
+    let client = client(&url);
+
+    assert_eq!(client.block_number().await.unwrap(), Some(BlockNumber(0)));
+    let served_block = client.block(BlockNumber(0)).await.unwrap().unwrap();
+    assert_eq!(
+        client.block_by_hash(block.header.block_hash).await.unwrap(),
+        Some(served_block.clone())
+    );
+    // The served block converts to the stored one.
+    let (served_block, starknet_version) =
+        served_block.to_starknet_api_block_and_version().unwrap();
+    assert_eq!(served_block, block);
+    assert_eq!(starknet_version, STARKNET_VERSION);
+
+    assert!(client.block(BlockNumber(1)).await.unwrap().is_none());
+    assert!(client.pending_block().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn serve_state_update_and_classes() {
+    let (url, block, state_diff, _temp_dir) = setup().await;
+    let client = client(&url);
+
+    let state_update = client.state_update(BlockNumber(0)).await.unwrap().unwrap();
+    assert_eq!(state_update.block_hash, block.header.block_hash);
+    assert_eq!(state_update.new_root.0, block.header.state_root.0);
+    let (expected_state_diff, _, _) = ThinStateDiff::from_state_diff(state_diff.clone());
+    assert_eq!(state_update.state_diff.nonces, expected_state_diff.nonces);
+    assert_eq!(
+        state_update.state_diff.old_declared_contracts,
+        expected_state_diff.deprecated_declared_classes
+    );
+    let state_update_with_block =
+        client.state_update_with_block(BlockNumber(0)).await.unwrap().unwrap();
+    assert_eq!(state_update_with_block.state_update, state_update);
+    assert_eq!(state_update_with_block.block.block_hash, block.header.block_hash);
+    assert!(client.state_update(BlockNumber(1)).await.unwrap().is_none());
+
+    for (class_hash, (_compiled_class_hash, class)) in &state_diff.declared_classes {
+        let GenericContractClass::Cairo1ContractClass(served_class) =
+            client.class_by_hash(*class_hash).await.unwrap().unwrap()
+        else {
+            panic!("Class {class_hash} should be a Cairo 1 class.");
+        };
+        assert_eq!(starknet_api::state::ContractClass::from(served_class), *class);
+        let compiled_class = client.compiled_class_by_hash(*class_hash).await.unwrap().unwrap();
+        assert_eq!(compiled_class, CasmContractClass::default());
+    }
+    for (class_hash, class) in &state_diff.deprecated_declared_classes {
+        let GenericContractClass::Cairo0ContractClass(served_class) =
+            client.class_by_hash(*class_hash).await.unwrap().unwrap()
+        else {
+            panic!("Class {class_hash} should be a Cairo 0 class.");
+        };
+        assert_eq!(served_class, *class);
+    }
+
+    let undeclared_class_hash = ClassHash(stark_felt!("0xdead"));
+    assert!(client.class_by_hash(undeclared_class_hash).await.unwrap().is_none());
+    assert!(client.compiled_class_by_hash(undeclared_class_hash).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn malformed_request() {
+    let (url, _block, _state_diff, _temp_dir) = setup().await;
+    let response =
+        reqwest::get(format!("{url}feeder_gateway/get_block?blockNumber=first")).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let error: StarknetError = response.json().await.unwrap();
+    assert_eq!(error.code, StarknetErrorCode::MalformedRequest);
+}
@@ -0,0 +1,289 @@
+//! A server of the [`Starknet`] feeder gateway API backed by the storage of a Papyrus node, so
+//! that other nodes and clients can sync from a Papyrus node instead of the feeder gateway.
+//!
+//! The server serves the blocks, the state updates and the classes that are stored in the node,
+//! in the shapes of the objects returned by the feeder gateway. Data that Papyrus doesn't store,
+//! such as the execution resources of the transactions, is returned with default values.
+//!
+//! [`Starknet`]: https://starknet.io/
+
+#[cfg(test)]
+mod feeder_gateway_test;
+mod objects;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use papyrus_config::dumping::{ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, SerializedParam};
+use papyrus_storage::body::BodyStorageReader;
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageError, StorageReader};
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::hash::StarkHash;
+use starknet_api::state::StateNumber;
+use starknet_client::reader::{Block, GenericContractClass, StateUpdateWithBlock};
+use starknet_client::{StarknetError, StarknetErrorCode};
+use tracing::{debug, instrument};
+
+const FEEDER_GATEWAY_PREFIX: &str = "feeder_gateway";
+const BLOCK_NUMBER_QUERY: &str = "blockNumber";
+const BLOCK_HASH_QUERY: &str = "blockHash";
+const CLASS_HASH_QUERY: &str = "classHash";
+const INCLUDE_BLOCK_QUERY: &str = "includeBlock";
+const LATEST_BLOCK_NUMBER: &str = "latest";
+const PENDING_BLOCK_NUMBER: &str = "pending";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FeederGatewayConfig {
+    pub run_server: bool,
+    pub server_address: String,
+}
+
+impl Default for FeederGatewayConfig {
+    fn default() -> Self {
+        FeederGatewayConfig { run_server: false, server_address: String::from("0.0.0.0:8082") }
+    }
+}
+
+impl SerializeConfig for FeederGatewayConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "run_server",
+                &self.run_server,
+                "If true, serves the stored data with the Starknet feeder-gateway API, so that \
+                 other nodes can sync from this node.",
+            ),
+            ser_param("server_address", &self.server_address, "node's feeder-gateway server."),
+        ])
+    }
+}
+
+impl Display for FeederGatewayConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+pub struct FeederGatewayServer {
+    config: FeederGatewayConfig,
+    storage_reader: StorageReader,
+}
+
+impl FeederGatewayServer {
+    pub fn new(config: FeederGatewayConfig, storage_reader: StorageReader) -> Self {
+        FeederGatewayServer { config, storage_reader }
+    }
+
+    /// Spawns a feeder gateway server that stops gracefully once the shutdown signal resolves. The
+    /// spawned task finishes immediately if the server isn't configured to run.
+    pub async fn spawn_server(
+        self,
+        shutdown_signal: impl Future<Output = ()> + Send + 'static,
+    ) -> tokio::task::JoinHandle<Result<(), hyper::Error>> {
+        tokio::spawn(async move { self.run_server(shutdown_signal).await })
+    }
+
+    #[instrument(skip(self, shutdown_signal), fields(config = %self.config), level = "debug")]
+    async fn run_server(
+        &self,
+        shutdown_signal: impl Future<Output = ()>,
+    ) -> std::result::Result<(), hyper::Error> {
+        if !self.config.run_server {
+            return Ok(());
+        }
+        let server_address = SocketAddr::from_str(&self.config.server_address)
+            .expect("Configuration value for feeder gateway server address should be valid");
+        debug!("Starting feeder gateway.");
+        axum::Server::bind(&server_address)
+            .serve(app(self.storage_reader.clone()).into_make_service())
+            .with_graceful_shutdown(shutdown_signal)
+            .await
+    }
+}
+
+fn app(storage_reader: StorageReader) -> Router {
+    Router::new()
+        .route(format!("/{FEEDER_GATEWAY_PREFIX}/get_block").as_str(), get(get_block))
+        .route(format!("/{FEEDER_GATEWAY_PREFIX}/get_state_update").as_str(), get(get_state_update))
+        .route(
+            format!("/{FEEDER_GATEWAY_PREFIX}/get_class_by_hash").as_str(),
+            get(get_class_by_hash),
+        )
+        .route(
+            format!("/{FEEDER_GATEWAY_PREFIX}/get_compiled_class_by_class_hash").as_str(),
+            get(get_compiled_class_by_class_hash),
+        )
+        .with_state(storage_reader)
+}
+
+/// Returns the block with the requested number or hash, or the latest block whose body is stored.
+#[instrument(skip(storage_reader), level = "debug", err)]
+async fn get_block(
+    State(storage_reader): State<StorageReader>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<Block>, ServerError> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let block_number = match block_id(&query)? {
+        BlockId::Hash(block_hash) => {
+            txn.get_block_number_by_hash(&block_hash)?.ok_or_else(|| {
+                block_not_found(format!("Block with hash {block_hash} was not found."))
+            })?
+        }
+        block_id => block_id.resolve(txn.get_body_marker()?)?,
+    };
+    let block = objects::get_block(&txn, block_number)?
+        .ok_or_else(|| block_not_found(format!("Block {block_number} was not found.")))?;
+    Ok(block.into())
+}
+
+/// Returns the state update of the block with the requested number, or of the latest block whose
+/// state diff is stored. The block is returned along with the state update if requested.
+#[instrument(skip(storage_reader), level = "debug", err)]
+async fn get_state_update(
+    State(storage_reader): State<StorageReader>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, ServerError> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let block_number = block_id(&query)?.resolve(txn.get_state_marker()?)?;
+    let not_found =
+        || block_not_found(format!("State update of block {block_number} was not found."));
+    let state_update = objects::get_state_update(&txn, block_number)?.ok_or_else(not_found)?;
+    if query.get(INCLUDE_BLOCK_QUERY).map(String::as_str) != Some("true") {
+        return Ok(Json(state_update).into_response());
+    }
+    let block = objects::get_block(&txn, block_number)?.ok_or_else(not_found)?;
+    Ok(Json(StateUpdateWithBlock { block, state_update }).into_response())
+}
+
+/// Returns the definition of the class in the state after the requested block, or in the latest
+/// stored state.
+#[instrument(skip(storage_reader), level = "debug", err)]
+async fn get_class_by_hash(
+    State(storage_reader): State<StorageReader>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<GenericContractClass>, ServerError> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let class_hash = class_hash(&query)?;
+    let block_number = block_id(&query)?.resolve(txn.get_state_marker()?)?;
+    objects::get_class(&txn, StateNumber::right_after_block(block_number), class_hash)?
+        .map(Json)
+        .ok_or_else(|| undeclared_class(class_hash))
+}
+
+/// Returns the Cairo assembly of the class.
+#[instrument(skip(storage_reader), level = "debug", err)]
+async fn get_compiled_class_by_class_hash(
+    State(storage_reader): State<StorageReader>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, ServerError> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let class_hash = class_hash(&query)?;
+    let compiled_class = txn.get_casm(&class_hash)?.ok_or_else(|| undeclared_class(class_hash))?;
+    Ok(Json(compiled_class).into_response())
+}
+
+// The block requested by the query parameters. The block number is the latest if neither the block
+// number nor the block hash are given.
+#[derive(Debug)]
+enum BlockId {
+    Hash(BlockHash),
+    Number(BlockNumber),
+    Latest,
+    Pending,
+}
+
+impl BlockId {
+    // Returns the number of the requested block, given the first block whose data isn't stored.
+    fn resolve(self, marker: BlockNumber) -> Result<BlockNumber, ServerError> {
+        match self {
+            BlockId::Number(block_number) => Ok(block_number),
+            BlockId::Latest => {
+                marker.prev().ok_or_else(|| block_not_found("There are no blocks.".to_owned()))
+            }
+            BlockId::Pending => {
+                Err(block_not_found("The pending block is not served by this node.".to_owned()))
+            }
+            BlockId::Hash(_) => {
+                Err(malformed_request(format!("{BLOCK_HASH_QUERY} is not supported.")))
+            }
+        }
+    }
+}
+
+fn block_id(query: &HashMap<String, String>) -> Result<BlockId, ServerError> {
+    if let Some(block_hash) = query.get(BLOCK_HASH_QUERY) {
+        return Ok(BlockId::Hash(BlockHash(parse_hash(BLOCK_HASH_QUERY, block_hash)?)));
+    }
+    match query.get(BLOCK_NUMBER_QUERY).map(String::as_str) {
+        None | Some(LATEST_BLOCK_NUMBER) => Ok(BlockId::Latest),
+        Some(PENDING_BLOCK_NUMBER) => Ok(BlockId::Pending),
+        Some(block_number) => block_number
+            .parse()
+            .map(|block_number| BlockId::Number(BlockNumber(block_number)))
+            .map_err(|_| {
+                malformed_request(format!("Invalid {BLOCK_NUMBER_QUERY}: {block_number}."))
+            }),
+    }
+}
+
+fn class_hash(query: &HashMap<String, String>) -> Result<ClassHash, ServerError> {
+    let class_hash = query
+        .get(CLASS_HASH_QUERY)
+        .ok_or_else(|| malformed_request(format!("Missing {CLASS_HASH_QUERY}.")))?;
+    Ok(ClassHash(parse_hash(CLASS_HASH_QUERY, class_hash)?))
+}
+
+fn parse_hash(query_name: &str, value: &str) -> Result<StarkHash, ServerError> {
+    StarkHash::try_from(value)
+        .map_err(|_| malformed_request(format!("Invalid {query_name}: {value}.")))
+}
+
+fn block_not_found(message: String) -> ServerError {
+    StarknetError { code: StarknetErrorCode::BlockNotFound, message }.into()
+}
+
+fn undeclared_class(class_hash: ClassHash) -> ServerError {
+    StarknetError {
+        code: StarknetErrorCode::UndeclaredClass,
+        message: format!("Class with hash {class_hash} is not declared."),
+    }
+    .into()
+}
+
+fn malformed_request(message: String) -> ServerError {
+    StarknetError { code: StarknetErrorCode::MalformedRequest, message }.into()
+}
+
+#[derive(thiserror::Error, Debug)]
+enum ServerError {
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    /// An error returned to the client in the format of the feeder gateway errors.
+    #[error(transparent)]
+    StarknetError(#[from] StarknetError),
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        match self {
+            ServerError::StorageError(err) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+            }
+            ServerError::StarknetError(err) => (StatusCode::BAD_REQUEST, Json(err)).into_response(),
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Conversions of the stored data to the objects returned by the feeder gateway.
+
+use papyrus_storage::body::events::ThinTransactionOutput;
+use papyrus_storage::body::{BodyStorageReader, TransactionIndex};
+use papyrus_storage::db::RO;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageResult, StorageTxn};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::{StateNumber, ThinStateDiff};
+use starknet_api::transaction::{
+    DeclareTransaction, EthAddress, Event, Fee, InvokeTransaction, L1ToL2Payload, MessageToL1,
+    Transaction, TransactionExecutionStatus, TransactionHash, TransactionOffsetInBlock,
+    TransactionVersion,
+};
+use starknet_client::reader::{
+    Block, BlockStatus, ContractClass, DeclaredClassHashEntry, DeployAccountTransaction,
+    DeployTransaction, DeployedContract, GenericContractClass, GlobalRoot,
+    IntermediateDeclareTransaction, IntermediateInvokeTransaction, L1HandlerTransaction,
+    L1ToL2Message, L1ToL2Nonce, L2ToL1Message, ReplacedClass, StateDiff, StateUpdate, StorageEntry,
+    Transaction as ClientTransaction, TransactionReceipt,
+};
+
+// Papyrus doesn't store the version of the Sierra classes, and all the classes declared so far have
+// this version.
+const CONTRACT_CLASS_VERSION: &str = "0.1.0";
+
+/// Returns the block, or None if its header or body aren't stored.
+pub(crate) fn get_block(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+) -> StorageResult<Option<Block>> {
+    let Some(header) = txn.get_block_header(block_number)? else {
+        return Ok(None);
+    };
+    let (Some(transactions), Some(transaction_outputs), Some(transaction_hashes)) = (
+        txn.get_block_transactions(block_number)?,
+        txn.get_block_transaction_outputs(block_number)?,
+        txn.get_block_transaction_hashes(block_number)?,
+    ) else {
+        return Ok(None);
+    };
+
+    let mut client_transactions = Vec::with_capacity(transactions.len());
+    let mut transaction_receipts = Vec::with_capacity(transactions.len());
+    for (offset, (((transaction, execution_status), output), transaction_hash)) in
+        transactions.into_iter().zip(transaction_outputs).zip(transaction_hashes).enumerate()
+    {
+        let transaction_index = TransactionIndex(block_number, TransactionOffsetInBlock(offset));
+        let events = txn.get_transaction_events(transaction_index)?.unwrap_or_default();
+        transaction_receipts.push(transaction_receipt(
+            &transaction,
+            transaction_index.1,
+            transaction_hash,
+            &output,
+            events,
+            execution_status,
+        ));
+        client_transactions.push(client_transaction(transaction, transaction_hash, &output));
+    }
+
+    Ok(Some(Block {
+        block_hash: header.block_hash,
+        block_number,
+        gas_price: header.gas_price,
+        parent_block_hash: header.parent_hash,
+        sequencer_address: header.sequencer,
+        state_root: GlobalRoot(header.state_root.0),
+        // Papyrus doesn't track whether the blocks were accepted on L1.
+        status: BlockStatus::AcceptedOnL2,
+        timestamp: header.timestamp,
+        transactions: client_transactions,
+        transaction_receipts,
+        starknet_version: txn
+            .get_starknet_version(block_number)?
+            .map(|version| version.0)
+            .unwrap_or_default(),
+    }))
+}
+
+/// Returns the state update of the block, or None if its header or state diff aren't stored.
+pub(crate) fn get_state_update(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+) -> StorageResult<Option<StateUpdate>> {
+    let (Some(header), Some(state_diff)) =
+        (txn.get_block_header(block_number)?, txn.get_state_diff(block_number)?)
+    else {
+        return Ok(None);
+    };
+    let old_root = match block_number.prev() {
+        Some(prev_block_number) => txn
+            .get_block_header(prev_block_number)?
+            .map(|prev_header| GlobalRoot(prev_header.state_root.0))
+            .unwrap_or_default(),
+        None => GlobalRoot::default(),
+    };
+    Ok(Some(StateUpdate {
+        block_hash: header.block_hash,
+        new_root: GlobalRoot(header.state_root.0),
+        old_root,
+        state_diff: client_state_diff(state_diff),
+    }))
+}
+
+/// Returns the definition of the class in the given state, or None if it isn't declared there.
+pub(crate) fn get_class(
+    txn: &StorageTxn<'_, RO>,
+    state_number: StateNumber,
+    class_hash: ClassHash,
+) -> StorageResult<Option<GenericContractClass>> {
+    let state_reader = txn.get_state_reader()?;
+    if let Some(class) = state_reader.get_class_definition_at(state_number, &class_hash)? {
+        return Ok(Some(GenericContractClass::Cairo1ContractClass(ContractClass {
+            sierra_program: class.sierra_program,
+            entry_points_by_type: class.entry_point_by_type,
+            contract_class_version: CONTRACT_CLASS_VERSION.to_owned(),
+            abi: class.abi,
+        })));
+    }
+    Ok(state_reader
+        .get_deprecated_class_definition_at(state_number, &class_hash)?
+        .map(GenericContractClass::Cairo0ContractClass))
+}
+
+fn client_state_diff(state_diff: ThinStateDiff) -> StateDiff {
+    StateDiff {
+        storage_diffs: state_diff
+            .storage_diffs
+            .into_iter()
+            .map(|(address, entries)| {
+                let entries =
+                    entries.into_iter().map(|(key, value)| StorageEntry { key, value }).collect();
+                (address, entries)
+            })
+            .collect(),
+        deployed_contracts: state_diff
+            .deployed_contracts
+            .into_iter()
+            .map(|(address, class_hash)| DeployedContract { address, class_hash })
+            .collect(),
+        declared_classes: state_diff
+            .declared_classes
+            .into_iter()
+            .map(|(class_hash, compiled_class_hash)| DeclaredClassHashEntry {
+                class_hash,
+                compiled_class_hash,
+            })
+            .collect(),
+        old_declared_contracts: state_diff.deprecated_declared_classes,
+        nonces: state_diff.nonces,
+        replaced_classes: state_diff
+            .replaced_classes
+            .into_iter()
+            .map(|(address, class_hash)| ReplacedClass { address, class_hash })
+            .collect(),
+    }
+}
+
+fn client_transaction(
+    transaction: Transaction,
+    transaction_hash: TransactionHash,
+    output: &ThinTransactionOutput,
+) -> ClientTransaction {
+    match transaction {
+        Transaction::Declare(declare_tx) => {
+            let (version, compiled_class_hash, tx) = match declare_tx {
+                DeclareTransaction::V0(tx) => (0, None, tx),
+                DeclareTransaction::V1(tx) => (1, None, tx),
+                DeclareTransaction::V2(tx) => (
+                    2,
+                    Some(tx.compiled_class_hash),
+                    starknet_api::transaction::DeclareTransactionV0V1 {
+                        max_fee: tx.max_fee,
+                        signature: tx.signature,
+                        nonce: tx.nonce,
+                        class_hash: tx.class_hash,
+                        sender_address: tx.sender_address,
+                    },
+                ),
+            };
+            ClientTransaction::Declare(IntermediateDeclareTransaction {
+                class_hash: tx.class_hash,
+                compiled_class_hash,
+                sender_address: tx.sender_address,
+                nonce: tx.nonce,
+                max_fee: tx.max_fee,
+                version: transaction_version(version),
+                transaction_hash,
+                signature: tx.signature,
+            })
+        }
+        Transaction::Deploy(tx) => ClientTransaction::Deploy(DeployTransaction {
+            contract_address: deployed_contract_address(output),
+            contract_address_salt: tx.contract_address_salt,
+            class_hash: tx.class_hash,
+            constructor_calldata: tx.constructor_calldata,
+            transaction_hash,
+            version: tx.version,
+        }),
+        Transaction::DeployAccount(tx) => {
+            ClientTransaction::DeployAccount(DeployAccountTransaction {
+                contract_address: deployed_contract_address(output),
+                contract_address_salt: tx.contract_address_salt,
+                class_hash: tx.class_hash,
+                constructor_calldata: tx.constructor_calldata,
+                nonce: tx.nonce,
+                max_fee: tx.max_fee,
+                signature: tx.signature,
+                transaction_hash,
+                version: tx.version,
+            })
+        }
+        Transaction::Invoke(InvokeTransaction::V0(tx)) => {
+            ClientTransaction::Invoke(IntermediateInvokeTransaction {
+                calldata: tx.calldata,
+                sender_address: tx.contract_address,
+                entry_point_selector: Some(tx.entry_point_selector),
+                nonce: None,
+                max_fee: tx.max_fee,
+                signature: tx.signature,
+                transaction_hash,
+                version: transaction_version(0),
+            })
+        }
+        Transaction::Invoke(InvokeTransaction::V1(tx)) => {
+            ClientTransaction::Invoke(IntermediateInvokeTransaction {
+                calldata: tx.calldata,
+                sender_address: tx.sender_address,
+                entry_point_selector: None,
+                nonce: Some(tx.nonce),
+                max_fee: tx.max_fee,
+                signature: tx.signature,
+                transaction_hash,
+                version: transaction_version(1),
+            })
+        }
+        Transaction::L1Handler(tx) => ClientTransaction::L1Handler(L1HandlerTransaction {
+            transaction_hash,
+            version: tx.version,
+            nonce: tx.nonce,
+            contract_address: tx.contract_address,
+            entry_point_selector: tx.entry_point_selector,
+            calldata: tx.calldata,
+        }),
+    }
+}
+
+fn transaction_receipt(
+    transaction: &Transaction,
+    transaction_index: TransactionOffsetInBlock,
+    transaction_hash: TransactionHash,
+    output: &ThinTransactionOutput,
+    events: Vec<Event>,
+    execution_status: TransactionExecutionStatus,
+) -> TransactionReceipt {
+    let (actual_fee, messages_sent) = fee_and_messages(output);
+    let l1_to_l2_consumed_message = match transaction {
+        Transaction::L1Handler(tx) => l1_to_l2_consumed_message(tx),
+        _ => L1ToL2Message::default(),
+    };
+    TransactionReceipt {
+        transaction_index,
+        transaction_hash,
+        l1_to_l2_consumed_message,
+        l2_to_l1_messages: messages_sent
+            .iter()
+            .map(|message| L2ToL1Message {
+                from_address: message.from_address,
+                to_address: message.to_address,
+                payload: message.payload.clone(),
+            })
+            .collect(),
+        events,
+        // Papyrus doesn't store the execution resources.
+        execution_resources: Default::default(),
+        actual_fee,
+        execution_status,
+    }
+}
+
+// The message of an L1 handler transaction is derived from the transaction: the first element of
+// the calldata is the sender of the message on L1 and the rest is the payload.
+fn l1_to_l2_consumed_message(
+    tx: &starknet_api::transaction::L1HandlerTransaction,
+) -> L1ToL2Message {
+    let Some((from_address, payload)) = tx.calldata.0.split_first() else {
+        return L1ToL2Message::default();
+    };
+    L1ToL2Message {
+        from_address: EthAddress::try_from(*from_address).unwrap_or_default(),
+        to_address: tx.contract_address,
+        selector: tx.entry_point_selector,
+        payload: L1ToL2Payload(payload.to_vec()),
+        nonce: L1ToL2Nonce(tx.nonce.0),
+    }
+}
+
+fn fee_and_messages(output: &ThinTransactionOutput) -> (Fee, &Vec<MessageToL1>) {
+    match output {
+        ThinTransactionOutput::Declare(output) => (output.actual_fee, &output.messages_sent),
+        ThinTransactionOutput::Deploy(output) => (output.actual_fee, &output.messages_sent),
+        ThinTransactionOutput::DeployAccount(output) => (output.actual_fee, &output.messages_sent),
+        ThinTransactionOutput::Invoke(output) => (output.actual_fee, &output.messages_sent),
+        ThinTransactionOutput::L1Handler(output) => (output.actual_fee, &output.messages_sent),
+    }
+}
+
+fn deployed_contract_address(output: &ThinTransactionOutput) -> ContractAddress {
+    match output {
+        ThinTransactionOutput::Deploy(output) => output.contract_address,
+        ThinTransactionOutput::DeployAccount(output) => output.contract_address,
+        _ => ContractAddress::default(),
+    }
+}
+
+fn transaction_version(version: u128) -> TransactionVersion {
+    TransactionVersion(StarkFelt::from(version))
+}
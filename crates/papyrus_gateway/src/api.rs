@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use jsonrpsee::{Methods, RpcModule};
+use papyrus_common::pending_data::PendingData;
 use papyrus_common::SyncingState;
 use papyrus_storage::StorageReader;
 use serde::{Deserialize, Serialize};
@@ -91,6 +92,7 @@ pub fn get_methods_from_supported_apis(
     max_events_chunk_size: usize,
     max_events_keys: usize,
     shared_syncing_state: Arc<RwLock<SyncingState>>,
+    pending_data: Arc<RwLock<PendingData>>,
 ) -> Methods {
     let mut methods: Methods = Methods::new();
     version_config::VERSION_CONFIG
@@ -106,6 +108,7 @@ pub fn get_methods_from_supported_apis(
                         max_events_chunk_size,
                         max_events_keys,
                         shared_syncing_state: shared_syncing_state.clone(),
+                        pending_data: pending_data.clone(),
                     }),
                     _ => None,
                 },
@@ -126,6 +129,7 @@ pub trait JsonRpcServerImpl: Sized {
         max_events_chunk_size: usize,
         max_events_keys: usize,
         shared_syncing_state: Arc<RwLock<SyncingState>>,
+        pending_data: Arc<RwLock<PendingData>>,
     ) -> Self;
 
     fn into_rpc_module(self) -> RpcModule<Self>;
@@ -18,7 +18,7 @@ use tower::BoxError;
 
 use crate::api::JsonRpcError;
 use crate::middleware::proxy_rpc_request;
-use crate::test_utils::{get_test_gateway_config, get_test_syncing_state};
+use crate::test_utils::{get_test_gateway_config, get_test_pending_data, get_test_syncing_state};
 use crate::version_config::VERSION_CONFIG;
 use crate::{get_block_status, run_server, SERVER_MAX_BODY_SIZE};
 
@@ -28,7 +28,9 @@ async fn run_server_no_blocks() {
     let gateway_config = get_test_gateway_config();
     let shared_syncing_state = get_test_syncing_state();
     let (addr, _handle) =
-        run_server(&gateway_config, shared_syncing_state, storage_reader).await.unwrap();
+        run_server(&gateway_config, shared_syncing_state, get_test_pending_data(), storage_reader)
+            .await
+            .unwrap();
     let client = HttpClientBuilder::default().build(format!("http://{addr:?}")).unwrap();
     let res: Result<RpcResult<BlockNumber>, Error> =
         client.request("starknet_blockNumber", [""]).await;
@@ -4,6 +4,7 @@ mod gateway_metrics;
 #[cfg(test)]
 mod gateway_test;
 mod middleware;
+mod pending;
 #[cfg(test)]
 mod test_utils;
 mod transaction;
@@ -17,13 +18,16 @@ use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use gateway_metrics::MetricLogger;
 use jsonrpsee::server::{ServerBuilder, ServerHandle};
 use jsonrpsee::types::error::ErrorCode::InternalError;
 use jsonrpsee::types::error::INTERNAL_ERROR_MSG;
 use jsonrpsee::types::ErrorObjectOwned;
+use papyrus_common::pending_data::PendingData;
 use papyrus_common::SyncingState;
+use papyrus_config::converters::deserialize_milliseconds_to_duration;
 use papyrus_config::dumping::{ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, SerializedParam};
 use papyrus_storage::base_layer::BaseLayerStorageReader;
@@ -52,6 +56,8 @@ pub struct GatewayConfig {
     pub max_events_chunk_size: usize,
     pub max_events_keys: usize,
     pub collect_metrics: bool,
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub shutdown_timeout: Duration,
 }
 
 impl Default for GatewayConfig {
@@ -62,6 +68,7 @@ impl Default for GatewayConfig {
             max_events_chunk_size: 1000,
             max_events_keys: 100,
             collect_metrics: false,
+            shutdown_timeout: Duration::from_secs(10),
         }
     }
 }
@@ -74,6 +81,7 @@ impl SerializeConfig for GatewayConfig {
             ser_param("max_events_chunk_size", &self.max_events_chunk_size, "Maximum chunk size supported by the node in get_events requests."),
             ser_param("max_events_keys", &self.max_events_keys, "Maximum number of keys supported by the node in get_events requests."),
             ser_param("collect_metrics", &self.collect_metrics, "If true, collect metrics for the gateway."),
+            ser_param("shutdown_timeout", &self.shutdown_timeout.as_millis(), "Maximum time in milliseconds to wait for in-flight requests to finish when the node shuts down."),
         ])
     }
 }
@@ -109,8 +117,10 @@ fn get_block_number<Mode: TransactionKind>(
         }
         BlockId::Tag(Tag::Latest) => get_latest_block_number(txn)?
             .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::BlockNotFound))?,
+        // The pending block isn't in the storage. Methods that support it handle it before
+        // getting here.
         BlockId::Tag(Tag::Pending) => {
-            todo!("Pending tag is not supported yet.")
+            return Err(ErrorObjectOwned::from(JsonRpcError::BlockNotFound));
         }
     })
 }
@@ -153,6 +163,7 @@ impl ContinuationToken {
 pub async fn run_server(
     config: &GatewayConfig,
     shared_syncing_state: Arc<RwLock<SyncingState>>,
+    pending_data: Arc<RwLock<PendingData>>,
     storage_reader: StorageReader,
 ) -> anyhow::Result<(SocketAddr, ServerHandle)> {
     debug!("Starting gateway.");
@@ -162,6 +173,7 @@ pub async fn run_server(
         config.max_events_chunk_size,
         config.max_events_keys,
         shared_syncing_state,
+        pending_data,
     );
     let addr;
     let handle;
@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+
+use jsonrpsee::types::ErrorObjectOwned;
+use papyrus_common::pending_data::{PendingBlock, PendingData};
+use papyrus_storage::db::TransactionKind;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateReader;
+use papyrus_storage::StorageTxn;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::{StateDiff, StateNumber, StorageKey, ThinStateDiff};
+
+use crate::api::{BlockId, Tag};
+use crate::{get_block_number, get_latest_block_number, internal_server_error};
+
+/// Returns the pending data if it extends the latest block in the storage. Otherwise, the sync
+/// didn't poll a pending block on top of the latest block yet, and an empty pending block on top
+/// of the latest block is returned.
+pub(crate) fn get_pending_data<'a, Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    pending_data: &'a PendingData,
+) -> Result<Cow<'a, PendingData>, ErrorObjectOwned> {
+    let latest_header = match get_latest_block_number(txn)? {
+        Some(block_number) => txn.get_block_header(block_number).map_err(internal_server_error)?,
+        None => None,
+    };
+    let latest_block_hash =
+        latest_header.as_ref().map(|header| header.block_hash).unwrap_or_default();
+    if pending_data.block.parent_block_hash == latest_block_hash {
+        return Ok(Cow::Borrowed(pending_data));
+    }
+
+    let block = match latest_header {
+        Some(header) => PendingBlock {
+            parent_block_hash: header.block_hash,
+            gas_price: header.gas_price,
+            timestamp: header.timestamp,
+            sequencer_address: header.sequencer,
+            ..PendingBlock::default()
+        },
+        None => PendingBlock::default(),
+    };
+    Ok(Cow::Owned(PendingData { block, ..PendingData::default() }))
+}
+
+/// Returns the number that the pending block will get once it is accepted.
+pub(crate) fn get_pending_block_number<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+) -> Result<BlockNumber, ErrorObjectOwned> {
+    txn.get_header_marker().map_err(internal_server_error)
+}
+
+/// Returns the state to read at for the given block id: the state number in the storage and, for
+/// the pending block, the pending state diff that applies on top of it.
+pub(crate) fn get_state<'a, Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    block_id: BlockId,
+    pending_data: &'a PendingData,
+) -> Result<(StateNumber, Option<&'a StateDiff>), ErrorObjectOwned> {
+    if block_id != BlockId::Tag(Tag::Pending) {
+        let block_number = get_block_number(txn, block_id)?;
+        return Ok((StateNumber::right_after_block(block_number), None));
+    }
+
+    let state_number = StateNumber::right_before_block(get_pending_block_number(txn)?);
+    // If the pending data doesn't extend the latest block, the pending block is empty.
+    let pending_state_diff = match get_pending_data(txn, pending_data)? {
+        Cow::Borrowed(pending_data) => Some(&pending_data.state_diff),
+        Cow::Owned(_) => None,
+    };
+    Ok((state_number, pending_state_diff))
+}
+
+/// Returns the class hash of the contract, taking into account the pending state diff if given.
+pub(crate) fn get_class_hash_at<Mode: TransactionKind>(
+    state_reader: &StateReader<'_, Mode>,
+    state_number: StateNumber,
+    pending_state_diff: Option<&StateDiff>,
+    contract_address: &ContractAddress,
+) -> Result<Option<ClassHash>, ErrorObjectOwned> {
+    if let Some(state_diff) = pending_state_diff {
+        if let Some(class_hash) = state_diff
+            .replaced_classes
+            .get(contract_address)
+            .or_else(|| state_diff.deployed_contracts.get(contract_address))
+        {
+            return Ok(Some(*class_hash));
+        }
+    }
+    state_reader.get_class_hash_at(state_number, contract_address).map_err(internal_server_error)
+}
+
+/// Returns the nonce of the contract, taking into account the pending state diff if given.
+pub(crate) fn get_nonce_at<Mode: TransactionKind>(
+    state_reader: &StateReader<'_, Mode>,
+    state_number: StateNumber,
+    pending_state_diff: Option<&StateDiff>,
+    contract_address: &ContractAddress,
+) -> Result<Option<Nonce>, ErrorObjectOwned> {
+    if let Some(state_diff) = pending_state_diff {
+        if let Some(nonce) = state_diff.nonces.get(contract_address) {
+            return Ok(Some(*nonce));
+        }
+        if state_diff.deployed_contracts.contains_key(contract_address) {
+            return Ok(Some(Nonce::default()));
+        }
+    }
+    state_reader.get_nonce_at(state_number, contract_address).map_err(internal_server_error)
+}
+
+/// Returns the storage value of the contract, taking into account the pending state diff if
+/// given, or None if the contract doesn't exist.
+pub(crate) fn get_storage_at<Mode: TransactionKind>(
+    state_reader: &StateReader<'_, Mode>,
+    state_number: StateNumber,
+    pending_state_diff: Option<&StateDiff>,
+    contract_address: &ContractAddress,
+    key: &StorageKey,
+) -> Result<Option<StarkFelt>, ErrorObjectOwned> {
+    // Check that the contract exists.
+    if get_class_hash_at(state_reader, state_number, pending_state_diff, contract_address)?
+        .is_none()
+    {
+        return Ok(None);
+    }
+
+    if let Some(value) = pending_state_diff
+        .and_then(|state_diff| state_diff.storage_diffs.get(contract_address))
+        .and_then(|storage_diff| storage_diff.get(key))
+    {
+        return Ok(Some(*value));
+    }
+    state_reader
+        .get_storage_at(state_number, contract_address, key)
+        .map(Some)
+        .map_err(internal_server_error)
+}
+
+/// Returns the thin state diff of the pending state diff, without cloning the class definitions.
+pub(crate) fn get_pending_thin_state_diff(state_diff: &StateDiff) -> ThinStateDiff {
+    ThinStateDiff {
+        deployed_contracts: state_diff.deployed_contracts.clone(),
+        storage_diffs: state_diff.storage_diffs.clone(),
+        declared_classes: state_diff
+            .declared_classes
+            .iter()
+            .map(|(class_hash, (compiled_class_hash, _class))| (*class_hash, *compiled_class_hash))
+            .collect(),
+        deprecated_declared_classes: state_diff
+            .deprecated_declared_classes
+            .keys()
+            .copied()
+            .collect(),
+        nonces: state_diff.nonces.clone(),
+        replaced_classes: state_diff.replaced_classes.clone(),
+    }
+}
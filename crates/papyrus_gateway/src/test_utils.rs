@@ -1,7 +1,9 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use jsonrpsee::server::RpcModule;
 use jsonschema::JSONSchema;
+use papyrus_common::pending_data::PendingData;
 use papyrus_common::SyncingState;
 use papyrus_storage::test_utils::get_test_storage;
 use papyrus_storage::StorageWriter;
@@ -18,6 +20,7 @@ pub fn get_test_gateway_config() -> GatewayConfig {
         max_events_chunk_size: 10,
         max_events_keys: 10,
         collect_metrics: false,
+        shutdown_timeout: Duration::from_secs(1),
     }
 }
 
@@ -25,11 +28,23 @@ pub(crate) fn get_test_syncing_state() -> Arc<RwLock<SyncingState>> {
     Arc::new(RwLock::new(SyncingState::default()))
 }
 
+pub(crate) fn get_test_pending_data() -> Arc<RwLock<PendingData>> {
+    Arc::new(RwLock::new(PendingData::default()))
+}
+
 pub(crate) fn get_test_rpc_server_and_storage_writer<T: JsonRpcServerImpl>()
 -> (RpcModule<T>, StorageWriter) {
+    let (module, storage_writer, _pending_data) =
+        get_test_rpc_server_storage_writer_and_pending_data();
+    (module, storage_writer)
+}
+
+pub(crate) fn get_test_rpc_server_storage_writer_and_pending_data<T: JsonRpcServerImpl>()
+-> (RpcModule<T>, StorageWriter, Arc<RwLock<PendingData>>) {
     let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
     let config = get_test_gateway_config();
     let shared_syncing_state = get_test_syncing_state();
+    let pending_data = get_test_pending_data();
     (
         T::new(
             config.chain_id,
@@ -37,9 +52,11 @@ pub(crate) fn get_test_rpc_server_and_storage_writer<T: JsonRpcServerImpl>()
             config.max_events_chunk_size,
             config.max_events_keys,
             shared_syncing_state,
+            pending_data.clone(),
         )
         .into_rpc_module(),
         storage_writer,
+        pending_data,
     )
 }
 
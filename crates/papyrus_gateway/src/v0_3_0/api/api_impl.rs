@@ -1,36 +1,43 @@
 use std::sync::Arc;
 
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::core::{async_trait, RpcResult};
 use jsonrpsee::types::ErrorObjectOwned;
 use jsonrpsee::RpcModule;
+use papyrus_common::pending_data::PendingData;
 use papyrus_common::SyncingState;
 use papyrus_storage::body::events::{EventIndex, EventsReader};
 use papyrus_storage::body::{BodyStorageReader, TransactionIndex};
-use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::db::TransactionKind;
+use papyrus_storage::state::{StateReader, StateStorageReader};
 use papyrus_storage::StorageReader;
 use starknet_api::block::{BlockNumber, BlockStatus};
 use starknet_api::core::{ChainId, ClassHash, ContractAddress, GlobalRoot, Nonce};
 use starknet_api::hash::{StarkFelt, StarkHash, GENESIS_HASH};
-use starknet_api::state::{StateNumber, StorageKey};
+use starknet_api::state::{StateDiff, StateNumber, StorageKey};
 use starknet_api::transaction::{
-    EventIndexInTransactionOutput, TransactionExecutionStatus, TransactionHash,
+    EventContent, EventIndexInTransactionOutput, TransactionExecutionStatus, TransactionHash,
     TransactionOffsetInBlock,
 };
 use tokio::sync::RwLock;
 use tracing::instrument;
 
-use super::super::block::{Block, BlockHeader};
-use super::super::state::StateUpdate;
+use super::super::block::{Block, BlockHeader, GeneralBlock, PendingBlock};
+use super::super::state::{GeneralStateUpdate, PendingStateUpdate, StateUpdate};
 use super::super::transaction::{
-    Event, Transaction, TransactionOutput, TransactionReceipt, TransactionReceiptWithStatus,
-    TransactionWithHash, Transactions,
+    Event, GeneralTransactionReceipt, PendingTransactionReceipt,
+    PendingTransactionReceiptWithStatus, Transaction, TransactionOutput, TransactionReceipt,
+    TransactionReceiptWithStatus, TransactionStatus, TransactionWithHash, Transactions,
 };
 use super::{
     BlockHashAndNumber, BlockId, EventFilter, EventsChunk, GatewayContractClass,
     JsonRpcV0_3_0Server,
 };
-use crate::api::{BlockHashOrNumber, ContinuationToken, JsonRpcError, JsonRpcServerImpl};
+use crate::api::{BlockHashOrNumber, ContinuationToken, JsonRpcError, JsonRpcServerImpl, Tag};
 use crate::block::get_block_header_by_number;
+use crate::pending::{
+    get_class_hash_at, get_nonce_at, get_pending_block_number, get_pending_data,
+    get_pending_thin_state_diff, get_state, get_storage_at,
+};
 use crate::transaction::{get_block_tx_hashes_by_number, get_block_txs_by_number};
 use crate::{
     get_block_number, get_block_status, get_latest_block_number, internal_server_error,
@@ -44,8 +51,10 @@ pub struct JsonRpcServerV0_3_0Impl {
     pub max_events_chunk_size: usize,
     pub max_events_keys: usize,
     pub shared_syncing_state: Arc<RwLock<SyncingState>>,
+    pub pending_data: Arc<RwLock<PendingData>>,
 }
 
+#[async_trait]
 impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     #[instrument(skip(self), level = "debug", err, ret)]
     fn block_number(&self) -> RpcResult<BlockNumber> {
@@ -64,19 +73,56 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_block_w_transaction_hashes(&self, block_id: BlockId) -> RpcResult<Block> {
+    async fn get_block_w_transaction_hashes(&self, block_id: BlockId) -> RpcResult<GeneralBlock> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if block_id == BlockId::Tag(Tag::Pending) {
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            let transaction_hashes = pending_data.block.body.transaction_hashes.clone();
+            return Ok(GeneralBlock::PendingBlock(PendingBlock {
+                parent_hash: pending_data.block.parent_block_hash,
+                sequencer_address: pending_data.block.sequencer_address,
+                timestamp: pending_data.block.timestamp,
+                transactions: Transactions::Hashes(transaction_hashes),
+            }));
+        }
+
         let block_number = get_block_number(&txn, block_id)?;
         let status = get_block_status(&txn, block_number)?;
         let header = get_block_header_by_number(&txn, block_number)?;
         let transaction_hashes = get_block_tx_hashes_by_number(&txn, block_number)?;
 
-        Ok(Block { status, header, transactions: Transactions::Hashes(transaction_hashes) })
+        Ok(GeneralBlock::Block(Block {
+            status,
+            header,
+            transactions: Transactions::Hashes(transaction_hashes),
+        }))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<Block> {
+    async fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<GeneralBlock> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if block_id == BlockId::Tag(Tag::Pending) {
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            let body = &pending_data.block.body;
+            let transactions_with_hash = body
+                .transactions
+                .iter()
+                .zip(&body.transaction_hashes)
+                .map(|(transaction, transaction_hash)| TransactionWithHash {
+                    transaction: transaction.clone().into(),
+                    transaction_hash: *transaction_hash,
+                })
+                .collect();
+            return Ok(GeneralBlock::PendingBlock(PendingBlock {
+                parent_hash: pending_data.block.parent_block_hash,
+                sequencer_address: pending_data.block.sequencer_address,
+                timestamp: pending_data.block.timestamp,
+                transactions: Transactions::Full(transactions_with_hash),
+            }));
+        }
+
         let block_number = get_block_number(&txn, block_id)?;
         let status = get_block_status(&txn, block_number)?;
         let header = get_block_header_by_number(&txn, block_number)?;
@@ -93,43 +139,53 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
             })
             .collect();
 
-        Ok(Block { status, header, transactions: Transactions::Full(transactions_with_hash) })
+        Ok(GeneralBlock::Block(Block {
+            status,
+            header,
+            transactions: Transactions::Full(transactions_with_hash),
+        }))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_storage_at(
+    async fn get_storage_at(
         &self,
         contract_address: ContractAddress,
         key: StorageKey,
         block_id: BlockId,
     ) -> RpcResult<StarkFelt> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
         // Check that the block is valid and get the state number.
-        let block_number = get_block_number(&txn, block_id)?;
-        let state = StateNumber::right_after_block(block_number);
+        let (state, pending_state_diff) = get_state(&txn, block_id, &pending_data)?;
         let state_reader = txn.get_state_reader().map_err(internal_server_error)?;
 
-        // Check that the contract exists.
-        state_reader
-            .get_class_hash_at(state, &contract_address)
-            .map_err(internal_server_error)?
-            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))?;
-
-        state_reader.get_storage_at(state, &contract_address, &key).map_err(internal_server_error)
+        get_storage_at(&state_reader, state, pending_state_diff, &contract_address, &key)?
+            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_transaction_by_hash(
+    async fn get_transaction_by_hash(
         &self,
         transaction_hash: TransactionHash,
     ) -> RpcResult<TransactionWithHash> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let transaction_index = txn
-            .get_transaction_idx_by_hash(&transaction_hash)
-            .map_err(internal_server_error)?
-            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::TransactionHashNotFound))?;
+        let Some(transaction_index) =
+            txn.get_transaction_idx_by_hash(&transaction_hash).map_err(internal_server_error)?
+        else {
+            // The transaction might be in the pending block.
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            let body = &pending_data.block.body;
+            let transaction = body
+                .transaction_hashes
+                .iter()
+                .position(|hash| *hash == transaction_hash)
+                .map(|offset| body.transactions[offset].clone())
+                .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::TransactionHashNotFound))?;
+            return Ok(TransactionWithHash { transaction: transaction.into(), transaction_hash });
+        };
 
         let (transaction, _execution_status) = txn
             .get_transaction(transaction_index)
@@ -140,12 +196,27 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_transaction_by_block_id_and_index(
+    async fn get_transaction_by_block_id_and_index(
         &self,
         block_id: BlockId,
         index: TransactionOffsetInBlock,
     ) -> RpcResult<TransactionWithHash> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if block_id == BlockId::Tag(Tag::Pending) {
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            let body = &pending_data.block.body;
+            let (transaction, transaction_hash) = body
+                .transactions
+                .get(index.0)
+                .zip(body.transaction_hashes.get(index.0))
+                .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::InvalidTransactionIndex))?;
+            return Ok(TransactionWithHash {
+                transaction: transaction.clone().into(),
+                transaction_hash: *transaction_hash,
+            });
+        }
+
         let block_number = get_block_number(&txn, block_id)?;
 
         let tx_index = TransactionIndex(block_number, index);
@@ -162,8 +233,13 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<usize> {
+    async fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<usize> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if block_id == BlockId::Tag(Tag::Pending) {
+            return Ok(get_pending_data(&txn, &pending_data)?.block.body.transactions.len());
+        }
+
         let block_number = get_block_number(&txn, block_id)?;
         let transactions: Vec<Transaction> = get_block_txs_by_number(&txn, block_number)?;
 
@@ -171,8 +247,25 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_state_update(&self, block_id: BlockId) -> RpcResult<StateUpdate> {
+    async fn get_state_update(&self, block_id: BlockId) -> RpcResult<GeneralStateUpdate> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if block_id == BlockId::Tag(Tag::Pending) {
+            // The old root of the pending block is the root of the latest block.
+            let old_root = match get_latest_block_number(&txn)? {
+                Some(block_number) => {
+                    get_block_header_by_number::<_, BlockHeader>(&txn, block_number)?.new_root
+                }
+                None => {
+                    GlobalRoot(StarkHash::try_from(GENESIS_HASH).map_err(internal_server_error)?)
+                }
+            };
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            return Ok(GeneralStateUpdate::PendingStateUpdate(PendingStateUpdate {
+                old_root,
+                state_diff: get_pending_thin_state_diff(&pending_data.state_diff).into(),
+            }));
+        }
 
         // Get the block header for the block hash and state root.
         let block_number = get_block_number(&txn, block_id)?;
@@ -185,9 +278,8 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
         );
         let mut old_root =
             GlobalRoot(StarkHash::try_from(GENESIS_HASH).map_err(internal_server_error)?);
-        if parent_block_number.is_ok() {
-            let parent_header: BlockHeader =
-                get_block_header_by_number(&txn, parent_block_number.unwrap())?;
+        if let Ok(parent_block_number) = parent_block_number {
+            let parent_header: BlockHeader = get_block_header_by_number(&txn, parent_block_number)?;
             old_root = parent_header.new_root;
         }
 
@@ -197,25 +289,47 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
             .map_err(internal_server_error)?
             .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::BlockNotFound))?;
 
-        Ok(StateUpdate {
+        Ok(GeneralStateUpdate::StateUpdate(StateUpdate {
             block_hash: header.block_hash,
             new_root: header.new_root,
             old_root,
             state_diff: thin_state_diff.into(),
-        })
+        }))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_transaction_receipt(
+    async fn get_transaction_receipt(
         &self,
         transaction_hash: TransactionHash,
-    ) -> RpcResult<TransactionReceiptWithStatus> {
+    ) -> RpcResult<GeneralTransactionReceipt> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let transaction_index = txn
-            .get_transaction_idx_by_hash(&transaction_hash)
-            .map_err(internal_server_error)?
-            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::TransactionHashNotFound))?;
+        let Some(transaction_index) =
+            txn.get_transaction_idx_by_hash(&transaction_hash).map_err(internal_server_error)?
+        else {
+            // The transaction might be in the pending block.
+            let pending_data = get_pending_data(&txn, &pending_data)?;
+            let body = &pending_data.block.body;
+            let offset = body
+                .transaction_hashes
+                .iter()
+                .position(|hash| *hash == transaction_hash)
+                .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::TransactionHashNotFound))?;
+            // RPC API v0.3 does not support reverted transactions.
+            if body.transaction_execution_statuses[offset] == TransactionExecutionStatus::Reverted {
+                return Err(ErrorObjectOwned::from(JsonRpcError::TransactionReverted))?;
+            }
+            return Ok(GeneralTransactionReceipt::PendingTransactionReceipt(
+                PendingTransactionReceiptWithStatus {
+                    status: TransactionStatus::Pending,
+                    receipt: PendingTransactionReceipt {
+                        transaction_hash,
+                        output: body.transaction_outputs[offset].clone().into(),
+                    },
+                },
+            ));
+        };
 
         let block_number = transaction_index.0;
         let status = get_block_status(&txn, block_number)?;
@@ -257,100 +371,77 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
 
         // todo: nevo - check what the expected behavior is when the transaction is reverted
         // todo: nevo - check the meaning of the rejected status
-        Ok(TransactionReceiptWithStatus {
+        Ok(GeneralTransactionReceipt::TransactionReceipt(TransactionReceiptWithStatus {
             receipt: TransactionReceipt { transaction_hash, block_hash, block_number, output },
             status: status.into(),
-        })
+        }))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_class(
+    async fn get_class(
         &self,
         block_id: BlockId,
         class_hash: ClassHash,
     ) -> RpcResult<GatewayContractClass> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let block_number = get_block_number(&txn, block_id)?;
-        let state_number = StateNumber::right_after_block(block_number);
+        let (state_number, pending_state_diff) = get_state(&txn, block_id, &pending_data)?;
         let state_reader = txn.get_state_reader().map_err(internal_server_error)?;
 
-        // The class might be a deprecated class. Search it first in the declared classes and if not
-        // found, search in the deprecated classes.
-        if let Some(class) = state_reader
-            .get_class_definition_at(state_number, &class_hash)
-            .map_err(internal_server_error)?
-        {
-            Ok(GatewayContractClass::Sierra(class.try_into().map_err(internal_server_error)?))
-        } else {
-            let class = state_reader
-                .get_deprecated_class_definition_at(state_number, &class_hash)
-                .map_err(internal_server_error)?
-                .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ClassHashNotFound))?;
-            Ok(GatewayContractClass::Cairo0(class.try_into().map_err(internal_server_error)?))
-        }
+        get_class_definition_at(&state_reader, state_number, pending_state_diff, &class_hash)?
+            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ClassHashNotFound))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_class_at(
+    async fn get_class_at(
         &self,
         block_id: BlockId,
         contract_address: ContractAddress,
     ) -> RpcResult<GatewayContractClass> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let block_number = get_block_number(&txn, block_id)?;
-        let state_number = StateNumber::right_after_block(block_number);
+        let (state_number, pending_state_diff) = get_state(&txn, block_id, &pending_data)?;
         let state_reader = txn.get_state_reader().map_err(internal_server_error)?;
 
-        let class_hash = state_reader
-            .get_class_hash_at(state_number, &contract_address)
-            .map_err(internal_server_error)?
-            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))?;
-
-        if let Some(class) = state_reader
-            .get_class_definition_at(state_number, &class_hash)
-            .map_err(internal_server_error)?
-        {
-            Ok(GatewayContractClass::Sierra(class.try_into().map_err(internal_server_error)?))
-        } else {
-            let class = state_reader
-                .get_deprecated_class_definition_at(state_number, &class_hash)
-                .map_err(internal_server_error)?
+        let class_hash =
+            get_class_hash_at(&state_reader, state_number, pending_state_diff, &contract_address)?
                 .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))?;
-            Ok(GatewayContractClass::Cairo0(class.try_into().map_err(internal_server_error)?))
-        }
+
+        get_class_definition_at(&state_reader, state_number, pending_state_diff, &class_hash)?
+            .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_class_hash_at(
+    async fn get_class_hash_at(
         &self,
         block_id: BlockId,
         contract_address: ContractAddress,
     ) -> RpcResult<ClassHash> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let block_number = get_block_number(&txn, block_id)?;
-        let state = StateNumber::right_after_block(block_number);
+        let (state, pending_state_diff) = get_state(&txn, block_id, &pending_data)?;
         let state_reader = txn.get_state_reader().map_err(internal_server_error)?;
 
-        state_reader
-            .get_class_hash_at(state, &contract_address)
-            .map_err(internal_server_error)?
+        get_class_hash_at(&state_reader, state, pending_state_diff, &contract_address)?
             .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_nonce(&self, block_id: BlockId, contract_address: ContractAddress) -> RpcResult<Nonce> {
+    async fn get_nonce(
+        &self,
+        block_id: BlockId,
+        contract_address: ContractAddress,
+    ) -> RpcResult<Nonce> {
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
-        let block_number = get_block_number(&txn, block_id)?;
-        let state = StateNumber::right_after_block(block_number);
+        let (state, pending_state_diff) = get_state(&txn, block_id, &pending_data)?;
         let state_reader = txn.get_state_reader().map_err(internal_server_error)?;
 
-        state_reader
-            .get_nonce_at(state, &contract_address)
-            .map_err(internal_server_error)?
+        get_nonce_at(&state_reader, state, pending_state_diff, &contract_address)?
             .ok_or_else(|| ErrorObjectOwned::from(JsonRpcError::ContractNotFound))
     }
 
@@ -360,7 +451,7 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk> {
+    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk> {
         // Check the chunk size.
         if filter.chunk_size > self.max_events_chunk_size {
             return Err(ErrorObjectOwned::from(JsonRpcError::PageSizeTooBig));
@@ -371,13 +462,18 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
         }
 
         // Get the requested block numbers.
+        let pending_data = self.pending_data.read().await;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-        let from_block_number = filter
-            .from_block
-            .map_or(Ok(BlockNumber(0)), |block_id| get_block_number(&txn, block_id))?;
+        let pending_block_number = get_pending_block_number(&txn)?;
+        let get_requested_block_number = |block_id| match block_id {
+            BlockId::Tag(Tag::Pending) => Ok(pending_block_number),
+            _ => get_block_number(&txn, block_id),
+        };
+        let from_block_number =
+            filter.from_block.map_or(Ok(BlockNumber(0)), get_requested_block_number)?;
         let maybe_to_block_number =
             filter.to_block.map_or(get_latest_block_number(&txn), |block_id| {
-                get_block_number(&txn, block_id).map(Some)
+                get_requested_block_number(block_id).map(Some)
             })?;
         if maybe_to_block_number.is_none() {
             // There are no blocks.
@@ -397,28 +493,90 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
                 EventIndexInTransactionOutput(0),
             ),
         };
+        // TODO: Consider changing empty sets in the filer keys to None.
+        let matches_keys = |content: &EventContent| {
+            filter.keys.iter().enumerate().all(|(i, keys)| {
+                content.keys.len() > i && (keys.is_empty() || keys.contains(&content.keys[i]))
+            })
+        };
 
         // Collect the requested events.
         // Once we collected enough events, we continue to check if there are any more events
         // corresponding to the requested filter. If there are, we return a continuation token
         // pointing to the next relevant event. Otherwise, we return a continuation token None.
         let mut filtered_events = vec![];
-        for ((from_address, event_index), content) in txn
-            .iter_events(filter.address, event_index, to_block_number)
-            .map_err(internal_server_error)?
+        // The events of the pending block aren't in the storage.
+        if let Some(storage_to_block_number) = get_latest_block_number(&txn)?
+            .map(|latest_block_number| latest_block_number.min(to_block_number))
         {
-            let block_number = (event_index.0).0;
-            if block_number > to_block_number {
-                break;
+            for ((from_address, event_index), content) in txn
+                .iter_events(filter.address, event_index, storage_to_block_number)
+                .map_err(internal_server_error)?
+            {
+                let block_number = (event_index.0).0;
+                if block_number > storage_to_block_number {
+                    break;
+                }
+                if filter.address.is_some() && from_address != filter.address.unwrap() {
+                    break;
+                }
+                if matches_keys(&content) {
+                    if filtered_events.len() == filter.chunk_size {
+                        return Ok(EventsChunk {
+                            events: filtered_events,
+                            continuation_token: Some(ContinuationToken::new(
+                                ContinuationTokenAsStruct(event_index),
+                            )?),
+                        });
+                    }
+                    let header: BlockHeader = get_block_header_by_number(&txn, block_number)
+                        .map_err(internal_server_error)?;
+                    let transaction_hash = txn
+                        .get_transaction_hash_by_idx(&event_index.0)
+                        .map_err(internal_server_error)?
+                        .ok_or_else(|| internal_server_error("Unknown internal error."))?;
+                    let emitted_event = Event {
+                        block_hash: Some(header.block_hash),
+                        block_number: Some(block_number),
+                        transaction_hash,
+                        event: starknet_api::transaction::Event { from_address, content },
+                    };
+                    filtered_events.push(emitted_event);
+                }
             }
-            if filter.address.is_some() && from_address != filter.address.unwrap() {
-                break;
+        }
+
+        if to_block_number < pending_block_number {
+            return Ok(EventsChunk { events: filtered_events, continuation_token: None });
+        }
+        // The continuation token might point into the pending block.
+        let first_event_index = match event_index {
+            EventIndex(TransactionIndex(block_number, tx_offset), event_offset)
+                if block_number == pending_block_number =>
+            {
+                (tx_offset.0, event_offset.0)
             }
-            // TODO: Consider changing empty sets in the filer keys to None.
-            if filter.keys.iter().enumerate().all(|(i, keys)| {
-                content.keys.len() > i && (keys.is_empty() || keys.contains(&content.keys[i]))
-            }) {
+            _ => (0, 0),
+        };
+        let pending_data = get_pending_data(&txn, &pending_data)?;
+        let body = &pending_data.block.body;
+        for (tx_offset, (output, transaction_hash)) in
+            body.transaction_outputs.iter().zip(&body.transaction_hashes).enumerate()
+        {
+            for (event_offset, event) in output.events().iter().enumerate() {
+                if (tx_offset, event_offset) < first_event_index {
+                    continue;
+                }
+                if filter.address.is_some_and(|address| address != event.from_address)
+                    || !matches_keys(&event.content)
+                {
+                    continue;
+                }
                 if filtered_events.len() == filter.chunk_size {
+                    let event_index = EventIndex(
+                        TransactionIndex(pending_block_number, TransactionOffsetInBlock(tx_offset)),
+                        EventIndexInTransactionOutput(event_offset),
+                    );
                     return Ok(EventsChunk {
                         events: filtered_events,
                         continuation_token: Some(ContinuationToken::new(
@@ -426,19 +584,12 @@ impl JsonRpcV0_3_0Server for JsonRpcServerV0_3_0Impl {
                         )?),
                     });
                 }
-                let header: BlockHeader = get_block_header_by_number(&txn, block_number)
-                    .map_err(internal_server_error)?;
-                let transaction_hash = txn
-                    .get_transaction_hash_by_idx(&event_index.0)
-                    .map_err(internal_server_error)?
-                    .ok_or_else(|| internal_server_error("Unknown internal error."))?;
-                let emitted_event = Event {
-                    block_hash: header.block_hash,
-                    block_number,
-                    transaction_hash,
-                    event: starknet_api::transaction::Event { from_address, content },
-                };
-                filtered_events.push(emitted_event);
+                filtered_events.push(Event {
+                    block_hash: None,
+                    block_number: None,
+                    transaction_hash: *transaction_hash,
+                    event: event.clone(),
+                });
             }
         }
 
@@ -459,6 +610,7 @@ impl JsonRpcServerImpl for JsonRpcServerV0_3_0Impl {
         max_events_chunk_size: usize,
         max_events_keys: usize,
         shared_syncing_state: Arc<RwLock<SyncingState>>,
+        pending_data: Arc<RwLock<PendingData>>,
     ) -> Self {
         Self {
             chain_id,
@@ -466,6 +618,7 @@ impl JsonRpcServerImpl for JsonRpcServerV0_3_0Impl {
             max_events_chunk_size,
             max_events_keys,
             shared_syncing_state,
+            pending_data,
         }
     }
 
@@ -473,3 +626,37 @@ impl JsonRpcServerImpl for JsonRpcServerV0_3_0Impl {
         self.into_rpc()
     }
 }
+
+// The class might be a deprecated class. Search it first in the declared classes and if not found,
+// search in the deprecated classes.
+fn get_class_definition_at<Mode: TransactionKind>(
+    state_reader: &StateReader<'_, Mode>,
+    state_number: StateNumber,
+    pending_state_diff: Option<&StateDiff>,
+    class_hash: &ClassHash,
+) -> Result<Option<GatewayContractClass>, ErrorObjectOwned> {
+    if let Some(state_diff) = pending_state_diff {
+        if let Some((_compiled_class_hash, class)) = state_diff.declared_classes.get(class_hash) {
+            return Ok(Some(GatewayContractClass::Sierra(class.clone().into())));
+        }
+        if let Some(class) = state_diff.deprecated_declared_classes.get(class_hash) {
+            return Ok(Some(GatewayContractClass::Cairo0(
+                class.clone().try_into().map_err(internal_server_error)?,
+            )));
+        }
+    }
+
+    if let Some(class) = state_reader
+        .get_class_definition_at(state_number, class_hash)
+        .map_err(internal_server_error)?
+    {
+        return Ok(Some(GatewayContractClass::Sierra(class.into())));
+    }
+    state_reader
+        .get_deprecated_class_definition_at(state_number, class_hash)
+        .map_err(internal_server_error)?
+        .map(|class| {
+            Ok(GatewayContractClass::Cairo0(class.try_into().map_err(internal_server_error)?))
+        })
+        .transpose()
+}
@@ -9,10 +9,10 @@ use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{TransactionHash, TransactionOffsetInBlock};
 
-use super::block::Block;
+use super::block::GeneralBlock;
 use super::deprecated_contract_class::ContractClass as DeprecatedContractClass;
-use super::state::{ContractClass, StateUpdate};
-use super::transaction::{Event, TransactionReceiptWithStatus, TransactionWithHash};
+use super::state::{ContractClass, GeneralStateUpdate};
+use super::transaction::{Event, GeneralTransactionReceipt, TransactionWithHash};
 use crate::api::{BlockHashAndNumber, BlockId, ContinuationToken, EventFilter};
 
 pub mod api_impl;
@@ -31,15 +31,15 @@ pub trait JsonRpc {
 
     /// Gets block information with transaction hashes given a block identifier.
     #[method(name = "getBlockWithTxHashes")]
-    fn get_block_w_transaction_hashes(&self, block_id: BlockId) -> RpcResult<Block>;
+    async fn get_block_w_transaction_hashes(&self, block_id: BlockId) -> RpcResult<GeneralBlock>;
 
     /// Gets block information with full transactions given a block identifier.
     #[method(name = "getBlockWithTxs")]
-    fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<Block>;
+    async fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<GeneralBlock>;
 
     /// Gets the value of the storage at the given address, key, and block.
     #[method(name = "getStorageAt")]
-    fn get_storage_at(
+    async fn get_storage_at(
         &self,
         contract_address: ContractAddress,
         key: StorageKey,
@@ -48,14 +48,14 @@ pub trait JsonRpc {
 
     /// Gets the details of a submitted transaction.
     #[method(name = "getTransactionByHash")]
-    fn get_transaction_by_hash(
+    async fn get_transaction_by_hash(
         &self,
         transaction_hash: TransactionHash,
     ) -> RpcResult<TransactionWithHash>;
 
     /// Gets the details of a transaction by a given block id and index.
     #[method(name = "getTransactionByBlockIdAndIndex")]
-    fn get_transaction_by_block_id_and_index(
+    async fn get_transaction_by_block_id_and_index(
         &self,
         block_id: BlockId,
         index: TransactionOffsetInBlock,
@@ -63,22 +63,22 @@ pub trait JsonRpc {
 
     /// Gets the number of transactions in a block given a block id.
     #[method(name = "getBlockTransactionCount")]
-    fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<usize>;
+    async fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<usize>;
 
     /// Gets the information about the result of executing the requested block.
     #[method(name = "getStateUpdate")]
-    fn get_state_update(&self, block_id: BlockId) -> RpcResult<StateUpdate>;
+    async fn get_state_update(&self, block_id: BlockId) -> RpcResult<GeneralStateUpdate>;
 
     /// Gets the transaction receipt by the transaction hash.
     #[method(name = "getTransactionReceipt")]
-    fn get_transaction_receipt(
+    async fn get_transaction_receipt(
         &self,
         transaction_hash: TransactionHash,
-    ) -> RpcResult<TransactionReceiptWithStatus>;
+    ) -> RpcResult<GeneralTransactionReceipt>;
 
     /// Gets the contract class definition associated with the given hash.
     #[method(name = "getClass")]
-    fn get_class(
+    async fn get_class(
         &self,
         block_id: BlockId,
         class_hash: ClassHash,
@@ -86,7 +86,7 @@ pub trait JsonRpc {
 
     /// Gets the contract class definition in the given block at the given address.
     #[method(name = "getClassAt")]
-    fn get_class_at(
+    async fn get_class_at(
         &self,
         block_id: BlockId,
         contract_address: ContractAddress,
@@ -95,7 +95,7 @@ pub trait JsonRpc {
     /// Gets the contract class hash in the given block for the contract deployed at the given
     /// address.
     #[method(name = "getClassHashAt")]
-    fn get_class_hash_at(
+    async fn get_class_hash_at(
         &self,
         block_id: BlockId,
         contract_address: ContractAddress,
@@ -103,7 +103,11 @@ pub trait JsonRpc {
 
     /// Gets the nonce associated with the given address in the given block.
     #[method(name = "getNonce")]
-    fn get_nonce(&self, block_id: BlockId, contract_address: ContractAddress) -> RpcResult<Nonce>;
+    async fn get_nonce(
+        &self,
+        block_id: BlockId,
+        contract_address: ContractAddress,
+    ) -> RpcResult<Nonce>;
 
     /// Returns the currently configured StarkNet chain id.
     #[method(name = "chainId")]
@@ -111,7 +115,7 @@ pub trait JsonRpc {
 
     /// Returns all events matching the given filter.
     #[method(name = "getEvents")]
-    fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk>;
+    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk>;
 
     /// Returns the synching status of the node, or false if the node is not synching.
     #[method(name = "syncing")]
@@ -8,6 +8,7 @@ use jsonrpsee::core::params::ObjectParams;
 use jsonrpsee::core::Error;
 use jsonrpsee::types::ErrorObjectOwned;
 use jsonschema::JSONSchema;
+use papyrus_common::pending_data::{PendingBlock as PendingBlockData, PendingData};
 use papyrus_storage::base_layer::BaseLayerStorageWriter;
 use papyrus_storage::body::events::EventIndex;
 use papyrus_storage::body::{BodyStorageWriter, TransactionIndex};
@@ -32,12 +33,13 @@ use test_utils::{
 };
 
 use super::super::api::EventsChunk;
-use super::super::block::Block;
+use super::super::block::{Block, PendingBlock};
 use super::super::deprecated_contract_class::ContractClass as DeprecatedContractClass;
-use super::super::state::{ContractClass, StateUpdate, ThinStateDiff};
+use super::super::state::{ContractClass, PendingStateUpdate, StateUpdate, ThinStateDiff};
 use super::super::transaction::{
-    Event, TransactionOutput, TransactionReceipt, TransactionReceiptWithStatus, TransactionStatus,
-    TransactionWithHash, Transactions,
+    Event, PendingTransactionReceipt, PendingTransactionReceiptWithStatus, TransactionOutput,
+    TransactionReceipt, TransactionReceiptWithStatus, TransactionStatus, TransactionWithHash,
+    Transactions,
 };
 use super::api_impl::JsonRpcServerV0_3_0Impl;
 use crate::api::{
@@ -45,7 +47,8 @@ use crate::api::{
     Tag,
 };
 use crate::test_utils::{
-    get_starknet_spec_api_schema, get_test_gateway_config, get_test_rpc_server_and_storage_writer,
+    get_starknet_spec_api_schema, get_test_gateway_config, get_test_pending_data,
+    get_test_rpc_server_and_storage_writer, get_test_rpc_server_storage_writer_and_pending_data,
     get_test_syncing_state,
 };
 use crate::version_config::VERSION_0_3_0;
@@ -1323,8 +1326,8 @@ async fn get_events_chunk_size_2_with_address() {
             if let Some(key) = event.content.keys.get(0) {
                 if filter_keys.get(key).is_some() && event.from_address == address {
                     emitted_events.push(Event {
-                        block_hash,
-                        block_number,
+                        block_hash: Some(block_hash),
+                        block_number: Some(block_number),
                         transaction_hash,
                         event: event.clone(),
                     });
@@ -1404,8 +1407,8 @@ async fn get_events_chunk_size_2_without_address() {
             if let Some(key) = event.content.keys.get(0) {
                 if filter_keys.get(key).is_some() {
                     emitted_events.push(Event {
-                        block_hash,
-                        block_number,
+                        block_hash: Some(block_hash),
+                        block_number: Some(block_number),
                         transaction_hash,
                         event: event.clone(),
                     });
@@ -1583,6 +1586,319 @@ async fn get_events_invalid_ct() {
     ));
 }
 
+#[tokio::test]
+async fn get_pending_block() {
+    let (module, mut storage_writer, pending_data) =
+        get_test_rpc_server_storage_writer_and_pending_data::<JsonRpcServerV0_3_0Impl>();
+    let block = get_test_block(1, None, None, None);
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block.header.block_number, &block.header)
+        .unwrap()
+        .append_body(block.header.block_number, block.body.clone())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // Before the sync polls a pending block on top of the latest block, the pending block is
+    // empty.
+    let res = module
+        .call::<_, PendingBlock>(
+            "starknet_V0_3_0_getBlockWithTxHashes",
+            [BlockId::Tag(Tag::Pending)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        PendingBlock {
+            parent_hash: block.header.block_hash,
+            sequencer_address: block.header.sequencer,
+            timestamp: block.header.timestamp,
+            transactions: Transactions::Hashes(vec![]),
+        }
+    );
+
+    let mut body = get_test_body(2, None, None, None);
+    body.transaction_hashes =
+        vec![TransactionHash(stark_felt!("0x100")), TransactionHash(stark_felt!("0x101"))];
+    *pending_data.write().await = PendingData {
+        block: PendingBlockData {
+            parent_block_hash: block.header.block_hash,
+            body: body.clone(),
+            ..PendingBlockData::default()
+        },
+        ..PendingData::default()
+    };
+
+    let res = module
+        .call::<_, PendingBlock>(
+            "starknet_V0_3_0_getBlockWithTxHashes",
+            [BlockId::Tag(Tag::Pending)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.parent_hash, block.header.block_hash);
+    assert_eq!(res.transactions, Transactions::Hashes(body.transaction_hashes.clone()));
+
+    let res = module
+        .call::<_, usize>("starknet_V0_3_0_getBlockTransactionCount", [BlockId::Tag(Tag::Pending)])
+        .await
+        .unwrap();
+    assert_eq!(res, 2);
+
+    let expected_transaction = TransactionWithHash {
+        transaction: body.transactions[1].clone().into(),
+        transaction_hash: body.transaction_hashes[1],
+    };
+    let res = module
+        .call::<_, TransactionWithHash>(
+            "starknet_V0_3_0_getTransactionByBlockIdAndIndex",
+            (BlockId::Tag(Tag::Pending), TransactionOffsetInBlock(1)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res, expected_transaction);
+    let res = module
+        .call::<_, TransactionWithHash>(
+            "starknet_V0_3_0_getTransactionByHash",
+            [body.transaction_hashes[1]],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res, expected_transaction);
+
+    // The transactions of the accepted blocks are still found.
+    let res = module
+        .call::<_, TransactionWithHash>(
+            "starknet_V0_3_0_getTransactionByHash",
+            [block.body.transaction_hashes[0]],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.transaction_hash, block.body.transaction_hashes[0]);
+
+    let expected_receipt = PendingTransactionReceiptWithStatus {
+        status: TransactionStatus::Pending,
+        receipt: PendingTransactionReceipt {
+            transaction_hash: body.transaction_hashes[1],
+            output: TransactionOutput::from(body.transaction_outputs[1].clone()),
+        },
+    };
+    let res = module
+        .call::<_, PendingTransactionReceiptWithStatus>(
+            "starknet_V0_3_0_getTransactionReceipt",
+            [body.transaction_hashes[1]],
+        )
+        .await
+        .unwrap();
+    // See the comment in the get_transaction_receipt test.
+    assert_eq!(
+        serde_json::to_string(&res).unwrap(),
+        serde_json::to_string(&expected_receipt).unwrap(),
+    );
+
+    // Ask for an invalid transaction index.
+    let err = module
+        .call::<_, TransactionWithHash>(
+            "starknet_V0_3_0_getTransactionByBlockIdAndIndex",
+            (BlockId::Tag(Tag::Pending), TransactionOffsetInBlock(2)),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == ErrorObjectOwned::owned(
+        JsonRpcError::InvalidTransactionIndex as i32,
+        JsonRpcError::InvalidTransactionIndex.to_string(),
+        None::<()>,
+    ));
+
+    // Pending data that doesn't extend the latest block is not served.
+    pending_data.write().await.block.parent_block_hash = BlockHash(stark_felt!("0x1"));
+    let res = module
+        .call::<_, usize>("starknet_V0_3_0_getBlockTransactionCount", [BlockId::Tag(Tag::Pending)])
+        .await
+        .unwrap();
+    assert_eq!(res, 0);
+}
+
+#[tokio::test]
+async fn get_pending_state() {
+    let (module, mut storage_writer, pending_data) =
+        get_test_rpc_server_storage_writer_and_pending_data::<JsonRpcServerV0_3_0Impl>();
+    let header = BlockHeader::default();
+    let diff = get_test_state_diff();
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(header.block_number, &header)
+        .unwrap()
+        .append_state_diff(header.block_number, diff.clone(), IndexMap::new())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let (address, storage_entries) = diff.storage_diffs.get_index(0).unwrap();
+    let (key, _value) = storage_entries.get_index(0).unwrap();
+    let new_value = stark_felt!("0x55");
+    let new_nonce = Nonce(stark_felt!("0x77"));
+    let new_address = ContractAddress(patricia_key!("0x1234"));
+    let new_class_hash = ClassHash(stark_felt!("0x4321"));
+    let (_, new_class) = diff.deprecated_declared_classes.get_index(0).unwrap();
+    let pending_state_diff = StateDiff {
+        deployed_contracts: IndexMap::from([(new_address, new_class_hash)]),
+        storage_diffs: IndexMap::from([(*address, IndexMap::from([(*key, new_value)]))]),
+        declared_classes: IndexMap::new(),
+        deprecated_declared_classes: IndexMap::from([(new_class_hash, new_class.clone())]),
+        nonces: IndexMap::from([(*address, new_nonce)]),
+        replaced_classes: IndexMap::new(),
+    };
+    *pending_data.write().await = PendingData {
+        block: PendingBlockData {
+            parent_block_hash: header.block_hash,
+            ..PendingBlockData::default()
+        },
+        state_diff: pending_state_diff.clone(),
+    };
+    let pending = BlockId::Tag(Tag::Pending);
+
+    let res = module
+        .call::<_, StarkFelt>("starknet_V0_3_0_getStorageAt", (*address, *key, pending))
+        .await
+        .unwrap();
+    assert_eq!(res, new_value);
+
+    let res =
+        module.call::<_, Nonce>("starknet_V0_3_0_getNonce", (pending, *address)).await.unwrap();
+    assert_eq!(res, new_nonce);
+    let res =
+        module.call::<_, Nonce>("starknet_V0_3_0_getNonce", (pending, new_address)).await.unwrap();
+    assert_eq!(res, Nonce::default());
+
+    let res = module
+        .call::<_, ClassHash>("starknet_V0_3_0_getClassHashAt", (pending, new_address))
+        .await
+        .unwrap();
+    assert_eq!(res, new_class_hash);
+
+    let expected_class: DeprecatedContractClass = new_class.clone().try_into().unwrap();
+    let res = module
+        .call::<_, DeprecatedContractClass>("starknet_V0_3_0_getClass", (pending, new_class_hash))
+        .await
+        .unwrap();
+    assert_eq!(res, expected_class);
+    let res = module
+        .call::<_, DeprecatedContractClass>("starknet_V0_3_0_getClassAt", (pending, new_address))
+        .await
+        .unwrap();
+    assert_eq!(res, expected_class);
+
+    let res = module
+        .call::<_, PendingStateUpdate>("starknet_V0_3_0_getStateUpdate", [pending])
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        PendingStateUpdate {
+            old_root: header.state_root,
+            state_diff: starknet_api::state::ThinStateDiff::from(pending_state_diff).into(),
+        }
+    );
+
+    // The pending contract doesn't exist in the latest block.
+    let err = module
+        .call::<_, ClassHash>(
+            "starknet_V0_3_0_getClassHashAt",
+            (BlockId::Tag(Tag::Latest), new_address),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == ErrorObjectOwned::owned(
+        JsonRpcError::ContractNotFound as i32,
+        JsonRpcError::ContractNotFound.to_string(),
+        None::<()>,
+    ));
+}
+
+#[tokio::test]
+async fn get_events_with_pending() {
+    let (module, mut storage_writer, pending_data) =
+        get_test_rpc_server_storage_writer_and_pending_data::<JsonRpcServerV0_3_0Impl>();
+    let block = get_test_block(2, Some(2), None, None);
+    let block_number = block.header.block_number;
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &block.header)
+        .unwrap()
+        .append_body(block_number, block.body.clone())
+        .unwrap()
+        .commit()
+        .unwrap();
+    let mut body = get_test_body(1, Some(3), None, None);
+    body.transaction_hashes = vec![TransactionHash(stark_felt!("0x100"))];
+    *pending_data.write().await = PendingData {
+        block: PendingBlockData {
+            parent_block_hash: block.header.block_hash,
+            body: body.clone(),
+            ..PendingBlockData::default()
+        },
+        ..PendingData::default()
+    };
+
+    let mut expected_events = vec![];
+    for (tx_output, transaction_hash) in
+        block.body.transaction_outputs.iter().zip(&block.body.transaction_hashes)
+    {
+        for event in tx_output.events() {
+            expected_events.push(Event {
+                block_hash: Some(block.header.block_hash),
+                block_number: Some(block_number),
+                transaction_hash: *transaction_hash,
+                event: event.clone(),
+            });
+        }
+    }
+    for event in body.transaction_outputs[0].events() {
+        expected_events.push(Event {
+            block_hash: None,
+            block_number: None,
+            transaction_hash: body.transaction_hashes[0],
+            event: event.clone(),
+        });
+    }
+
+    let chunk_size = 2;
+    let mut filter = EventFilter {
+        from_block: None,
+        to_block: Some(BlockId::Tag(Tag::Pending)),
+        continuation_token: None,
+        chunk_size,
+        address: None,
+        keys: vec![],
+    };
+    let mut events = vec![];
+    loop {
+        let res = module
+            .call::<_, EventsChunk>("starknet_V0_3_0_getEvents", [filter.clone()])
+            .await
+            .unwrap();
+        events.extend(res.events);
+        if res.continuation_token.is_none() {
+            break;
+        }
+        filter.continuation_token = res.continuation_token;
+    }
+    assert_eq!(events, expected_events);
+
+    // Ask only for the pending events.
+    filter.from_block = Some(BlockId::Tag(Tag::Pending));
+    filter.continuation_token = None;
+    filter.chunk_size = 10;
+    let res = module.call::<_, EventsChunk>("starknet_V0_3_0_getEvents", [filter]).await.unwrap();
+    assert_eq!(res.events, expected_events[4..]);
+}
+
 #[tokio::test]
 async fn serialize_returns_valid_json() {
     let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
@@ -1629,8 +1945,14 @@ async fn serialize_returns_valid_json() {
         .unwrap();
 
     let gateway_config = get_test_gateway_config();
-    let (server_address, _handle) =
-        run_server(&gateway_config, get_test_syncing_state(), storage_reader).await.unwrap();
+    let (server_address, _handle) = run_server(
+        &gateway_config,
+        get_test_syncing_state(),
+        get_test_pending_data(),
+        storage_reader,
+    )
+    .await
+    .unwrap();
 
     let schema = get_starknet_spec_api_schema(
         &[
@@ -34,3 +34,19 @@ pub struct Block {
     pub header: BlockHeader,
     pub transactions: Transactions,
 }
+
+/// A block that is not accepted yet, so it has no hash, number or state root.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PendingBlock {
+    pub parent_hash: BlockHash,
+    pub sequencer_address: ContractAddress,
+    pub timestamp: BlockTimestamp,
+    pub transactions: Transactions,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum GeneralBlock {
+    Block(Block),
+    PendingBlock(PendingBlock),
+}
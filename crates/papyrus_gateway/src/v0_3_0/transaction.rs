@@ -246,6 +246,28 @@ pub struct TransactionReceipt {
     pub output: TransactionOutput,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PendingTransactionReceiptWithStatus {
+    pub status: TransactionStatus,
+    #[serde(flatten)]
+    pub receipt: PendingTransactionReceipt,
+}
+
+/// The receipt of a transaction in the pending block, which has no block hash or number yet.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PendingTransactionReceipt {
+    pub transaction_hash: TransactionHash,
+    #[serde(flatten)]
+    pub output: TransactionOutput,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum GeneralTransactionReceipt {
+    TransactionReceipt(TransactionReceiptWithStatus),
+    PendingTransactionReceipt(PendingTransactionReceiptWithStatus),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 #[serde(tag = "type")]
 pub enum TransactionOutput {
@@ -332,8 +354,11 @@ impl From<starknet_api::transaction::TransactionOutput> for TransactionOutput {
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Event {
-    pub block_hash: BlockHash,
-    pub block_number: BlockNumber,
+    // Events of the pending block have no block hash and number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<BlockHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
     pub transaction_hash: TransactionHash,
     #[serde(flatten)]
     pub event: starknet_api::transaction::Event,
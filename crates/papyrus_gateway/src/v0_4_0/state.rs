@@ -18,6 +18,20 @@ pub struct StateUpdate {
     pub state_diff: ThinStateDiff,
 }
 
+/// The state update of a block that is not accepted yet, so it has no block hash or new root.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct PendingStateUpdate {
+    pub old_root: GlobalRoot,
+    pub state_diff: ThinStateDiff,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum GeneralStateUpdate {
+    StateUpdate(StateUpdate),
+    PendingStateUpdate(PendingStateUpdate),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct ThinStateDiff {
     pub deployed_contracts: Vec<DeployedContract>,
@@ -3,6 +3,7 @@ mod gateway_test;
 
 use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
@@ -83,19 +84,25 @@ impl MonitoringServer {
         })
     }
 
-    /// Spawns a monitoring server.
-    pub async fn spawn_server(self) -> tokio::task::JoinHandle<Result<(), hyper::Error>> {
-        tokio::spawn(async move { self.run_server().await })
+    /// Spawns a monitoring server that stops gracefully once the shutdown signal resolves.
+    pub async fn spawn_server(
+        self,
+        shutdown_signal: impl Future<Output = ()> + Send + 'static,
+    ) -> tokio::task::JoinHandle<Result<(), hyper::Error>> {
+        tokio::spawn(async move { self.run_server(shutdown_signal).await })
     }
 
     #[instrument(
-        skip(self),
+        skip(self, shutdown_signal),
         fields(
             version = %self.version,
             config = %self.config,
             general_config_representation = %self.general_config_representation),
         level = "debug")]
-    async fn run_server(&self) -> std::result::Result<(), hyper::Error> {
+    async fn run_server(
+        &self,
+        shutdown_signal: impl Future<Output = ()>,
+    ) -> std::result::Result<(), hyper::Error> {
         let server_address = SocketAddr::from_str(&self.config.server_address)
             .expect("Configuration value for monitor server address should be valid");
         let app = app(
@@ -105,7 +112,10 @@ impl MonitoringServer {
             self.prometheus_handle.clone(),
         );
         debug!("Starting monitoring gateway.");
-        axum::Server::bind(&server_address).serve(app.into_make_service()).await
+        axum::Server::bind(&server_address)
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(shutdown_signal)
+            .await
     }
 }
 
@@ -0,0 +1,90 @@
+//! The audit subcommand of the node, which downloads the data of a range of blocks again and
+//! compares it to the stored data.
+
+#[cfg(test)]
+#[path = "audit_test.rs"]
+mod audit_test;
+
+use std::path::PathBuf;
+
+use clap::error::ErrorKind;
+use clap::{arg, value_parser, Arg, Command};
+use starknet_api::block::BlockNumber;
+
+/// The name of the subcommand, given as the first argument of the node.
+pub const AUDIT_SUBCOMMAND: &str = "audit";
+
+/// The arguments of the audit subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditArgs {
+    pub start_block: BlockNumber,
+    /// The first block after the audited blocks.
+    pub end_block: BlockNumber,
+    pub report_file: PathBuf,
+    /// The arguments that configure the node, starting with the name of the binary.
+    pub config_args: Vec<String>,
+}
+
+impl AuditArgs {
+    /// Returns whether the arguments of the node invoke the audit subcommand.
+    pub fn is_audit(args: &[String]) -> bool {
+        args.get(1).is_some_and(|arg| arg == AUDIT_SUBCOMMAND)
+    }
+
+    /// Parses the arguments of the node, which should invoke the audit subcommand.
+    pub fn parse(args: &[String]) -> Result<Self, clap::Error> {
+        let matches = audit_command().try_get_matches_from(&args[1..])?;
+        let start_block = BlockNumber(
+            *matches.get_one::<u64>("start_block").expect("The start block should be required."),
+        );
+        let end_block = BlockNumber(
+            *matches.get_one::<u64>("end_block").expect("The end block should be required."),
+        );
+        if end_block <= start_block {
+            return Err(audit_command().error(
+                ErrorKind::InvalidValue,
+                format!("The end block {end_block} should be after the start block {start_block}."),
+            ));
+        }
+        let report_file = matches
+            .get_one::<PathBuf>("report_file")
+            .expect("The report file should be required.")
+            .clone();
+        let config_args = args[..1]
+            .iter()
+            .chain(matches.get_many::<String>("config_args").into_iter().flatten())
+            .cloned()
+            .collect();
+        Ok(AuditArgs { start_block, end_block, report_file, config_args })
+    }
+}
+
+fn audit_command() -> Command {
+    Command::new(AUDIT_SUBCOMMAND)
+        .about(
+            "Downloads the blocks, state updates and classes of a range of blocks from the \
+             central source and compares them field by field to the stored data. The differing \
+             fields are written as a JSON report. The node shouldn't run during the audit.",
+        )
+        .arg(
+            arg!(--start_block <BLOCK_NUMBER> "The first audited block.")
+                .required(true)
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            arg!(--end_block <BLOCK_NUMBER> "The first block after the audited blocks.")
+                .required(true)
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            arg!(--report_file <PATH> "The file to write the report to.")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("config_args")
+                .num_args(0..)
+                .last(true)
+                .help("The configuration of the node, given after '--' as to the node itself."),
+        )
+}
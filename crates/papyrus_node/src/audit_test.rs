@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use clap::error::ErrorKind;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+
+use crate::audit::AuditArgs;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn parse_audit_args() {
+    let node_args = args(&[
+        "papyrus_node",
+        "audit",
+        "--start_block",
+        "10",
+        "--end_block",
+        "20",
+        "--report_file",
+        "report.json",
+        "--",
+        "--central.url",
+        "https://alpha4.starknet.io/",
+    ]);
+    assert!(AuditArgs::is_audit(&node_args));
+    assert_eq!(
+        AuditArgs::parse(&node_args).unwrap(),
+        AuditArgs {
+            start_block: BlockNumber(10),
+            end_block: BlockNumber(20),
+            report_file: PathBuf::from("report.json"),
+            config_args: args(&["papyrus_node", "--central.url", "https://alpha4.starknet.io/"]),
+        }
+    );
+}
+
+#[test]
+fn parse_audit_args_without_config_args() {
+    let node_args = args(&[
+        "papyrus_node",
+        "audit",
+        "--start_block",
+        "0",
+        "--end_block",
+        "1",
+        "--report_file",
+        "report.json",
+    ]);
+    assert_eq!(AuditArgs::parse(&node_args).unwrap().config_args, args(&["papyrus_node"]));
+}
+
+#[test]
+fn parse_invalid_audit_args() {
+    let missing_report_file =
+        args(&["papyrus_node", "audit", "--start_block", "0", "--end_block", "1"]);
+    assert_eq!(
+        AuditArgs::parse(&missing_report_file).unwrap_err().kind(),
+        ErrorKind::MissingRequiredArgument
+    );
+
+    let empty_range = args(&[
+        "papyrus_node",
+        "audit",
+        "--start_block",
+        "1",
+        "--end_block",
+        "1",
+        "--report_file",
+        "report.json",
+    ]);
+    assert_eq!(AuditArgs::parse(&empty_range).unwrap_err().kind(), ErrorKind::InvalidValue);
+}
+
+#[test]
+fn node_args_are_not_audit() {
+    assert!(!AuditArgs::is_audit(&args(&["papyrus_node", "--central.url", "audit"])));
+    assert!(!AuditArgs::is_audit(&args(&["papyrus_node"])));
+}
@@ -10,6 +10,12 @@ use tokio_stream::StreamExt;
 
 const STREAM_LENGTH: u64 = 10;
 
+// The URL of the feeder gateway, which may be given as the first argument, for example of the
+// feeder gateway server of a Papyrus node for running offline.
+fn central_url() -> String {
+    env::args().nth(1).unwrap_or_else(|| "https://alpha4.starknet.io/".to_owned())
+}
+
 #[tokio::main]
 async fn main() {
     let mut path = env::temp_dir();
@@ -18,7 +24,7 @@ async fn main() {
     fs::create_dir_all(path.clone()).expect("Should make a temporary `data` directory");
     let config = NodeConfig::load_and_process(vec![
         "--chain_id=SN_GOERLI".to_owned(),
-        "--central.url=https://alpha4.starknet.io/".to_owned(),
+        format!("--central.url={}", central_url()),
         format!("--storage.db_config.path_prefix={}", path.display()),
     ])
     .expect("Load config");
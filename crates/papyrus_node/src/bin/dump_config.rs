@@ -15,7 +15,11 @@ lazy_static! {
             &ChainId("SN_MAIN".to_string()),
             "The chain to follow. For more details see https://docs.starknet.io/documentation/architecture_and_concepts/Blocks/transactions/#chain-id.",
         ),
-        vec!["storage.db_config.chain_id".to_owned(), "gateway.chain_id".to_owned()],
+        vec![
+            "storage.db_config.chain_id".to_owned(),
+            "gateway.chain_id".to_owned(),
+            "sync.chain_id".to_owned(),
+        ],
     )];
 }
 
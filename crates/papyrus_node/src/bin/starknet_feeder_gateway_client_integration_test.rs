@@ -1,3 +1,5 @@
+use std::env;
+
 use papyrus_node::config::NodeConfig;
 use papyrus_node::version::VERSION_FULL;
 use starknet_api::block::BlockNumber;
@@ -5,11 +7,17 @@ use starknet_api::core::ClassHash;
 use starknet_api::hash::StarkHash;
 use starknet_client::reader::{StarknetFeederGatewayClient, StarknetReader};
 
+// The URL of the feeder gateway, which may be given as the first argument, for example of the
+// feeder gateway server of a Papyrus node for running offline.
+fn central_url() -> String {
+    env::args().nth(1).unwrap_or_else(|| "https://alpha4.starknet.io/".to_owned())
+}
+
 #[tokio::main]
 async fn main() {
     let config = NodeConfig::load_and_process(vec![
         "--chain_id=SN_GOERLI".to_owned(),
-        "--central.url=https://alpha4.starknet.io/".to_owned(),
+        format!("--central.url={}", central_url()),
     ])
     .expect("Load config");
     let starknet_client = StarknetFeederGatewayClient::new(
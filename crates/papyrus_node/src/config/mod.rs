@@ -14,6 +14,7 @@ use itertools::chain;
 use papyrus_config::dumping::{append_sub_config_name, SerializeConfig};
 use papyrus_config::loading::load_and_process_config;
 use papyrus_config::{ConfigError, ParamPath, SerializedParam};
+use papyrus_feeder_gateway::FeederGatewayConfig;
 use papyrus_gateway::GatewayConfig;
 use papyrus_monitoring_gateway::MonitoringGatewayConfig;
 use papyrus_storage::db::DbConfig;
@@ -35,6 +36,7 @@ pub struct NodeConfig {
     pub gateway: GatewayConfig,
     pub central: CentralSourceConfig,
     pub monitoring_gateway: MonitoringGatewayConfig,
+    pub feeder_gateway: FeederGatewayConfig,
     pub storage: StorageConfig,
     /// None if the syncing should be disabled.
     pub sync: Option<SyncConfig>,
@@ -47,6 +49,7 @@ impl Default for NodeConfig {
             central: CentralSourceConfig::default(),
             gateway: GatewayConfig::default(),
             monitoring_gateway: MonitoringGatewayConfig::default(),
+            feeder_gateway: FeederGatewayConfig::default(),
             storage: StorageConfig::default(),
             sync: Some(SyncConfig::default()),
         }
@@ -57,10 +60,11 @@ impl SerializeConfig for NodeConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
         chain!(
             append_sub_config_name(self.central.dump(), "central"),
+            append_sub_config_name(self.feeder_gateway.dump(), "feeder_gateway"),
             append_sub_config_name(self.gateway.dump(), "gateway"),
             append_sub_config_name(self.monitoring_gateway.dump(), "monitoring_gateway"),
             append_sub_config_name(self.storage.dump(), "storage"),
-            match self.sync {
+            match &self.sync {
                 None => BTreeMap::new(),
                 Some(sync_config) => append_sub_config_name(sync_config.dump(), "sync"),
             },
@@ -1,3 +1,4 @@
+pub mod audit;
 #[allow(unused_imports)]
 pub mod config;
 pub mod version;
@@ -1,26 +1,94 @@
 use std::env::args;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::BufWriter;
 use std::sync::Arc;
+use std::time::Duration;
 
+use jsonrpsee::server::ServerHandle;
+use papyrus_common::pending_data::PendingData;
 use papyrus_common::SyncingState;
 use papyrus_config::ConfigError;
+use papyrus_feeder_gateway::FeederGatewayServer;
 use papyrus_gateway::run_server;
 use papyrus_monitoring_gateway::MonitoringServer;
+use papyrus_node::audit::AuditArgs;
 use papyrus_node::config::NodeConfig;
 use papyrus_node::version::VERSION_FULL;
 use papyrus_storage::{open_storage, StorageReader, StorageWriter};
-use papyrus_sync::{CentralError, CentralSource, StateSync, StateSyncError};
-use tokio::sync::RwLock;
-use tracing::info;
+use papyrus_sync::{
+    audit_blocks, ArchiveReader, ArchiveSource, ArchiveStateSync, AuditReport, CentralError,
+    CentralSource, Checkpoints, StateSync, StateSyncError,
+};
+use starknet_api::block::BlockNumber;
+use starknet_client::reader::{StarknetFeederGatewayClient, StarknetReader};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
 use tracing::metadata::LevelFilter;
+use tracing::{error, info, warn};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
+use url::Url;
 
 // TODO(yair): Add to config.
 const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
 
+// The exit code of the node when it fails before all the components are running.
+const SETUP_FAILURE_EXIT_CODE: i32 = 1;
+
+// The exit code of the audit subcommand when the stored data differs from the central source.
+const AUDIT_DIFFERENCES_EXIT_CODE: i32 = 5;
+
+/// A component of the node that runs until the node shuts down.
+#[derive(Clone, Copy, Debug)]
+enum Component {
+    Gateway,
+    MonitoringGateway,
+    FeederGateway,
+    Sync,
+}
+
+impl Component {
+    // The exit code of the node when this component fails.
+    fn exit_code(self) -> i32 {
+        match self {
+            Component::Gateway => 2,
+            Component::MonitoringGateway => 3,
+            Component::Sync => 4,
+            Component::FeederGateway => 6,
+        }
+    }
+}
+
+impl Display for Component {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Component::Gateway => write!(f, "JSON-RPC server"),
+            Component::MonitoringGateway => write!(f, "monitoring server"),
+            Component::FeederGateway => write!(f, "feeder gateway server"),
+            Component::Sync => write!(f, "sync"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("The {component} failed")]
+struct ComponentError {
+    component: Component,
+    source: anyhow::Error,
+}
+
 async fn run_threads(config: NodeConfig) -> anyhow::Result<()> {
     let (storage_reader, storage_writer) = open_storage(config.storage.db_config.clone())?;
 
+    // Every component stops once a shutdown is requested, either by a signal or by the failure of
+    // another component.
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+    let shutdown_sender = Arc::new(shutdown_sender);
+    let sigterm = signal(SignalKind::terminate())?;
+    let signal_handle = tokio::spawn(request_shutdown_on_signal(sigterm, shutdown_sender.clone()));
+
     // Monitoring server.
     let monitoring_server = MonitoringServer::new(
         config.monitoring_gateway.clone(),
@@ -28,46 +96,235 @@ async fn run_threads(config: NodeConfig) -> anyhow::Result<()> {
         storage_reader.clone(),
         VERSION_FULL,
     )?;
-    let monitoring_server_handle = monitoring_server.spawn_server().await;
+    let monitoring_server_handle =
+        monitoring_server.spawn_server(shutdown_requested(shutdown_receiver.clone())).await;
+
+    // Feeder gateway server.
+    let feeder_gateway_server =
+        FeederGatewayServer::new(config.feeder_gateway.clone(), storage_reader.clone());
+    let feeder_gateway_server_handle =
+        feeder_gateway_server.spawn_server(shutdown_requested(shutdown_receiver.clone())).await;
 
     // The sync is the only writer of the syncing state.
     let shared_syncing_state = Arc::new(RwLock::new(SyncingState::default()));
+    // The sync is also the only writer of the pending data.
+    let pending_data = Arc::new(RwLock::new(PendingData::default()));
     // JSON-RPC server.
-    let (_, server_handle) =
-        run_server(&config.gateway, shared_syncing_state.clone(), storage_reader.clone()).await?;
-    let server_handle_future = tokio::spawn(server_handle.stopped());
+    let (_, server_handle) = run_server(
+        &config.gateway,
+        shared_syncing_state.clone(),
+        pending_data.clone(),
+        storage_reader.clone(),
+    )
+    .await?;
+    let server_handle_future = tokio::spawn(run_gateway_until_shutdown(
+        server_handle,
+        config.gateway.shutdown_timeout,
+        shutdown_receiver.clone(),
+    ));
 
     // Sync task.
-    let sync_future =
-        run_sync(config, shared_syncing_state, storage_reader.clone(), storage_writer);
+    let checkpoints = match &config.sync {
+        Some(sync_config) => Checkpoints::load(
+            &config.storage.db_config.chain_id,
+            sync_config.checkpoints_file.as_deref(),
+        )?,
+        None => Checkpoints::default(),
+    };
+    let sync_future = run_sync(
+        config,
+        shared_syncing_state,
+        pending_data,
+        storage_reader.clone(),
+        storage_writer,
+        shutdown_receiver,
+        checkpoints,
+    );
     let sync_handle = tokio::spawn(sync_future);
 
-    let (_, _, sync_result) =
-        tokio::try_join!(server_handle_future, monitoring_server_handle, sync_handle)?;
+    let (gateway_result, monitoring_result, feeder_gateway_result, sync_result) = tokio::join!(
+        wait_for_component(Component::Gateway, server_handle_future, &shutdown_sender),
+        wait_for_component(
+            Component::MonitoringGateway,
+            monitoring_server_handle,
+            &shutdown_sender
+        ),
+        wait_for_component(
+            Component::FeederGateway,
+            feeder_gateway_server_handle,
+            &shutdown_sender
+        ),
+        wait_for_component(Component::Sync, sync_handle, &shutdown_sender),
+    );
+    signal_handle.abort();
+    gateway_result?;
+    monitoring_result?;
+    feeder_gateway_result?;
     sync_result?;
+    info!("Node stopped.");
     return Ok(());
 
     async fn run_sync(
         config: NodeConfig,
         shared_syncing_state: Arc<RwLock<SyncingState>>,
+        pending_data: Arc<RwLock<PendingData>>,
         storage_reader: StorageReader,
         storage_writer: StorageWriter,
+        shutdown_receiver: watch::Receiver<bool>,
+        checkpoints: Checkpoints,
     ) -> Result<(), StateSyncError> {
         let Some(sync_config) = config.sync else { return Ok(()) };
+        // A file URL points to a local archive directory instead of a feeder gateway.
+        if let Some(archive_dir) =
+            Url::parse(&config.central.url).ok().and_then(|url| url.to_file_path().ok())
+        {
+            info!("Syncing from the archive directory {}.", archive_dir.display());
+            let archive_source = ArchiveSource::new(
+                archive_dir,
+                config.central.concurrent_requests,
+                storage_reader.clone(),
+            );
+            let mut sync = ArchiveStateSync::new(
+                sync_config,
+                shared_syncing_state,
+                pending_data,
+                archive_source,
+                storage_reader,
+                storage_writer,
+            );
+            sync.set_shutdown_receiver(shutdown_receiver);
+            sync.set_checkpoints(checkpoints);
+            return sync.run().await;
+        }
         let central_source =
             CentralSource::new(config.central.clone(), VERSION_FULL, storage_reader.clone())
                 .map_err(CentralError::ClientCreation)?;
         let mut sync = StateSync::new(
             sync_config,
             shared_syncing_state,
+            pending_data,
             central_source,
             storage_reader.clone(),
             storage_writer,
         );
+        sync.set_shutdown_receiver(shutdown_receiver);
+        sync.set_checkpoints(checkpoints);
         sync.run().await
     }
 }
 
+// Waits for a component to finish, and requests a shutdown of the rest of the node if it failed.
+async fn wait_for_component<E: Into<anyhow::Error>>(
+    component: Component,
+    handle: JoinHandle<Result<(), E>>,
+    shutdown_sender: &watch::Sender<bool>,
+) -> Result<(), ComponentError> {
+    let result = match handle.await {
+        Ok(result) => result.map_err(Into::into),
+        Err(join_error) => Err(join_error.into()),
+    };
+    result.map_err(|source| {
+        error!("The {component} failed, shutting down the node: {source:#}");
+        shutdown_sender.send_replace(true);
+        ComponentError { component, source }
+    })
+}
+
+// Stops the JSON-RPC server once a shutdown is requested, waiting up to the timeout for the
+// in-flight requests to finish.
+async fn run_gateway_until_shutdown(
+    server_handle: ServerHandle,
+    shutdown_timeout: Duration,
+    shutdown_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    tokio::select! {
+        _ = server_handle.clone().stopped() => {
+            return Err(anyhow::anyhow!("The server stopped unexpectedly."));
+        }
+        _ = shutdown_requested(shutdown_receiver) => {}
+    }
+    info!("Stopping the JSON-RPC server.");
+    server_handle.stop()?;
+    if tokio::time::timeout(shutdown_timeout, server_handle.stopped()).await.is_err() {
+        warn!("The JSON-RPC server didn't finish the in-flight requests in {shutdown_timeout:?}.");
+    }
+    Ok(())
+}
+
+// Resolves once a shutdown of the node is requested.
+async fn shutdown_requested(mut shutdown_receiver: watch::Receiver<bool>) {
+    // The sender lives until all the components stop, so an error can't happen before a shutdown.
+    let _ = shutdown_receiver.wait_for(|shutdown| *shutdown).await;
+}
+
+// Requests a shutdown of the node upon SIGINT or SIGTERM.
+async fn request_shutdown_on_signal(
+    mut sigterm: Signal,
+    shutdown_sender: Arc<watch::Sender<bool>>,
+) {
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT, shutting down the node."),
+        _ = sigterm.recv() => info!("Received SIGTERM, shutting down the node."),
+    }
+    shutdown_sender.send_replace(true);
+}
+
+// Runs the audit subcommand, which exits with a failure if the stored data of the audited blocks
+// differs from the central source.
+async fn run_audit(args: &[String]) -> anyhow::Result<()> {
+    let audit_args = AuditArgs::parse(args).unwrap_or_else(|clap_err| clap_err.exit());
+    let config = NodeConfig::load_and_process(audit_args.config_args);
+    if let Err(ConfigError::CommandInput(clap_err)) = config {
+        clap_err.exit();
+    }
+    let config = config?;
+    configure_tracing();
+    let (storage_reader, _storage_writer) = open_storage(config.storage.db_config)?;
+    info!("Auditing blocks [{}, {}).", audit_args.start_block, audit_args.end_block);
+    let report = match Url::parse(&config.central.url).ok().and_then(|url| url.to_file_path().ok())
+    {
+        Some(archive_dir) => {
+            audit(
+                ArchiveReader::new(archive_dir),
+                storage_reader,
+                audit_args.start_block,
+                audit_args.end_block,
+            )
+            .await?
+        }
+        None => {
+            let starknet_client = StarknetFeederGatewayClient::new(
+                &config.central.url,
+                config.central.http_headers,
+                VERSION_FULL,
+                config.central.retry_config,
+            )?;
+            audit(starknet_client, storage_reader, audit_args.start_block, audit_args.end_block)
+                .await?
+        }
+    };
+    serde_json::to_writer_pretty(BufWriter::new(File::create(&audit_args.report_file)?), &report)?;
+    if !report.is_clean() {
+        error!(
+            "Found {} fields that differ from the central source, see {}.",
+            report.differences.len(),
+            audit_args.report_file.display()
+        );
+        std::process::exit(AUDIT_DIFFERENCES_EXIT_CODE);
+    }
+    info!("The audited blocks match the central source.");
+    return Ok(());
+
+    async fn audit<TStarknetClient: StarknetReader + Send + Sync>(
+        starknet_client: TStarknetClient,
+        storage_reader: StorageReader,
+        start_block: BlockNumber,
+        end_block: BlockNumber,
+    ) -> anyhow::Result<AuditReport> {
+        Ok(audit_blocks(&starknet_client, &storage_reader, start_block, end_block).await?)
+    }
+}
+
 // TODO(yair): add dynamic level filtering.
 // TODO(dan): filter out logs from dependencies (happens when RUST_LOG=DEBUG)
 // TODO(yair): define and implement configurable filtering.
@@ -83,11 +340,22 @@ fn configure_tracing() {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let config = NodeConfig::load_and_process(args().collect());
+    let args: Vec<String> = args().collect();
+    if AuditArgs::is_audit(&args) {
+        return run_audit(&args).await;
+    }
+    let config = NodeConfig::load_and_process(args);
     if let Err(ConfigError::CommandInput(clap_err)) = config {
         clap_err.exit();
     }
     configure_tracing();
     info!("Booting up.");
-    run_threads(config?).await
+    if let Err(err) = run_threads(config?).await {
+        let exit_code = err
+            .downcast_ref::<ComponentError>()
+            .map_or(SETUP_FAILURE_EXIT_CODE, |err| err.component.exit_code());
+        error!("{err:#}");
+        std::process::exit(exit_code);
+    }
+    Ok(())
 }
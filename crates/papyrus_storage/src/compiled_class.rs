@@ -47,7 +47,11 @@ use crate::{MarkerKind, MarkersTable, StorageError, StorageResult, StorageTxn};
 pub trait CasmStorageReader {
     /// Returns the Cairo assembly of a class given its Sierra class hash.
     fn get_casm(&self, class_hash: &ClassHash) -> StorageResult<Option<CasmContractClass>>;
-    /// The block marker is the first block number that doesn't exist yet.
+    /// Returns whether the Cairo assembly of a class is stored, without reading it.
+    fn has_casm(&self, class_hash: &ClassHash) -> StorageResult<bool>;
+    /// The block marker is the first block number that doesn't exist yet. A block exists once the
+    /// Cairo assembly of all of its declared classes is stored, regardless of the order in which
+    /// they were stored.
     ///
     /// Note: If the last blocks don't contain any declared classes, the marker will point at the
     /// block after the last block that had declared classes.
@@ -70,6 +74,11 @@ impl<'env, Mode: TransactionKind> CasmStorageReader for StorageTxn<'env, Mode> {
         Ok(casm_table.get(&self.txn, class_hash)?)
     }
 
+    fn has_casm(&self, class_hash: &ClassHash) -> StorageResult<bool> {
+        let casm_table = self.txn.open_table(&self.tables.casms)?;
+        Ok(casm_table.contains_key(&self.txn, class_hash)?)
+    }
+
     fn get_compiled_class_marker(&self) -> StorageResult<BlockNumber> {
         let markers_table = self.txn.open_table(&self.tables.markers)?;
         Ok(markers_table.get(&self.txn, &MarkerKind::CompiledClass)?.unwrap_or_default())
@@ -88,7 +97,7 @@ impl<'env> CasmStorageWriter for StorageTxn<'env, RW> {
                 StorageError::from(err)
             }
         })?;
-        update_marker(&self.txn, &markers_table, &state_diff_table, class_hash)?;
+        update_marker(&self.txn, &markers_table, &state_diff_table, &casm_table)?;
         Ok(self)
     }
 }
@@ -97,21 +106,26 @@ fn update_marker<'env>(
     txn: &DbTransaction<'env, RW>,
     markers_table: &'env MarkersTable<'env>,
     state_diffs_table: &'env TableHandle<'_, BlockNumber, ThinStateDiff>,
-    class_hash: &ClassHash,
+    casm_table: &'env TableHandle<'_, ClassHash, CasmContractClass>,
 ) -> StorageResult<()> {
-    // The marker needs to update if we reached the last class from the state diff. We can continue
-    // advancing it if the next blocks don't have declared classes.
+    // The marker needs to update if all the classes of the state diff are stored. We can continue
+    // advancing it if the next blocks don't have declared classes or their classes are already
+    // stored.
     let mut block_number = markers_table.get(txn, &MarkerKind::CompiledClass)?.unwrap_or_default();
     loop {
         let Some(state_diff) = state_diffs_table.get(txn, &block_number)? else {
             break;
         };
-        if let Some((last_class_hash, _)) = state_diff.declared_classes.last() {
-            // Not the last class in the state diff, keep the current marker.
-            if last_class_hash != class_hash {
+        let mut has_missing_class = false;
+        for class_hash in state_diff.declared_classes.keys() {
+            if !casm_table.contains_key(txn, class_hash)? {
+                has_missing_class = true;
                 break;
             }
         }
+        if has_missing_class {
+            break;
+        }
         block_number = block_number.next();
         markers_table.upsert(txn, &MarkerKind::CompiledClass, &block_number)?;
     }
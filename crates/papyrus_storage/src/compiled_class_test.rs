@@ -1,10 +1,15 @@
 use assert_matches::assert_matches;
 use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use indexmap::indexmap;
 use pretty_assertions::assert_eq;
-use starknet_api::core::ClassHash;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::hash::StarkHash;
+use starknet_api::state::{ContractClass, StateDiff};
 use test_utils::read_json_file;
 
 use crate::compiled_class::{CasmStorageReader, CasmStorageWriter};
+use crate::state::StateStorageWriter;
 use crate::test_utils::get_test_storage;
 use crate::StorageError;
 
@@ -48,3 +53,50 @@ fn casm_rewrite() {
 
     assert_matches!(err, StorageError::CompiledClassReWrite{class_hash} if class_hash == ClassHash::default());
 }
+
+#[test]
+fn marker_advances_when_all_block_classes_are_stored() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let first_class_hash = ClassHash(StarkHash::from(1u8));
+    let second_class_hash = ClassHash(StarkHash::from(2u8));
+    let state_diff = StateDiff {
+        declared_classes: indexmap! {
+            first_class_hash => (CompiledClassHash::default(), ContractClass::default()),
+            second_class_hash => (CompiledClassHash::default(), ContractClass::default()),
+        },
+        ..StateDiff::default()
+    };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff, indexmap! {})
+        .unwrap()
+        .append_state_diff(BlockNumber(1), StateDiff::default(), indexmap! {})
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // Storing the last class of the block first doesn't advance the marker.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_casm(&second_class_hash, &CasmContractClass::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_compiled_class_marker().unwrap(), BlockNumber(0));
+    assert!(txn.has_casm(&second_class_hash).unwrap());
+    assert!(!txn.has_casm(&first_class_hash).unwrap());
+    drop(txn);
+
+    // Storing the rest of the classes advances the marker, also over blocks without classes.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_casm(&first_class_hash, &CasmContractClass::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_compiled_class_marker().unwrap(), BlockNumber(2));
+}
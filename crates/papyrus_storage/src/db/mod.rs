@@ -270,6 +270,16 @@ impl<'env, 'txn, K: StorageSerde, V: StorageSerde> TableHandle<'env, K, V> {
         Ok(Some(value))
     }
 
+    // Checks whether the key exists without deserializing its value.
+    pub(crate) fn contains_key<Mode: TransactionKind>(
+        &'env self,
+        txn: &'env DbTransaction<'env, Mode>,
+        key: &K,
+    ) -> DbResult<bool> {
+        let bin_key = key.serialize()?;
+        Ok(txn.txn.get::<Cow<'env, [u8]>>(&self.database, &bin_key)?.is_some())
+    }
+
     pub(crate) fn upsert(
         &'env self,
         txn: &DbTransaction<'env, RW>,
@@ -4,7 +4,10 @@ use starknet_api::block::{BlockHash, BlockHeader, BlockNumber};
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
 
+use crate::body::BodyStorageReader;
+use crate::compiled_class::CasmStorageReader;
 use crate::header::{HeaderStorageReader, HeaderStorageWriter, StarknetVersion};
+use crate::state::StateStorageReader;
 use crate::test_utils::get_test_storage;
 use crate::{StorageError, StorageWriter};
 
@@ -121,6 +124,39 @@ async fn get_reverted_block_number_by_hash_returns_none() {
     );
 }
 
+#[tokio::test]
+async fn initialize_markers() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    writer.begin_rw_txn().unwrap().initialize_markers(BlockNumber(5)).unwrap().commit().unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(5));
+    assert_eq!(txn.get_body_marker().unwrap(), BlockNumber(5));
+    assert_eq!(txn.get_state_marker().unwrap(), BlockNumber(5));
+    assert_eq!(txn.get_compiled_class_marker().unwrap(), BlockNumber(5));
+    drop(txn);
+
+    // Append a header right after the initialized markers.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(5), &BlockHeader::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_header_marker().unwrap(), BlockNumber(6));
+
+    // Initializing the markers of a non empty storage fails.
+    let Err(err) = writer.begin_rw_txn().unwrap().initialize_markers(BlockNumber(2)) else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(
+        err,
+        StorageError::MarkerMismatch { expected, found }
+        if expected == BlockNumber(0) && found == BlockNumber(6)
+    );
+}
+
 fn append_2_headers(writer: &mut StorageWriter) {
     writer
         .begin_rw_txn()
@@ -221,6 +221,27 @@ impl<'env> StorageTxn<'env, RW> {
     pub fn commit(self) -> StorageResult<()> {
         Ok(self.txn.commit()?)
     }
+
+    /// Sets the header, body, state and compiled class markers of an empty storage to the given
+    /// block number, so that the data is appended starting from this block instead of the genesis
+    /// block.
+    // To enforce that no commit happen after a failure, we consume and return Self on success.
+    pub fn initialize_markers(self, block_number: BlockNumber) -> StorageResult<Self> {
+        let markers_table = self.txn.open_table(&self.tables.markers)?;
+        for marker_kind in
+            [MarkerKind::Header, MarkerKind::Body, MarkerKind::State, MarkerKind::CompiledClass]
+        {
+            let marker = markers_table.get(&self.txn, &marker_kind)?.unwrap_or_default();
+            if marker != BlockNumber(0) {
+                return Err(StorageError::MarkerMismatch {
+                    expected: BlockNumber(0),
+                    found: marker,
+                });
+            }
+            markers_table.upsert(&self.txn, &marker_kind, &block_number)?;
+        }
+        Ok(self)
+    }
 }
 
 /// Returns the names of the tables in the storage.
@@ -0,0 +1,354 @@
+//! Auditing of the stored data against the central source, for detecting a corruption of the
+//! storage. The data of each block is downloaded again and compared field by field to the stored
+//! data, producing a report of the differences.
+
+#[cfg(test)]
+#[path = "audit_test.rs"]
+mod audit_test;
+
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use indexmap::IndexMap;
+use papyrus_storage::body::events::ThinTransactionOutput;
+use papyrus_storage::body::{BodyStorageReader, TransactionIndex};
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::db::RO;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageError, StorageReader, StorageTxn};
+use serde::Serialize;
+use serde_json::Value;
+use starknet_api::block::{Block, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::state::{StateDiff, StateNumber, ThinStateDiff};
+use starknet_api::transaction::TransactionOffsetInBlock;
+use starknet_client::reader::{ReaderClientError, StarknetReader};
+use tracing::{debug, info};
+
+use crate::sort_state_diff;
+use crate::sources::{client_to_central_state_diff, ApiContractClass};
+
+/// The kinds of the audited data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditedData {
+    Header,
+    StarknetVersion,
+    Transactions,
+    TransactionHashes,
+    TransactionOutputs,
+    Events,
+    StateDiff,
+    ClassDefinition,
+    CompiledClass,
+}
+
+/// A field whose stored value differs from its value in the central source. A value that is
+/// missing is null.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldDifference {
+    pub block_number: BlockNumber,
+    pub data: AuditedData,
+    /// The hash of the class, for class definitions and compiled classes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_hash: Option<ClassHash>,
+    /// A JSON pointer to the field in the JSON representation of the data.
+    pub path: String,
+    pub stored: Value,
+    pub central: Value,
+}
+
+/// The differences between the storage and the central source in the audited blocks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AuditReport {
+    pub start_block: BlockNumber,
+    /// The first block after the audited blocks.
+    pub end_block: BlockNumber,
+    pub differences: Vec<FieldDifference>,
+}
+
+impl AuditReport {
+    /// Returns whether the stored data matches the central source.
+    pub fn is_clean(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuditError {
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    #[error(transparent)]
+    ClientError(#[from] ReaderClientError),
+    #[error("Block {block_number} was not found in the central source.")]
+    BlockNotFound { block_number: BlockNumber },
+    #[error("The state update of block {block_number} was not found in the central source.")]
+    StateUpdateNotFound { block_number: BlockNumber },
+    #[error("Class {class_hash} was not found in the central source.")]
+    ClassNotFound { class_hash: ClassHash },
+    #[error("Compiled class {class_hash} was not found in the central source.")]
+    CompiledClassNotFound { class_hash: ClassHash },
+    #[error("Failed to serialize the audited data: {0}")]
+    SerializationError(#[from] serde_json::Error),
+}
+
+/// Downloads the data of the blocks in [start_block, end_block) from the central source and
+/// compares it to the stored data: the headers, the transactions with their outputs and events,
+/// the state diffs and the definitions of the classes declared in the blocks.
+pub async fn audit_blocks<TStarknetClient: StarknetReader + Send + Sync>(
+    starknet_client: &TStarknetClient,
+    storage_reader: &StorageReader,
+    start_block: BlockNumber,
+    end_block: BlockNumber,
+) -> Result<AuditReport, AuditError> {
+    let mut report = AuditReport { start_block, end_block, differences: vec![] };
+    for block_number in start_block.iter_up_to(end_block) {
+        let mut block_audit = BlockAudit { block_number, differences: &mut report.differences };
+        let n_differences = block_audit.differences.len();
+        block_audit.audit_block(starknet_client, storage_reader).await?;
+        block_audit.audit_state_update(starknet_client, storage_reader).await?;
+        let n_block_differences = report.differences.len() - n_differences;
+        if n_block_differences == 0 {
+            debug!("Block {block_number} matches the central source.");
+        } else {
+            info!("Block {block_number} has {n_block_differences} differing fields.");
+        }
+    }
+    Ok(report)
+}
+
+// Collects the differences found in a single block.
+struct BlockAudit<'a> {
+    block_number: BlockNumber,
+    differences: &'a mut Vec<FieldDifference>,
+}
+
+impl BlockAudit<'_> {
+    async fn audit_block<TStarknetClient: StarknetReader + Send + Sync>(
+        &mut self,
+        starknet_client: &TStarknetClient,
+        storage_reader: &StorageReader,
+    ) -> Result<(), AuditError> {
+        let block_number = self.block_number;
+        let client_block = starknet_client
+            .block(block_number)
+            .await?
+            .ok_or(AuditError::BlockNotFound { block_number })?;
+        let (Block { header, body }, starknet_version) =
+            client_block.to_starknet_api_block_and_version()?;
+
+        let txn = storage_reader.begin_ro_txn()?;
+        self.compare(AuditedData::Header, None, txn.get_block_header(block_number)?, header)?;
+        self.compare(
+            AuditedData::StarknetVersion,
+            None,
+            txn.get_starknet_version(block_number)?.map(|version| version.0),
+            starknet_version,
+        )?;
+        let transactions: Vec<_> =
+            body.transactions.into_iter().zip(body.transaction_execution_statuses).collect();
+        self.compare(
+            AuditedData::Transactions,
+            None,
+            txn.get_block_transactions(block_number)?,
+            transactions,
+        )?;
+        self.compare(
+            AuditedData::TransactionHashes,
+            None,
+            txn.get_block_transaction_hashes(block_number)?,
+            body.transaction_hashes,
+        )?;
+        let events: Vec<_> =
+            body.transaction_outputs.iter().map(|output| output.events().to_vec()).collect();
+        let outputs: Vec<_> =
+            body.transaction_outputs.into_iter().map(ThinTransactionOutput::from).collect();
+        self.compare(
+            AuditedData::TransactionOutputs,
+            None,
+            txn.get_block_transaction_outputs(block_number)?,
+            outputs,
+        )?;
+        self.compare(AuditedData::Events, None, get_block_events(&txn, block_number)?, events)?;
+        Ok(())
+    }
+
+    async fn audit_state_update<TStarknetClient: StarknetReader + Send + Sync>(
+        &mut self,
+        starknet_client: &TStarknetClient,
+        storage_reader: &StorageReader,
+    ) -> Result<(), AuditError> {
+        let block_number = self.block_number;
+        let state_update = starknet_client
+            .state_update(block_number)
+            .await?
+            .ok_or(AuditError::StateUpdateNotFound { block_number })?;
+        let mut classes = IndexMap::new();
+        for class_hash in state_update.state_diff.class_hashes() {
+            let class = starknet_client
+                .class_by_hash(class_hash)
+                .await?
+                .ok_or(AuditError::ClassNotFound { class_hash })?;
+            classes.insert(class_hash, ApiContractClass::from(class));
+        }
+        let (mut state_diff, deployed_contract_class_definitions) =
+            client_to_central_state_diff(state_update.state_diff, classes);
+        sort_state_diff(&mut state_diff);
+        let mut compiled_classes = Vec::with_capacity(state_diff.declared_classes.len());
+        for class_hash in state_diff.declared_classes.keys() {
+            let compiled_class = starknet_client
+                .compiled_class_by_hash(*class_hash)
+                .await?
+                .ok_or(AuditError::CompiledClassNotFound { class_hash: *class_hash })?;
+            compiled_classes.push((*class_hash, compiled_class));
+        }
+
+        let txn = storage_reader.begin_ro_txn()?;
+        let StateDiff {
+            deployed_contracts,
+            storage_diffs,
+            declared_classes,
+            deprecated_declared_classes,
+            nonces,
+            replaced_classes,
+        } = state_diff;
+        self.compare(
+            AuditedData::StateDiff,
+            None,
+            txn.get_state_diff(block_number)?,
+            ThinStateDiff {
+                deployed_contracts,
+                storage_diffs,
+                declared_classes: declared_classes
+                    .iter()
+                    .map(|(class_hash, (compiled_class_hash, _class))| {
+                        (*class_hash, *compiled_class_hash)
+                    })
+                    .collect(),
+                deprecated_declared_classes: deprecated_declared_classes.keys().copied().collect(),
+                nonces,
+                replaced_classes,
+            },
+        )?;
+
+        let state_reader = txn.get_state_reader()?;
+        let state_number = StateNumber::right_after_block(block_number);
+        for (class_hash, (_compiled_class_hash, class)) in declared_classes {
+            self.compare(
+                AuditedData::ClassDefinition,
+                Some(class_hash),
+                state_reader.get_class_definition_at(state_number, &class_hash)?,
+                class,
+            )?;
+        }
+        for (class_hash, class) in
+            deprecated_declared_classes.into_iter().chain(deployed_contract_class_definitions)
+        {
+            self.compare(
+                AuditedData::ClassDefinition,
+                Some(class_hash),
+                state_reader.get_deprecated_class_definition_at(state_number, &class_hash)?,
+                class,
+            )?;
+        }
+        for (class_hash, compiled_class) in compiled_classes {
+            self.compare::<CasmContractClass>(
+                AuditedData::CompiledClass,
+                Some(class_hash),
+                txn.get_casm(&class_hash)?,
+                compiled_class,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Records the fields in which the stored value differs from the central one.
+    fn compare<T: Serialize>(
+        &mut self,
+        data: AuditedData,
+        class_hash: Option<ClassHash>,
+        stored: Option<T>,
+        central: T,
+    ) -> Result<(), AuditError> {
+        let stored = serde_json::to_value(stored)?;
+        let central = serde_json::to_value(central)?;
+        let mut differing_fields = vec![];
+        diff_values(String::new(), stored, central, &mut differing_fields);
+        self.differences.extend(differing_fields.into_iter().map(|(path, stored, central)| {
+            FieldDifference {
+                block_number: self.block_number,
+                data,
+                class_hash,
+                path,
+                stored,
+                central,
+            }
+        }));
+        Ok(())
+    }
+}
+
+// Returns the events of each of the stored transactions of the block, or None if the body of the
+// block isn't stored.
+fn get_block_events(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+) -> Result<Option<Vec<Vec<starknet_api::transaction::Event>>>, StorageError> {
+    let Some(transaction_hashes) = txn.get_block_transaction_hashes(block_number)? else {
+        return Ok(None);
+    };
+    let mut events = Vec::with_capacity(transaction_hashes.len());
+    for offset in 0..transaction_hashes.len() {
+        let transaction_index = TransactionIndex(block_number, TransactionOffsetInBlock(offset));
+        events.push(txn.get_transaction_events(transaction_index)?.unwrap_or_default());
+    }
+    Ok(Some(events))
+}
+
+// Collects the paths, in JSON pointer notation, of the differing leaves of two JSON values together
+// with the differing values. Objects and arrays are compared by their keys and indices.
+fn diff_values(
+    path: String,
+    stored: Value,
+    central: Value,
+    differences: &mut Vec<(String, Value, Value)>,
+) {
+    match (stored, central) {
+        (Value::Object(mut stored), Value::Object(central)) => {
+            for (key, central_value) in central {
+                let stored_value = stored.remove(&key).unwrap_or(Value::Null);
+                diff_values(
+                    json_pointer_child(&path, &key),
+                    stored_value,
+                    central_value,
+                    differences,
+                );
+            }
+            for (key, stored_value) in stored {
+                differences.push((json_pointer_child(&path, &key), stored_value, Value::Null));
+            }
+        }
+        (Value::Array(stored), Value::Array(central)) => {
+            let len = stored.len().max(central.len());
+            let mut stored = stored.into_iter();
+            let mut central = central.into_iter();
+            for index in 0..len {
+                diff_values(
+                    json_pointer_child(&path, &index.to_string()),
+                    stored.next().unwrap_or(Value::Null),
+                    central.next().unwrap_or(Value::Null),
+                    differences,
+                );
+            }
+        }
+        (stored, central) => {
+            if stored != central {
+                differences.push((path, stored, central));
+            }
+        }
+    }
+}
+
+// Appends a key to a JSON pointer, escaping it as defined in RFC 6901.
+fn json_pointer_child(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
@@ -0,0 +1,176 @@
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use indexmap::IndexMap;
+use papyrus_storage::body::BodyStorageWriter;
+use papyrus_storage::compiled_class::CasmStorageWriter;
+use papyrus_storage::header::{HeaderStorageWriter, StarknetVersion};
+use papyrus_storage::state::StateStorageWriter;
+use papyrus_storage::test_utils::get_test_storage;
+use papyrus_storage::StorageReader;
+use pretty_assertions::assert_eq;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, Nonce};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_client::reader::{
+    Block, ContractClass, GenericContractClass, MockStarknetReader, StateUpdate,
+};
+use tempfile::TempDir;
+use test_utils::get_absolute_path;
+
+use crate::audit::{audit_blocks, AuditReport, AuditedData, FieldDifference};
+use crate::sort_state_diff;
+use crate::sources::{client_to_central_state_diff, ApiContractClass};
+
+// The Sierra class declared in the state update of the test resources.
+const DECLARED_CLASS_HASH: &str = "0x10";
+
+fn read_resource<T: DeserializeOwned>(file_name: &str) -> T {
+    let path = get_absolute_path(&format!("crates/starknet_client/resources/reader/{file_name}"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn class_by_hash(class_hash: ClassHash) -> GenericContractClass {
+    if class_hash == ClassHash(stark_felt!(DECLARED_CLASS_HASH)) {
+        GenericContractClass::Cairo1ContractClass(ContractClass::default())
+    } else {
+        GenericContractClass::Cairo0ContractClass(DeprecatedContractClass::default())
+    }
+}
+
+// Stores the block and the state update of the test resources as block 0, as the sync does.
+fn store_block(block: Block, state_update: StateUpdate) -> (StorageReader, TempDir) {
+    let ((reader, mut writer), temp_dir) = get_test_storage();
+    let (block, starknet_version) = block.to_starknet_api_block_and_version().unwrap();
+    let classes: IndexMap<_, _> = state_update
+        .state_diff
+        .class_hashes()
+        .into_iter()
+        .map(|class_hash| (class_hash, ApiContractClass::from(class_by_hash(class_hash))))
+        .collect();
+    let (mut state_diff, deployed_contract_class_definitions) =
+        client_to_central_state_diff(state_update.state_diff, classes);
+    sort_state_diff(&mut state_diff);
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &block.header)
+        .unwrap()
+        .update_starknet_version(&BlockNumber(0), &StarknetVersion(starknet_version))
+        .unwrap()
+        .append_body(BlockNumber(0), block.body)
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff, deployed_contract_class_definitions)
+        .unwrap()
+        .append_casm(&ClassHash(stark_felt!(DECLARED_CLASS_HASH)), &CasmContractClass::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    (reader, temp_dir)
+}
+
+fn mock_client(
+    block: Block,
+    state_update: StateUpdate,
+    compiled_class: CasmContractClass,
+) -> MockStarknetReader {
+    let mut mock = MockStarknetReader::new();
+    mock.expect_block().returning(move |_block_number| Ok(Some(block.clone())));
+    mock.expect_state_update().returning(move |_block_number| Ok(Some(state_update.clone())));
+    mock.expect_class_by_hash().returning(|class_hash| Ok(Some(class_by_hash(class_hash))));
+    mock.expect_compiled_class_by_hash()
+        .returning(move |_class_hash| Ok(Some(compiled_class.clone())));
+    mock
+}
+
+#[tokio::test]
+async fn audit_of_synced_block_is_clean() {
+    let block: Block = read_resource("block.json");
+    let state_update: StateUpdate = read_resource("block_state_update.json");
+    let (reader, _temp_dir) = store_block(block.clone(), state_update.clone());
+    let mock = mock_client(block, state_update, CasmContractClass::default());
+
+    let report = audit_blocks(&mock, &reader, BlockNumber(0), BlockNumber(1)).await.unwrap();
+    assert_eq!(
+        report,
+        AuditReport { start_block: BlockNumber(0), end_block: BlockNumber(1), differences: vec![] }
+    );
+    assert!(report.is_clean());
+}
+
+#[tokio::test]
+async fn audit_reports_differing_fields() {
+    let block: Block = read_resource("block.json");
+    let state_update: StateUpdate = read_resource("block_state_update.json");
+    let (reader, _temp_dir) = store_block(block.clone(), state_update.clone());
+
+    let mut central_block = block;
+    central_block.timestamp.0 += 1;
+    let mut central_state_update = state_update;
+    let (contract_address, nonce) = central_state_update
+        .state_diff
+        .nonces
+        .first_mut()
+        .map(|(address, nonce)| (*address, nonce))
+        .unwrap();
+    let stored_nonce = *nonce;
+    *nonce = Nonce(stark_felt!("0x1234"));
+    let central_compiled_class =
+        CasmContractClass { compiler_version: "0.0.0".to_owned(), ..CasmContractClass::default() };
+    let mock = mock_client(central_block.clone(), central_state_update, central_compiled_class);
+
+    let report = audit_blocks(&mock, &reader, BlockNumber(0), BlockNumber(1)).await.unwrap();
+    let difference = |data, class_hash, path: &str, stored, central| FieldDifference {
+        block_number: BlockNumber(0),
+        data,
+        class_hash,
+        path: path.to_owned(),
+        stored,
+        central,
+    };
+    assert_eq!(
+        report.differences,
+        vec![
+            difference(
+                AuditedData::Header,
+                None,
+                "/timestamp",
+                json!(central_block.timestamp.0 - 1),
+                json!(central_block.timestamp.0),
+            ),
+            difference(
+                AuditedData::StateDiff,
+                None,
+                &format!("/nonces/{}", json!(contract_address).as_str().unwrap()),
+                json!(stored_nonce),
+                json!("0x1234"),
+            ),
+            difference(
+                AuditedData::CompiledClass,
+                Some(ClassHash(stark_felt!(DECLARED_CLASS_HASH))),
+                "/compiler_version",
+                json!(""),
+                json!("0.0.0"),
+            ),
+        ]
+    );
+    assert!(!report.is_clean());
+}
+
+#[tokio::test]
+async fn audit_reports_missing_data() {
+    let block: Block = read_resource("block.json");
+    let state_update: StateUpdate = read_resource("block_state_update.json");
+    let ((reader, _writer), _temp_dir) = get_test_storage();
+    let mock = mock_client(block, state_update, CasmContractClass::default());
+
+    let report = audit_blocks(&mock, &reader, BlockNumber(0), BlockNumber(1)).await.unwrap();
+    // Every audited data of the block is missing as a whole.
+    assert!(!report.differences.is_empty());
+    for difference in report.differences {
+        assert_eq!(difference.path, "");
+        assert_eq!(difference.stored, json!(null));
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+#[path = "checkpoints_test.rs"]
+mod checkpoints_test;
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::hash::StarkHash;
+
+// Checkpoints that are bundled with the node, by chain id.
+const BUNDLED_CHECKPOINTS: &[(&str, &[(u64, &str)])] =
+    &[("SN_MAIN", &[(0, "0x47c3637b57c2b079b93c61539950c17e868a28f46cdef28f88521067f21e943")])];
+
+#[derive(thiserror::Error, Debug)]
+pub enum CheckpointsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse the checkpoints file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Trusted block hashes that the sync verifies the stored headers against.
+///
+/// A checkpoints file is a JSON object from block numbers to block hashes, for example
+/// `{"0": "0x47c3637b57c2b079b93c61539950c17e868a28f46cdef28f88521067f21e943"}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoints(BTreeMap<BlockNumber, BlockHash>);
+
+impl Checkpoints {
+    pub fn new(checkpoints: BTreeMap<BlockNumber, BlockHash>) -> Self {
+        Checkpoints(checkpoints)
+    }
+
+    /// Returns the checkpoints bundled with the node for the chain.
+    pub fn bundled(chain_id: &ChainId) -> Self {
+        let checkpoints = BUNDLED_CHECKPOINTS
+            .iter()
+            .filter(|(bundled_chain_id, _)| *bundled_chain_id == chain_id.0)
+            .flat_map(|(_, checkpoints)| checkpoints.iter())
+            .map(|(block_number, block_hash)| {
+                let block_hash = StarkHash::try_from(*block_hash)
+                    .expect("Bundled checkpoints should contain valid block hashes.");
+                (BlockNumber(*block_number), BlockHash(block_hash))
+            })
+            .collect();
+        Checkpoints(checkpoints)
+    }
+
+    /// Reads checkpoints from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self, CheckpointsError> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+
+    /// Returns the bundled checkpoints of the chain together with the checkpoints of the file, if
+    /// given. The checkpoints of the file take precedence.
+    pub fn load(chain_id: &ChainId, path: Option<&Path>) -> Result<Self, CheckpointsError> {
+        let mut checkpoints = Self::bundled(chain_id);
+        if let Some(path) = path {
+            checkpoints.0.extend(Self::from_file(path)?.0);
+        }
+        Ok(checkpoints)
+    }
+
+    /// Returns the trusted hash of the block, if the block is a checkpoint.
+    pub fn get(&self, block_number: BlockNumber) -> Option<BlockHash> {
+        self.0.get(&block_number).copied()
+    }
+
+    /// Returns the latest checkpoint before the given block number.
+    pub fn latest_before(&self, block_number: BlockNumber) -> Option<BlockNumber> {
+        self.0.range(..block_number).next_back().map(|(block_number, _)| *block_number)
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use tempfile::NamedTempFile;
+
+use crate::checkpoints::Checkpoints;
+
+#[test]
+fn load_checkpoints() {
+    let chain_id = ChainId("SN_MAIN".to_owned());
+    let genesis_hash = Checkpoints::bundled(&chain_id).get(BlockNumber(0)).unwrap();
+
+    let mut file = NamedTempFile::new().unwrap();
+    write!(file, r#"{{"0": "0x1", "10": "0xa"}}"#).unwrap();
+    let checkpoints = Checkpoints::load(&chain_id, Some(file.path())).unwrap();
+    assert_ne!(genesis_hash, BlockHash(stark_felt!("0x1")));
+    // The checkpoints of the file take precedence over the bundled checkpoints.
+    assert_eq!(
+        checkpoints,
+        Checkpoints::new(BTreeMap::from([
+            (BlockNumber(0), BlockHash(stark_felt!("0x1"))),
+            (BlockNumber(10), BlockHash(stark_felt!("0xa"))),
+        ]))
+    );
+
+    let unknown_chain_id = ChainId("UNKNOWN".to_owned());
+    assert_eq!(Checkpoints::load(&unknown_chain_id, None).unwrap(), Checkpoints::default());
+}
+
+#[test]
+fn latest_checkpoint_before_block() {
+    let checkpoints = Checkpoints::new(BTreeMap::from([
+        (BlockNumber(5), BlockHash::default()),
+        (BlockNumber(10), BlockHash::default()),
+    ]));
+    assert_eq!(checkpoints.latest_before(BlockNumber(5)), None);
+    assert_eq!(checkpoints.latest_before(BlockNumber(6)), Some(BlockNumber(5)));
+    assert_eq!(checkpoints.latest_before(BlockNumber(11)), Some(BlockNumber(10)));
+}
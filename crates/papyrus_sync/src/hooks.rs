@@ -0,0 +1,46 @@
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use papyrus_storage::db::RO;
+use papyrus_storage::StorageTxn;
+use starknet_api::block::{Block, BlockHash, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::state::StateDiff;
+
+/// A hook that the sync calls after committing data to the storage, for building indexers that
+/// live inside the node and stay consistent with the stored chain.
+///
+/// Each method receives the committed data and a read transaction that already contains it. The
+/// hooks are called synchronously from the sync loop, so they should be fast and handle their own
+/// errors. A reverted block is reported through [`SyncHook::on_revert`], after which the data of
+/// the new block at the same height is reported again.
+pub trait SyncHook: Send + Sync {
+    /// Called after the header and body of a block are stored.
+    fn on_block(&self, _txn: &StorageTxn<'_, RO>, _block_number: BlockNumber, _block: &Block) {}
+
+    /// Called after the state diff of a block is stored.
+    fn on_state_diff(
+        &self,
+        _txn: &StorageTxn<'_, RO>,
+        _block_number: BlockNumber,
+        _block_hash: BlockHash,
+        _state_diff: &StateDiff,
+    ) {
+    }
+
+    /// Called after a compiled class is stored.
+    fn on_compiled_class(
+        &self,
+        _txn: &StorageTxn<'_, RO>,
+        _class_hash: ClassHash,
+        _compiled_class: &CasmContractClass,
+    ) {
+    }
+
+    /// Called after the data of a block is reverted from the storage.
+    fn on_revert(
+        &self,
+        _txn: &StorageTxn<'_, RO>,
+        _block_number: BlockNumber,
+        _block_hash: BlockHash,
+    ) {
+    }
+}
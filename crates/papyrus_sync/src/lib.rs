@@ -2,19 +2,35 @@
 #[path = "sync_test.rs"]
 mod sync_test;
 
+mod audit;
+mod checkpoints;
+mod hooks;
 mod sources;
+mod sync_metrics;
+mod verification;
 
 use std::cmp::min;
 use std::collections::BTreeMap;
+use std::future::{pending, Future};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_stream::try_stream;
 use cairo_lang_starknet::casm_contract_class::CasmContractClass;
-use futures_util::{pin_mut, select, Stream, StreamExt};
+use futures_util::future::Either;
+use futures_util::{pin_mut, select, FutureExt, Stream, StreamExt};
 use indexmap::IndexMap;
+use metrics::{gauge, histogram, increment_counter};
+use papyrus_common::block_hash::BlockHashError;
+use papyrus_common::class_hash::ClassHashError;
+use papyrus_common::pending_data::PendingData;
+use papyrus_common::transaction_hash::TransactionHashError;
 use papyrus_common::SyncingState;
-use papyrus_config::converters::deserialize_milliseconds_to_duration;
+use papyrus_config::converters::{
+    deserialize_milliseconds_to_duration, deserialize_optional_number, deserialize_optional_path,
+    serialize_optional_number, serialize_optional_path,
+};
 use papyrus_config::dumping::{ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, SerializedParam};
 use papyrus_storage::body::BodyStorageWriter;
@@ -23,17 +39,26 @@ use papyrus_storage::header::{HeaderStorageReader, HeaderStorageWriter, Starknet
 use papyrus_storage::ommer::{OmmerStorageReader, OmmerStorageWriter};
 use papyrus_storage::state::{StateStorageReader, StateStorageWriter};
 use papyrus_storage::{StorageError, StorageReader, StorageWriter};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use starknet_api::block::{Block, BlockHash, BlockNumber};
-use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::core::{ChainId, ClassHash, CompiledClassHash};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::StateDiff;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tracing::{debug, error, info, instrument, trace, warn};
 
-pub use self::sources::{CentralError, CentralSource, CentralSourceConfig, CentralSourceTrait};
+pub use self::audit::{audit_blocks, AuditError, AuditReport, AuditedData, FieldDifference};
+pub use self::checkpoints::{Checkpoints, CheckpointsError};
+pub use self::hooks::SyncHook;
+use self::sources::CentralStateUpdate;
+pub use self::sources::{
+    ArchiveReader, ArchiveSource, CentralError, CentralSource, CentralSourceConfig,
+    CentralSourceTrait, StateUpdateStreamConfig, UnknownStarknetVersionPolicy,
+};
+use self::sync_metrics::{ERROR_KIND_LABEL, EVENT_LABEL};
+pub use self::verification::VerificationMode;
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SyncConfig {
     #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
     pub block_propagation_sleep_duration: Duration,
@@ -41,6 +66,21 @@ pub struct SyncConfig {
     pub recoverable_error_sleep_duration: Duration,
     pub blocks_max_stream_size: u32,
     pub state_updates_max_stream_size: u32,
+    pub download_blocks_with_state_updates: bool,
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub pending_sleep_duration: Duration,
+    #[serde(deserialize_with = "deserialize_optional_block_number")]
+    pub halt_block_number: Option<BlockNumber>,
+    #[serde(deserialize_with = "deserialize_optional_block_number")]
+    pub initial_block_number: Option<BlockNumber>,
+    pub max_revert_depth: u64,
+    pub allow_deep_revert: bool,
+    #[serde(deserialize_with = "deserialize_optional_path")]
+    pub checkpoints_file: Option<PathBuf>,
+    pub chain_id: ChainId,
+    pub block_hash_verification: VerificationMode,
+    pub transaction_hash_verification: VerificationMode,
+    pub class_hash_verification: VerificationMode,
 }
 
 impl SerializeConfig for SyncConfig {
@@ -68,6 +108,73 @@ impl SerializeConfig for SyncConfig {
                 &self.state_updates_max_stream_size,
                 "Max amount of state updates to download in a stream.",
             ),
+            ser_param(
+                "download_blocks_with_state_updates",
+                &self.download_blocks_with_state_updates,
+                "If true, downloads every block together with its state update in a single \
+                 request instead of two, which halves the requests to the central source.",
+            ),
+            ser_param(
+                "pending_sleep_duration",
+                &self.pending_sleep_duration.as_millis(),
+                "Time in milliseconds between polls of the pending block after the node is \
+                 synchronized.",
+            ),
+            ser_param(
+                "halt_block_number",
+                &serialize_optional_number(&self.halt_block_number),
+                "If set, the sync stops after storing all the data of this block and the node \
+                 keeps serving the stored data.",
+            ),
+            ser_param(
+                "initial_block_number",
+                &serialize_optional_number(&self.initial_block_number),
+                "Experimental. If set and the storage is empty, the sync starts from this block \
+                 instead of the genesis block.",
+            ),
+            ser_param(
+                "max_revert_depth",
+                &self.max_revert_depth,
+                "Maximum number of blocks the sync reverts without confirmation. A deeper revert \
+                 stops the sync.",
+            ),
+            ser_param(
+                "allow_deep_revert",
+                &self.allow_deep_revert,
+                "If true, the sync reverts blocks beyond max_revert_depth. Set it to confirm a \
+                 deep revert after the sync stopped on one.",
+            ),
+            ser_param(
+                "checkpoints_file",
+                &serialize_optional_path(&self.checkpoints_file),
+                "Path to a JSON file of trusted block hashes by block number, in addition to the \
+                 checkpoints bundled for the chain. The sync stops on a block that doesn't match \
+                 a checkpoint and refuses to revert blocks below the latest stored checkpoint.",
+            ),
+            ser_param(
+                "chain_id",
+                &self.chain_id,
+                "The chain to follow, used for verifying the hashes of the synced data.",
+            ),
+            ser_param(
+                "block_hash_verification",
+                &self.block_hash_verification,
+                "How to handle a block whose hash doesn't match its calculated hash. One of \
+                 verify (stop the sync), warn (log and store the block) or off.",
+            ),
+            ser_param(
+                "transaction_hash_verification",
+                &self.transaction_hash_verification,
+                "How to handle a transaction whose hash doesn't match its calculated hash. One of \
+                 verify (stop the sync), warn (log and store the block) or off.",
+            ),
+            ser_param(
+                "class_hash_verification",
+                &self.class_hash_verification,
+                "How to handle a class whose class hash or compiled class hash doesn't match its \
+                 calculated hash. One of verify (stop the sync), warn (log and store the class) \
+                 or off.",
+            ),
         ])
     }
 }
@@ -79,18 +186,40 @@ impl Default for SyncConfig {
             recoverable_error_sleep_duration: Duration::from_secs(10),
             blocks_max_stream_size: 1000,
             state_updates_max_stream_size: 1000,
+            download_blocks_with_state_updates: false,
+            pending_sleep_duration: Duration::from_secs(1),
+            halt_block_number: None,
+            initial_block_number: None,
+            max_revert_depth: 100,
+            allow_deep_revert: false,
+            checkpoints_file: None,
+            chain_id: ChainId("SN_MAIN".to_string()),
+            block_hash_verification: VerificationMode::default(),
+            transaction_hash_verification: VerificationMode::default(),
+            class_hash_verification: VerificationMode::default(),
         }
     }
 }
 
+fn deserialize_optional_block_number<'de, D>(de: D) -> Result<Option<BlockNumber>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(deserialize_optional_number::<D, u64>(de)?.map(BlockNumber))
+}
+
 // Orchestrates specific network interfaces (e.g. central, p2p, l1) and writes to Storage and shared
 // memory.
 pub struct GenericStateSync<TCentralSource: CentralSourceTrait + Sync + Send> {
     config: SyncConfig,
     shared_syncing_state: Arc<RwLock<SyncingState>>,
+    pending_data: Arc<RwLock<PendingData>>,
     central_source: Arc<TCentralSource>,
     reader: StorageReader,
     writer: StorageWriter,
+    hooks: Vec<Arc<dyn SyncHook>>,
+    shutdown_receiver: Option<watch::Receiver<bool>>,
+    checkpoints: Checkpoints,
 }
 
 pub type StateSyncResult = Result<(), StateSyncError>;
@@ -115,6 +244,30 @@ pub enum StateSyncError {
          matching header (neither in the ommer headers)."
     )]
     StateDiffWithoutMatchingHeader { block_number: BlockNumber, block_hash: BlockHash },
+    #[error(
+        "Reverting more than {max_revert_depth} blocks, down to block {block_number}, requires \
+         confirmation. Set allow_deep_revert to proceed."
+    )]
+    RevertDepthExceeded { block_number: BlockNumber, max_revert_depth: u64 },
+    #[error(
+        "Block {block_number} has hash {block_hash}, which doesn't match the checkpoint hash \
+         {checkpoint_block_hash}."
+    )]
+    CheckpointMismatch {
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        checkpoint_block_hash: BlockHash,
+    },
+    #[error(
+        "Refusing to revert block {block_number}, which is below the checkpoint {checkpoint}."
+    )]
+    RevertBelowCheckpoint { block_number: BlockNumber, checkpoint: BlockNumber },
+    #[error(transparent)]
+    BlockHashError(#[from] BlockHashError),
+    #[error(transparent)]
+    TransactionHashError(#[from] TransactionHashError),
+    #[error(transparent)]
+    ClassHashError(#[from] ClassHashError),
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -139,17 +292,42 @@ pub enum SyncEvent {
         compiled_class_hash: CompiledClassHash,
         compiled_class: CasmContractClass,
     },
+    PendingDataAvailable {
+        pending_data: PendingData,
+    },
+}
+
+impl SyncEvent {
+    // The value of the event label of the metrics.
+    fn metric_label(&self) -> &'static str {
+        match self {
+            SyncEvent::BlockAvailable { .. } => sync_metrics::BLOCK_EVENT,
+            SyncEvent::StateDiffAvailable { .. } => sync_metrics::STATE_DIFF_EVENT,
+            SyncEvent::CompiledClassAvailable { .. } => sync_metrics::COMPILED_CLASS_EVENT,
+            SyncEvent::PendingDataAvailable { .. } => sync_metrics::PENDING_DATA_EVENT,
+        }
+    }
 }
 
 impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSync<TCentralSource> {
     pub async fn run(&mut self) -> StateSyncResult {
         info!("State sync started.");
+        self.initialize_storage_markers()?;
         loop {
             match self.sync_while_ok().await {
                 // A recoverable error occurred. Sleep and try syncing again.
                 Err(err) if is_recoverable(&err) => {
                     warn!("Recoverable error encountered while syncing, error: {}", err);
-                    tokio::time::sleep(self.config.recoverable_error_sleep_duration).await;
+                    increment_counter!(
+                        sync_metrics::RECOVERABLE_ERRORS,
+                        ERROR_KIND_LABEL => sync_metrics::recoverable_error_kind(&err)
+                    );
+                    let sleep_duration = self.config.recoverable_error_sleep_duration;
+                    if tokio::time::timeout(sleep_duration, self.wait_for_shutdown()).await.is_ok()
+                    {
+                        info!("Shutdown requested, stopping the sync.");
+                        return Ok(());
+                    }
                     continue;
                 }
                 // Unrecoverable errors.
@@ -157,9 +335,8 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
                     error!("Fatal error while syncing: {}", err);
                     return Err(err);
                 }
-                Ok(_) => {
-                    unreachable!("Sync should either return with an error or continue forever.")
-                }
+                // The sync reached the halt block or a shutdown was requested.
+                Ok(_) => return Ok(()),
             }
         }
 
@@ -194,56 +371,155 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         }
     }
 
-    // Sync until encountering an error:
+    // Sets the storage markers to the initial block if configured and the storage is empty.
+    fn initialize_storage_markers(&mut self) -> StateSyncResult {
+        let Some(initial_block_number) = self.config.initial_block_number else {
+            return Ok(());
+        };
+        if self.reader.begin_ro_txn()?.get_header_marker()? != BlockNumber(0) {
+            return Ok(());
+        }
+        info!("Initializing an empty storage to start syncing from block {initial_block_number}.");
+        self.writer.begin_rw_txn()?.initialize_markers(initial_block_number)?.commit()?;
+        Ok(())
+    }
+
+    // Resolves once a shutdown is requested. Never resolves if the sync has no shutdown receiver or
+    // the sender was dropped without requesting a shutdown.
+    fn wait_for_shutdown(&self) -> impl Future<Output = ()> {
+        let shutdown_receiver = self.shutdown_receiver.clone();
+        async move {
+            if let Some(mut shutdown_receiver) = shutdown_receiver {
+                if shutdown_receiver.wait_for(|shutdown| *shutdown).await.is_ok() {
+                    return;
+                }
+            }
+            pending().await
+        }
+    }
+
+    // Sync until encountering an error, reaching the halt block or a shutdown request:
     //  1. If needed, revert blocks from the end of the chain.
     //  2. Create infinite block and state diff streams to fetch data from the central source.
     //  3. Fetch data from the streams with unblocking wait while there is no new data.
     async fn sync_while_ok(&mut self) -> StateSyncResult {
         // TODO(yoav): Set actual values for the sync status.
         *self.shared_syncing_state.write().await = SyncingState::Synced;
+        if self.reached_halt_block()? {
+            info!("Sync reached the halt block, stopping the sync.");
+            return Ok(());
+        }
         self.handle_block_reverts().await?;
-        let block_stream = stream_new_blocks(
-            self.reader.clone(),
-            self.central_source.clone(),
-            self.config.block_propagation_sleep_duration,
-            self.config.blocks_max_stream_size,
-        )
-        .fuse();
-        let state_diff_stream = stream_new_state_diffs(
+        sync_metrics::update_marker_metrics(&self.reader.begin_ro_txn()?)?;
+        // When the blocks are downloaded with their state updates, the block stream yields the
+        // state diffs as well.
+        let (block_stream, state_diff_stream) = if self.config.download_blocks_with_state_updates {
+            let block_stream = stream_new_blocks_with_state_diffs(
+                self.reader.clone(),
+                self.central_source.clone(),
+                self.config.block_propagation_sleep_duration,
+                self.config.blocks_max_stream_size,
+                self.config.halt_block_number,
+            );
+            (Either::Left(block_stream), Either::Left(futures_util::stream::pending()))
+        } else {
+            let block_stream = stream_new_blocks(
+                self.reader.clone(),
+                self.central_source.clone(),
+                self.config.block_propagation_sleep_duration,
+                self.config.blocks_max_stream_size,
+                self.config.halt_block_number,
+            );
+            let state_diff_stream = stream_new_state_diffs(
+                self.reader.clone(),
+                self.central_source.clone(),
+                self.config.block_propagation_sleep_duration,
+                self.config.state_updates_max_stream_size,
+            );
+            (Either::Right(block_stream), Either::Right(state_diff_stream))
+        };
+        let block_stream = block_stream.fuse();
+        let state_diff_stream = state_diff_stream.fuse();
+        let compiled_class_stream = stream_new_compiled_classes(
             self.reader.clone(),
             self.central_source.clone(),
             self.config.block_propagation_sleep_duration,
+            // TODO(yair): separate config param.
             self.config.state_updates_max_stream_size,
         )
         .fuse();
-        let compiled_class_stream = stream_new_compiled_classes(
+        let pending_data_stream = stream_pending_data(
             self.reader.clone(),
             self.central_source.clone(),
-            self.config.block_propagation_sleep_duration,
-            // TODO(yair): separate config param.
-            self.config.state_updates_max_stream_size,
+            self.config.pending_sleep_duration,
+            self.config.halt_block_number.is_some(),
         )
         .fuse();
-        pin_mut!(block_stream, state_diff_stream, compiled_class_stream);
+        let shutdown = self.wait_for_shutdown().fuse();
+        pin_mut!(
+            block_stream,
+            state_diff_stream,
+            compiled_class_stream,
+            pending_data_stream,
+            shutdown
+        );
 
         loop {
             debug!("Selecting between block sync and state diff sync.");
+            // An event that is being processed is committed before checking for a shutdown, and
+            // the streams are dropped when returning.
             let sync_event = select! {
+              () = shutdown => {
+                  info!("Shutdown requested, stopping the sync.");
+                  return Ok(());
+              },
               res = block_stream.next() => res,
               res = state_diff_stream.next() => res,
               res = compiled_class_stream.next() => res,
+              res = pending_data_stream.next() => res,
               complete => break,
             }
             .expect("Received None as a sync event.")?;
             self.process_sync_event(sync_event).await?;
             debug!("Finished processing sync event.");
+            sync_metrics::update_marker_metrics(&self.reader.begin_ro_txn()?)?;
+            if self.reached_halt_block()? {
+                info!("Sync reached the halt block, stopping the sync.");
+                return Ok(());
+            }
         }
         unreachable!("Fetching data loop should never return.");
     }
 
+    // Returns true if all the data up to and including the halt block is stored.
+    fn reached_halt_block(&self) -> Result<bool, StateSyncError> {
+        let Some(halt_block_number) = self.config.halt_block_number else {
+            return Ok(false);
+        };
+        let txn = self.reader.begin_ro_txn()?;
+        if txn.get_state_marker()? <= halt_block_number {
+            return Ok(false);
+        }
+        // The compiled class marker doesn't advance over blocks without declared classes, so check
+        // that no such block up to the halt block is missing compiled classes.
+        let mut block_number = txn.get_compiled_class_marker()?;
+        while block_number <= halt_block_number {
+            let state_diff = txn
+                .get_state_diff(block_number)?
+                .expect("Expecting to have state diff up to the marker.");
+            if !state_diff.declared_classes.is_empty() {
+                return Ok(false);
+            }
+            block_number = block_number.next();
+        }
+        Ok(true)
+    }
+
     // Tries to store the incoming data.
     async fn process_sync_event(&mut self, sync_event: SyncEvent) -> StateSyncResult {
-        match sync_event {
+        let event_label = sync_event.metric_label();
+        let start = Instant::now();
+        let res = match sync_event {
             SyncEvent::BlockAvailable { block_number, block, starknet_version } => {
                 self.store_block(block_number, block, &starknet_version)
             }
@@ -263,7 +539,17 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
                 compiled_class_hash,
                 compiled_class,
             } => self.store_compiled_class(class_hash, compiled_class_hash, compiled_class),
-        }
+            SyncEvent::PendingDataAvailable { pending_data } => {
+                *self.pending_data.write().await = pending_data;
+                Ok(())
+            }
+        };
+        histogram!(
+            sync_metrics::COMMIT_LATENCY,
+            start.elapsed().as_secs_f64(),
+            EVENT_LABEL => event_label
+        );
+        res
     }
 
     #[instrument(skip(self, block), level = "debug", fields(block_hash = %block.header.block_hash), err)]
@@ -276,15 +562,35 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         // Assuming the central source is trusted, detect reverts by comparing the incoming block's
         // parent hash to the current hash.
         self.verify_parent_block_hash(block_number, &block)?;
+        self.verify_checkpoint(block_number, block.header.block_hash)?;
+        verification::verify_block(
+            self.config.block_hash_verification,
+            &block,
+            starknet_version,
+            &self.config.chain_id,
+        )?;
+        verification::verify_transactions(
+            self.config.transaction_hash_verification,
+            &block.body,
+            &self.config.chain_id,
+        )?;
 
         debug!("Storing block.");
         trace!("Block data: {block:#?}");
+        // The body is moved into the storage, keep a copy only if there are hooks to report to.
+        let block_for_hooks = if self.hooks.is_empty() { None } else { Some(block.clone()) };
         self.writer
             .begin_rw_txn()?
             .append_header(block_number, &block.header)?
             .update_starknet_version(&block_number, starknet_version)?
             .append_body(block_number, block.body)?
             .commit()?;
+        if let Some(block) = block_for_hooks {
+            let txn = self.reader.begin_ro_txn()?;
+            for hook in &self.hooks {
+                hook.on_block(&txn, block_number, &block);
+            }
+        }
         Ok(())
     }
 
@@ -297,12 +603,25 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         deployed_contract_class_definitions: IndexMap<ClassHash, DeprecatedContractClass>,
     ) -> StateSyncResult {
         if !self.is_reverted_state_diff(block_number, block_hash)? {
+            verification::verify_classes(
+                self.config.class_hash_verification,
+                &state_diff,
+                &deployed_contract_class_definitions,
+            )?;
             debug!("Storing state diff.");
             trace!("StateDiff data: {state_diff:#?}");
+            let state_diff_for_hooks =
+                if self.hooks.is_empty() { None } else { Some(state_diff.clone()) };
             self.writer
                 .begin_rw_txn()?
                 .append_state_diff(block_number, state_diff, deployed_contract_class_definitions)?
                 .commit()?;
+            if let Some(state_diff) = state_diff_for_hooks {
+                let txn = self.reader.begin_ro_txn()?;
+                for hook in &self.hooks {
+                    hook.on_state_diff(&txn, block_number, block_hash, &state_diff);
+                }
+            }
 
             // Info the user on syncing the block once all the data is stored.
             info!("Added block {} with hash {}.", block_number, block_hash);
@@ -319,7 +638,14 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         compiled_class_hash: CompiledClassHash,
         compiled_class: CasmContractClass,
     ) -> StateSyncResult {
+        verification::verify_compiled_class(
+            self.config.class_hash_verification,
+            &class_hash,
+            &compiled_class_hash,
+            &compiled_class,
+        )?;
         let txn = self.writer.begin_rw_txn()?;
+        let compiled_class_marker = txn.get_compiled_class_marker()?;
         let is_reverted_class =
             txn.get_state_reader()?.get_class_definition_block_number(&class_hash)?.is_none();
         if is_reverted_class {
@@ -329,11 +655,21 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
             Ok(txn) => {
                 txn.commit()?;
                 debug!("Added compiled class.");
+                let txn = self.reader.begin_ro_txn()?;
+                for hook in &self.hooks {
+                    hook.on_compiled_class(&txn, class_hash, &compiled_class);
+                }
+                let new_compiled_class_marker = txn.get_compiled_class_marker()?;
+                if new_compiled_class_marker != compiled_class_marker {
+                    debug!(
+                        "Stored the compiled classes of blocks [{compiled_class_marker} - \
+                         {new_compiled_class_marker})."
+                    );
+                }
                 Ok(())
             }
-            // TODO(yair): Modify the stream so it skips already stored classes.
-            // Compiled classes rewrite is valid because the stream downloads from the beginning of
-            // the block instead of the last downloaded class.
+            // The stream skips already stored classes, but a class can still be rewritten if the
+            // stream checked it before a previous stream stored it.
             Err(StorageError::CompiledClassReWrite { class_hash: existing_class_hash })
                 if existing_class_hash == class_hash =>
             {
@@ -354,6 +690,12 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
             None => return Ok(()),
             Some(bn) => bn,
         };
+        // The storage doesn't contain the blocks before the initial block.
+        if Some(block_number) == self.config.initial_block_number
+            && self.reader.begin_ro_txn()?.get_block_header(prev_block_number)?.is_none()
+        {
+            return Ok(());
+        }
         let prev_hash = self
             .reader
             .begin_ro_txn()?
@@ -377,20 +719,101 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         Ok(())
     }
 
+    // Compares the block hash to the checkpoint at the block number, if there is one.
+    fn verify_checkpoint(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> StateSyncResult {
+        match self.checkpoints.get(block_number) {
+            Some(checkpoint_block_hash) if checkpoint_block_hash != block_hash => {
+                error!(
+                    "Block {block_number} with hash {block_hash} doesn't match the checkpoint \
+                     hash {checkpoint_block_hash}. Stopping the sync."
+                );
+                Err(StateSyncError::CheckpointMismatch {
+                    block_number,
+                    block_hash,
+                    checkpoint_block_hash,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     // Reverts data if needed.
     async fn handle_block_reverts(&mut self) -> Result<(), StateSyncError> {
         debug!("Handling block reverts.");
         let header_marker = self.reader.begin_ro_txn()?.get_header_marker()?;
+        let revert_depth = self.revert_depth(header_marker).await?;
+        if revert_depth == 0 {
+            return Ok(());
+        }
+
+        // Revert last blocks.
+        let first_reverted_block = BlockNumber(header_marker.0 - revert_depth);
+        warn!(
+            "Reverting {revert_depth} blocks, from block {first_reverted_block} to block {}, \
+             since they are not in central.",
+            header_marker.0 - 1
+        );
+        if revert_depth > self.config.max_revert_depth {
+            warn!(
+                "The revert is deeper than {} blocks, and proceeds since allow_deep_revert is set.",
+                self.config.max_revert_depth
+            );
+        }
+        for block_number in (first_reverted_block.0..header_marker.0).rev() {
+            self.revert_block(BlockNumber(block_number))?;
+        }
+        Ok(())
+    }
 
-        // Revert last blocks if needed.
+    // Returns the number of blocks at the end of the storage that central doesn't have, after
+    // checking that each of them may be reverted. Every block is checked against central once.
+    async fn revert_depth(&self, header_marker: BlockNumber) -> Result<u64, StateSyncError> {
+        let latest_checkpoint = self.checkpoints.latest_before(header_marker);
+        let mut revert_depth = 0;
         let mut last_block_in_storage = header_marker.prev();
         while let Some(block_number) = last_block_in_storage {
-            if self.should_revert_block(block_number).await? {
-                self.revert_block(block_number)?;
-                last_block_in_storage = block_number.prev();
-            } else {
+            if !self.should_revert_block(block_number).await? {
                 break;
             }
+            revert_depth += 1;
+            self.verify_revert(block_number, revert_depth, latest_checkpoint)?;
+            last_block_in_storage = block_number.prev();
+        }
+        Ok(revert_depth)
+    }
+
+    // Checks that a block to revert is above the latest stored checkpoint, and that the number of
+    // blocks to revert down to it doesn't exceed the maximum revert depth, unless a deep revert is
+    // allowed.
+    fn verify_revert(
+        &self,
+        block_number: BlockNumber,
+        revert_depth: u64,
+        latest_checkpoint: Option<BlockNumber>,
+    ) -> StateSyncResult {
+        if let Some(checkpoint) = latest_checkpoint.filter(|checkpoint| block_number <= *checkpoint)
+        {
+            error!(
+                "Central requires reverting block {block_number}, which is below the checkpoint \
+                 {checkpoint}. Stopping the sync."
+            );
+            return Err(StateSyncError::RevertBelowCheckpoint { block_number, checkpoint });
+        }
+        if !self.config.allow_deep_revert && revert_depth > self.config.max_revert_depth {
+            increment_counter!(sync_metrics::DEEP_REVERTS);
+            error!(
+                "Detected a revert deeper than {} blocks, down to block {block_number}. Stopping \
+                 the sync until the revert is confirmed.",
+                self.config.max_revert_depth
+            );
+            return Err(StateSyncError::RevertDepthExceeded {
+                block_number,
+                max_revert_depth: self.config.max_revert_depth,
+            });
         }
         Ok(())
     }
@@ -441,6 +864,11 @@ impl<TCentralSource: CentralSourceTrait + Sync + Send + 'static> GenericStateSyn
         txn.commit()?;
         if let Some(hash) = reverted_block_hash {
             info!(%hash, "Reverted block.");
+            increment_counter!(sync_metrics::REVERTED_BLOCKS);
+            let txn = self.reader.begin_ro_txn()?;
+            for hook in &self.hooks {
+                hook.on_revert(&txn, block_number, hash);
+            }
         }
         Ok(())
     }
@@ -490,12 +918,17 @@ fn stream_new_blocks<TCentralSource: CentralSourceTrait + Sync + Send>(
     central_source: Arc<TCentralSource>,
     block_propagation_sleep_duration: Duration,
     max_stream_size: u32,
+    halt_block_number: Option<BlockNumber>,
 ) -> impl Stream<Item = Result<SyncEvent, StateSyncError>> {
     try_stream! {
         loop {
             let header_marker = reader.begin_ro_txn()?.get_header_marker()?;
-            let last_block_number = central_source.get_block_marker().await?;
-            if header_marker == last_block_number {
+            let mut last_block_number = central_source.get_block_marker().await?;
+            gauge!(sync_metrics::CENTRAL_BLOCK_MARKER, last_block_number.0 as f64);
+            if let Some(halt_block_number) = halt_block_number {
+                last_block_number = min(last_block_number, halt_block_number.next());
+            }
+            if header_marker >= last_block_number {
                 debug!("Blocks syncing reached the last known block, waiting for blockchain to advance.");
                 tokio::time::sleep(block_propagation_sleep_duration).await;
                 continue;
@@ -505,9 +938,12 @@ fn stream_new_blocks<TCentralSource: CentralSourceTrait + Sync + Send>(
             let block_stream =
                 central_source.stream_new_blocks(header_marker, up_to).fuse();
             pin_mut!(block_stream);
+            let mut download_start = Instant::now();
             while let Some(maybe_block) = block_stream.next().await {
+                record_download_latency(download_start, sync_metrics::BLOCK_EVENT);
                 let (block_number, block, starknet_version) = maybe_block?;
                 yield SyncEvent::BlockAvailable { block_number, block , starknet_version};
+                download_start = Instant::now();
             }
         }
     }
@@ -536,25 +972,153 @@ fn stream_new_state_diffs<TCentralSource: CentralSourceTrait + Sync + Send>(
                 central_source.stream_state_updates(state_marker, up_to).fuse();
             pin_mut!(state_diff_stream);
 
+            let mut download_start = Instant::now();
             while let Some(maybe_state_diff) = state_diff_stream.next().await {
-                let (
-                    block_number,
-                    block_hash,
-                    mut state_diff,
-                    deployed_contract_class_definitions,
-                ) = maybe_state_diff?;
-                sort_state_diff(&mut state_diff);
-                yield SyncEvent::StateDiffAvailable {
-                    block_number,
-                    block_hash,
-                    state_diff,
-                    deployed_contract_class_definitions,
+                record_download_latency(download_start, sync_metrics::STATE_DIFF_EVENT);
+                yield state_diff_event(maybe_state_diff?);
+                download_start = Instant::now();
+            }
+        }
+    }
+}
+
+// Downloads every block together with its state update and yields the block and then its state
+// diff. The state diffs of blocks whose header is already stored, for example after the sync ran
+// without downloading them together, are downloaded on their own first.
+fn stream_new_blocks_with_state_diffs<TCentralSource: CentralSourceTrait + Sync + Send>(
+    reader: StorageReader,
+    central_source: Arc<TCentralSource>,
+    block_propagation_sleep_duration: Duration,
+    max_stream_size: u32,
+    halt_block_number: Option<BlockNumber>,
+) -> impl Stream<Item = Result<SyncEvent, StateSyncError>> {
+    try_stream! {
+        loop {
+            let txn = reader.begin_ro_txn()?;
+            let header_marker = txn.get_header_marker()?;
+            let state_marker = txn.get_state_marker()?;
+            drop(txn);
+            if state_marker < header_marker {
+                let up_to = min(header_marker, BlockNumber(state_marker.0 + max_stream_size as u64));
+                debug!("Downloading state diffs [{} - {}).", state_marker, up_to);
+                let state_diff_stream =
+                    central_source.stream_state_updates(state_marker, up_to).fuse();
+                pin_mut!(state_diff_stream);
+                let mut download_start = Instant::now();
+                while let Some(maybe_state_diff) = state_diff_stream.next().await {
+                    record_download_latency(download_start, sync_metrics::STATE_DIFF_EVENT);
+                    yield state_diff_event(maybe_state_diff?);
+                    download_start = Instant::now();
+                }
+                continue;
+            }
+            let mut last_block_number = central_source.get_block_marker().await?;
+            gauge!(sync_metrics::CENTRAL_BLOCK_MARKER, last_block_number.0 as f64);
+            if let Some(halt_block_number) = halt_block_number {
+                last_block_number = min(last_block_number, halt_block_number.next());
+            }
+            if header_marker >= last_block_number {
+                debug!("Blocks syncing reached the last known block, waiting for blockchain to advance.");
+                tokio::time::sleep(block_propagation_sleep_duration).await;
+                continue;
+            }
+            let up_to = min(last_block_number, BlockNumber(header_marker.0 + max_stream_size as u64));
+            debug!("Downloading blocks with state diffs [{} - {}).", header_marker, up_to);
+            let block_stream =
+                central_source.stream_blocks_with_state_updates(header_marker, up_to).fuse();
+            pin_mut!(block_stream);
+            let mut download_start = Instant::now();
+            while let Some(maybe_block) = block_stream.next().await {
+                record_download_latency(download_start, sync_metrics::BLOCK_EVENT);
+                let (block, starknet_version, state_update) = maybe_block?;
+                let block_number = state_update.0;
+                yield SyncEvent::BlockAvailable { block_number, block, starknet_version };
+                yield state_diff_event(state_update);
+                download_start = Instant::now();
+            }
+        }
+    }
+}
+
+fn state_diff_event(state_update: CentralStateUpdate) -> SyncEvent {
+    let (block_number, block_hash, mut state_diff, deployed_contract_class_definitions) =
+        state_update;
+    sort_state_diff(&mut state_diff);
+    SyncEvent::StateDiffAvailable {
+        block_number,
+        block_hash,
+        state_diff,
+        deployed_contract_class_definitions,
+    }
+}
+
+// Polls the pending block once all the data of the latest block is stored, since the pending block
+// extends it. Pending data that doesn't extend the latest stored block is replaced by an empty one.
+// When the sync halts at a block, the pending block doesn't extend the stored chain and isn't
+// polled.
+fn stream_pending_data<TCentralSource: CentralSourceTrait + Sync + Send>(
+    reader: StorageReader,
+    central_source: Arc<TCentralSource>,
+    pending_sleep_duration: Duration,
+    has_halt_block: bool,
+) -> impl Stream<Item = Result<SyncEvent, StateSyncError>> {
+    try_stream! {
+        loop {
+            if has_halt_block {
+                tokio::time::sleep(pending_sleep_duration).await;
+                continue;
+            }
+            let txn = reader.begin_ro_txn()?;
+            let header_marker = txn.get_header_marker()?;
+            let state_marker = txn.get_state_marker()?;
+            let latest_block_hash = match header_marker.prev() {
+                Some(block_number) => txn.get_block_header(block_number)?.map(|header| header.block_hash),
+                None => None,
+            };
+            drop(txn);
+            // The pending data is polled again after the sleep, so a failure to get it only skips
+            // this update rather than restarting the sync.
+            let central_block_marker = match central_source.get_block_marker().await {
+                Ok(block_marker) => block_marker,
+                Err(err) => {
+                    warn!("Failed to get the block marker for the pending data, skipping this update: {err}");
+                    tokio::time::sleep(pending_sleep_duration).await;
+                    continue;
+                }
+            };
+            if state_marker == header_marker && header_marker == central_block_marker {
+                let download_start = Instant::now();
+                let pending_data = match central_source.get_pending_data().await {
+                    Ok(pending_data) => pending_data,
+                    Err(err) => {
+                        warn!("Failed to get the pending data, skipping this update: {err}");
+                        tokio::time::sleep(pending_sleep_duration).await;
+                        continue;
+                    }
                 };
+                record_download_latency(download_start, sync_metrics::PENDING_DATA_EVENT);
+                let pending_data = pending_data
+                    .filter(|pending_data| {
+                        pending_data.block.parent_block_hash == latest_block_hash.unwrap_or_default()
+                    })
+                    .unwrap_or_default();
+                trace!("Pending block has {} transactions.", pending_data.block.body.transactions.len());
+                yield SyncEvent::PendingDataAvailable { pending_data };
             }
+            tokio::time::sleep(pending_sleep_duration).await;
         }
     }
 }
 
+// Records the time it took to download the next item from the central source.
+fn record_download_latency(download_start: Instant, event_label: &'static str) {
+    histogram!(
+        sync_metrics::DOWNLOAD_LATENCY,
+        download_start.elapsed().as_secs_f64(),
+        EVENT_LABEL => event_label
+    );
+}
+
 pub fn sort_state_diff(diff: &mut StateDiff) {
     diff.declared_classes.sort_unstable_keys();
     diff.deprecated_declared_classes.sort_unstable_keys();
@@ -568,23 +1132,45 @@ pub fn sort_state_diff(diff: &mut StateDiff) {
 }
 
 pub type StateSync = GenericStateSync<CentralSource>;
+pub type ArchiveStateSync = GenericStateSync<ArchiveSource>;
 
-impl StateSync {
+impl<TCentralSource: CentralSourceTrait + Sync + Send> GenericStateSync<TCentralSource> {
     pub fn new(
         config: SyncConfig,
         shared_syncing_state: Arc<RwLock<SyncingState>>,
-        central_source: CentralSource,
+        pending_data: Arc<RwLock<PendingData>>,
+        central_source: TCentralSource,
         reader: StorageReader,
         writer: StorageWriter,
     ) -> Self {
         Self {
             config,
             shared_syncing_state,
+            pending_data,
             central_source: Arc::new(central_source),
             reader,
             writer,
+            hooks: Vec::new(),
+            shutdown_receiver: None,
+            checkpoints: Checkpoints::default(),
         }
     }
+
+    /// Registers a hook that is called after the sync commits data to the storage.
+    pub fn register_hook(&mut self, hook: Arc<dyn SyncHook>) {
+        self.hooks.push(hook);
+    }
+
+    /// Sets a receiver of the shutdown signal of the node. Once the signal is true, the sync
+    /// finishes processing its current event and returns.
+    pub fn set_shutdown_receiver(&mut self, shutdown_receiver: watch::Receiver<bool>) {
+        self.shutdown_receiver = Some(shutdown_receiver);
+    }
+
+    /// Sets the trusted checkpoints the sync verifies the headers against.
+    pub fn set_checkpoints(&mut self, checkpoints: Checkpoints) {
+        self.checkpoints = checkpoints;
+    }
 }
 
 fn stream_new_compiled_classes<TCentralSource: CentralSourceTrait + Sync + Send>(
@@ -625,13 +1211,16 @@ fn stream_new_compiled_classes<TCentralSource: CentralSourceTrait + Sync + Send>
 
             // TODO(yair): Consider adding the block number and hash in order to make sure
             // that we do not write classes of ommer blocks.
+            let mut download_start = Instant::now();
             while let Some(maybe_compiled_class) = compiled_classes_stream.next().await {
+                record_download_latency(download_start, sync_metrics::COMPILED_CLASS_EVENT);
                 let (class_hash, compiled_class_hash, compiled_class) = maybe_compiled_class?;
                 yield SyncEvent::CompiledClassAvailable {
                     class_hash,
                     compiled_class_hash,
                     compiled_class,
                 };
+                download_start = Instant::now();
             }
         }
     }
@@ -0,0 +1,272 @@
+//! A [`StarknetReader`] that serves the feeder gateway objects from a local directory of archive
+//! files, so the sync can run without network access.
+//!
+//! The archive directory has the following layout, where every file holds the JSON response of the
+//! matching feeder gateway endpoint:
+//!
+//! ```text
+//! <archive_dir>/blocks/<block_number>.json              (get_block)
+//! <archive_dir>/state_updates/<block_number>.json       (get_state_update)
+//! <archive_dir>/classes/<class_hash>.json               (get_class_by_hash)
+//! <archive_dir>/compiled_classes/<class_hash>.json      (get_compiled_class_by_class_hash)
+//! ```
+//!
+//! A block is available once both its block file and its state update file exist. The archive
+//! reports the last block before the first missing one, so the sync stops at a gap and waits until
+//! it is filled.
+//!
+//! Transactions, signatures and storage values aren't indexed in the archive, so the requests for
+//! them fail with [`ReaderClientError::Unsupported`].
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use papyrus_storage::StorageReader;
+use serde::Deserialize;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::TransactionHash;
+use starknet_client::reader::{
+    Block, BlockSignatureData, ContractAddresses, GenericContractClass, PendingBlock, PendingData,
+    ReaderClientError, ReaderClientResult, StarknetReader, StateUpdate, StateUpdateWithBlock,
+    TransactionInfo, TransactionReceiptInfo, TransactionStatusInfo,
+};
+use tokio::sync::Mutex;
+use tracing::{debug, instrument};
+
+use super::central::{
+    GenericCentralSource, PendingClasses, StateUpdateStreamConfig, UnknownStarknetVersionPolicy,
+};
+
+pub(crate) const BLOCKS_DIR: &str = "blocks";
+pub(crate) const STATE_UPDATES_DIR: &str = "state_updates";
+pub(crate) const CLASSES_DIR: &str = "classes";
+pub(crate) const COMPILED_CLASSES_DIR: &str = "compiled_classes";
+
+/// A reader of a local archive of feeder gateway responses.
+pub struct ArchiveReader {
+    archive_dir: PathBuf,
+    // The first block that was not found in the archive in the last scan. Blocks are never removed
+    // from the archive, so the next scan can start from here.
+    first_missing_block: Mutex<BlockNumber>,
+}
+
+impl ArchiveReader {
+    pub fn new(archive_dir: impl Into<PathBuf>) -> Self {
+        ArchiveReader {
+            archive_dir: archive_dir.into(),
+            first_missing_block: Mutex::new(BlockNumber::default()),
+        }
+    }
+
+    fn block_path(&self, block_number: BlockNumber) -> PathBuf {
+        self.archive_dir.join(BLOCKS_DIR).join(format!("{block_number}.json"))
+    }
+
+    fn state_update_path(&self, block_number: BlockNumber) -> PathBuf {
+        self.archive_dir.join(STATE_UPDATES_DIR).join(format!("{block_number}.json"))
+    }
+
+    fn class_path(&self, dir: &str, class_hash: ClassHash) -> ReaderClientResult<PathBuf> {
+        Ok(self.archive_dir.join(dir).join(format!("{}.json", class_hash_file_name(class_hash)?)))
+    }
+
+    async fn is_block_available(&self, block_number: BlockNumber) -> ReaderClientResult<bool> {
+        Ok(is_file(&self.block_path(block_number)).await?
+            && is_file(&self.state_update_path(block_number)).await?)
+    }
+}
+
+/// Returns the name (without the extension) of the archive files of `class_hash`. The hash is
+/// formatted the same way the feeder gateway client formats it in its queries.
+pub(crate) fn class_hash_file_name(class_hash: ClassHash) -> ReaderClientResult<String> {
+    let class_hash = serde_json::to_string(&class_hash)?;
+    Ok(class_hash[1..class_hash.len() - 1].to_owned())
+}
+
+#[async_trait]
+impl StarknetReader for ArchiveReader {
+    #[instrument(skip(self), level = "debug")]
+    async fn block_number(&self) -> ReaderClientResult<Option<BlockNumber>> {
+        let mut first_missing_block = self.first_missing_block.lock().await;
+        while self.is_block_available(*first_missing_block).await? {
+            *first_missing_block = first_missing_block.next();
+        }
+        Ok(first_missing_block.prev())
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn block(&self, block_number: BlockNumber) -> ReaderClientResult<Option<Block>> {
+        load_object_from_file(&self.block_path(block_number)).await
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn class_by_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> ReaderClientResult<Option<GenericContractClass>> {
+        load_object_from_file(&self.class_path(CLASSES_DIR, class_hash)?).await
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn compiled_class_by_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> ReaderClientResult<Option<CasmContractClass>> {
+        load_object_from_file(&self.class_path(COMPILED_CLASSES_DIR, class_hash)?).await
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn state_update(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdate>> {
+        let state_update: Option<StateUpdate> =
+            load_object_from_file(&self.state_update_path(block_number)).await?;
+        Ok(state_update.map(|mut state_update| {
+            // Same as the feeder gateway client, remove empty storage diffs.
+            state_update.state_diff.storage_diffs.retain(|_k, v| !v.is_empty());
+            state_update
+        }))
+    }
+
+    // The archive holds only accepted blocks.
+    #[instrument(skip(self), level = "debug")]
+    async fn pending_data(&self) -> ReaderClientResult<Option<PendingData>> {
+        Ok(None)
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn block_by_hash(&self, _block_hash: BlockHash) -> ReaderClientResult<Option<Block>> {
+        unsupported("get_block by hash")
+    }
+
+    // The archive holds only accepted blocks.
+    #[instrument(skip(self), level = "debug")]
+    async fn pending_block(&self) -> ReaderClientResult<Option<PendingBlock>> {
+        Ok(None)
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn transaction(
+        &self,
+        _transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionInfo>> {
+        unsupported("get_transaction")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn transaction_receipt(
+        &self,
+        _transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionReceiptInfo>> {
+        unsupported("get_transaction_receipt")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn transaction_status(
+        &self,
+        _transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<TransactionStatusInfo> {
+        unsupported("get_transaction_status")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn block_signature(
+        &self,
+        _block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<BlockSignatureData>> {
+        unsupported("get_signature")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn public_key(&self) -> ReaderClientResult<StarkFelt> {
+        unsupported("get_public_key")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn contract_addresses(&self) -> ReaderClientResult<ContractAddresses> {
+        unsupported("get_contract_addresses")
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn state_update_with_block(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdateWithBlock>> {
+        let Some(block) = self.block(block_number).await? else {
+            return Ok(None);
+        };
+        Ok(self
+            .state_update(block_number)
+            .await?
+            .map(|state_update| StateUpdateWithBlock { block, state_update }))
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn storage_at(
+        &self,
+        _contract_address: ContractAddress,
+        _key: StorageKey,
+        _block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StarkFelt>> {
+        unsupported("get_storage_at")
+    }
+}
+
+// Fails a request for data that isn't in the archive.
+fn unsupported<T>(request: &str) -> ReaderClientResult<T> {
+    Err(ReaderClientError::Unsupported {
+        request: request.to_owned(),
+        reader: "archive".to_owned(),
+    })
+}
+
+/// Loads an object from a json file, returning None if the file doesn't exist.
+async fn is_file(path: &Path) -> ReaderClientResult<bool> {
+    match tokio::fs::metadata(path).await {
+        Ok(metadata) => Ok(metadata.is_file()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn load_object_from_file<Object: for<'a> Deserialize<'a>>(
+    path: &Path,
+) -> ReaderClientResult<Option<Object>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(raw_object) => Ok(Some(serde_json::from_str(&raw_object)?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            debug!("Archive file {} not found.", path.display());
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// A central source that reads the data from a local archive directory.
+pub type ArchiveSource = GenericCentralSource<ArchiveReader>;
+
+impl ArchiveSource {
+    pub fn new(
+        archive_dir: impl Into<PathBuf>,
+        concurrent_requests: usize,
+        storage_reader: StorageReader,
+    ) -> ArchiveSource {
+        ArchiveSource {
+            concurrent_requests,
+            starknet_client: Arc::new(ArchiveReader::new(archive_dir)),
+            storage_reader,
+            state_update_stream_config: StateUpdateStreamConfig::default(),
+            // The archive contains the compiled classes, so reading them is cheaper than compiling.
+            compile_classes_locally: false,
+            unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+            pending_classes: PendingClasses::default(),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use papyrus_common::pending_data::PendingData;
+use papyrus_common::SyncingState;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::test_utils::get_test_storage;
+use papyrus_storage::StorageReader;
+use pretty_assertions::assert_eq;
+use serde::Serialize;
+use serde_json::json;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_client::reader::{
+    Block, GenericContractClass, ReaderClientError, StarknetReader, StateUpdate,
+    StateUpdateWithBlock,
+};
+use tempfile::{tempdir, TempDir};
+use tokio::sync::RwLock;
+
+use super::archive::{
+    class_hash_file_name, ArchiveReader, ArchiveSource, BLOCKS_DIR, CLASSES_DIR,
+    COMPILED_CLASSES_DIR, STATE_UPDATES_DIR,
+};
+use crate::{Checkpoints, GenericStateSync, SyncConfig};
+
+const SYNC_SLEEP_DURATION: Duration = Duration::from_millis(50);
+const MAX_TIME_TO_SYNC: Duration = Duration::from_secs(5);
+const ZERO_GLOBAL_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn read_client_resource(file_name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../starknet_client/resources/reader")
+        .join(file_name);
+    fs::read_to_string(path).unwrap()
+}
+
+fn write_archive_file(archive_dir: &Path, dir: &str, file_name: &str, content: &str) {
+    let dir: PathBuf = archive_dir.join(dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(format!("{file_name}.json")), content).unwrap();
+}
+
+fn write_archive_object(archive_dir: &Path, dir: &str, file_name: &str, object: &impl Serialize) {
+    write_archive_file(archive_dir, dir, file_name, &serde_json::to_string(object).unwrap());
+}
+
+fn block_hash(block_number: u64) -> BlockHash {
+    BlockHash(stark_felt!(block_number + 1))
+}
+
+// Writes a block and a state update that extend the chain of `block_hash`.
+fn write_archive_block(archive_dir: &Path, block_number: u64) {
+    let block = Block {
+        block_number: BlockNumber(block_number),
+        block_hash: block_hash(block_number),
+        parent_block_hash: block_number.checked_sub(1).map(block_hash).unwrap_or_default(),
+        ..Block::default()
+    };
+    let state_update =
+        StateUpdate { block_hash: block_hash(block_number), ..StateUpdate::default() };
+    // The feeder gateway returns global roots without the "0x" prefix.
+    let mut block = serde_json::to_value(block).unwrap();
+    block["state_root"] = json!(ZERO_GLOBAL_ROOT);
+    let mut state_update = serde_json::to_value(state_update).unwrap();
+    state_update["new_root"] = json!(ZERO_GLOBAL_ROOT);
+    state_update["old_root"] = json!(ZERO_GLOBAL_ROOT);
+    write_archive_object(archive_dir, BLOCKS_DIR, &block_number.to_string(), &block);
+    write_archive_object(archive_dir, STATE_UPDATES_DIR, &block_number.to_string(), &state_update);
+}
+
+#[tokio::test]
+async fn block_number_stops_at_gap() {
+    let archive_dir = tempdir().unwrap();
+    let reader = ArchiveReader::new(archive_dir.path());
+    assert_eq!(reader.block_number().await.unwrap(), None);
+
+    write_archive_block(archive_dir.path(), 0);
+    write_archive_block(archive_dir.path(), 1);
+    write_archive_block(archive_dir.path(), 3);
+    assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(1)));
+
+    // A block without a state update is not available yet.
+    write_archive_object(archive_dir.path(), BLOCKS_DIR, "2", &Block::default());
+    assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(1)));
+
+    write_archive_block(archive_dir.path(), 2);
+    assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(3)));
+}
+
+#[tokio::test]
+async fn read_recorded_objects() {
+    let archive_dir = tempdir().unwrap();
+    let class_hash = ClassHash(stark_felt!("0x7"));
+    let class_file_name = class_hash_file_name(class_hash).unwrap();
+    write_archive_file(archive_dir.path(), BLOCKS_DIR, "0", &read_client_resource("block.json"));
+    write_archive_file(
+        archive_dir.path(),
+        STATE_UPDATES_DIR,
+        "0",
+        &read_client_resource("block_state_update.json"),
+    );
+    write_archive_file(
+        archive_dir.path(),
+        CLASSES_DIR,
+        &class_file_name,
+        &read_client_resource("contract_class.json"),
+    );
+    write_archive_file(
+        archive_dir.path(),
+        COMPILED_CLASSES_DIR,
+        &class_file_name,
+        &read_client_resource("casm_contract_class.json"),
+    );
+    let reader = ArchiveReader::new(archive_dir.path());
+
+    let expected_block: Block = serde_json::from_str(&read_client_resource("block.json")).unwrap();
+    assert_eq!(reader.block(BlockNumber(0)).await.unwrap(), Some(expected_block.clone()));
+
+    let mut expected_state_update: StateUpdate =
+        serde_json::from_str(&read_client_resource("block_state_update.json")).unwrap();
+    expected_state_update.state_diff.storage_diffs.retain(|_k, v| !v.is_empty());
+    assert_eq!(
+        reader.state_update(BlockNumber(0)).await.unwrap(),
+        Some(expected_state_update.clone())
+    );
+    assert_eq!(
+        reader.state_update_with_block(BlockNumber(0)).await.unwrap(),
+        Some(StateUpdateWithBlock { block: expected_block, state_update: expected_state_update })
+    );
+
+    assert!(matches!(
+        reader.class_by_hash(class_hash).await.unwrap(),
+        Some(GenericContractClass::Cairo1ContractClass(_))
+    ));
+    let expected_casm: CasmContractClass =
+        serde_json::from_str(&read_client_resource("casm_contract_class.json")).unwrap();
+    assert_eq!(reader.compiled_class_by_hash(class_hash).await.unwrap(), Some(expected_casm));
+
+    // Missing files.
+    assert!(reader.block(BlockNumber(1)).await.unwrap().is_none());
+    assert!(reader.state_update(BlockNumber(1)).await.unwrap().is_none());
+    assert!(reader.state_update_with_block(BlockNumber(1)).await.unwrap().is_none());
+    assert!(reader.class_by_hash(ClassHash(stark_felt!("0x8"))).await.unwrap().is_none());
+
+    // Data that isn't in the archive.
+    assert!(reader.pending_block().await.unwrap().is_none());
+    assert_matches!(
+        reader.public_key().await,
+        Err(ReaderClientError::Unsupported { request, .. }) if request == "get_public_key"
+    );
+}
+
+#[tokio::test]
+async fn unreadable_file() {
+    let archive_dir = tempdir().unwrap();
+    // A directory in place of the block file can't be read.
+    fs::create_dir_all(archive_dir.path().join(BLOCKS_DIR).join("0.json")).unwrap();
+    let reader = ArchiveReader::new(archive_dir.path());
+
+    assert_matches!(reader.block(BlockNumber(0)).await, Err(ReaderClientError::IoError(_)));
+}
+
+async fn wait_for_markers(reader: &StorageReader, expected_marker: BlockNumber) {
+    let started_at = tokio::time::Instant::now();
+    loop {
+        let txn = reader.begin_ro_txn().unwrap();
+        let header_marker = txn.get_header_marker().unwrap();
+        let state_marker = txn.get_state_marker().unwrap();
+        drop(txn);
+        assert!(header_marker <= expected_marker, "Synced blocks beyond {expected_marker}.");
+        if header_marker == expected_marker && state_marker == expected_marker {
+            return;
+        }
+        assert!(started_at.elapsed() < MAX_TIME_TO_SYNC, "Sync did not reach {expected_marker}.");
+        tokio::time::sleep(SYNC_SLEEP_DURATION).await;
+    }
+}
+
+#[tokio::test]
+async fn sync_from_archive_with_gap() {
+    let archive_dir: TempDir = tempdir().unwrap();
+    for block_number in [0, 1, 2, 4] {
+        write_archive_block(archive_dir.path(), block_number);
+    }
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let mut state_sync = GenericStateSync {
+        config: SyncConfig {
+            block_propagation_sleep_duration: SYNC_SLEEP_DURATION,
+            recoverable_error_sleep_duration: SYNC_SLEEP_DURATION,
+            ..SyncConfig::default()
+        },
+        shared_syncing_state: Arc::new(RwLock::new(SyncingState::default())),
+        pending_data: Arc::new(RwLock::new(PendingData::default())),
+        central_source: Arc::new(ArchiveSource::new(archive_dir.path(), 10, reader.clone())),
+        reader: reader.clone(),
+        writer,
+        hooks: Vec::new(),
+        shutdown_receiver: None,
+        checkpoints: Checkpoints::default(),
+    };
+
+    let check_flow = async {
+        // The sync waits at the gap.
+        wait_for_markers(&reader, BlockNumber(3)).await;
+        tokio::time::sleep(SYNC_SLEEP_DURATION * 3).await;
+        wait_for_markers(&reader, BlockNumber(3)).await;
+
+        // Filling the gap lets the sync continue.
+        write_archive_block(archive_dir.path(), 3);
+        wait_for_markers(&reader, BlockNumber(5)).await;
+        let stored_hash =
+            reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(4)).unwrap().unwrap();
+        assert_eq!(stored_hash.block_hash, block_hash(4));
+    };
+
+    tokio::select! {
+        sync_result = state_sync.run() => panic!("Sync stopped with {sync_result:?}."),
+        _ = check_flow => {},
+    }
+}
@@ -1,7 +1,11 @@
+mod casm_compilation;
+mod starknet_versions;
 mod state_update_stream;
 
 use std::collections::{BTreeMap, HashMap};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_stream::stream;
 use async_trait::async_trait;
@@ -10,36 +14,64 @@ use futures::stream::BoxStream;
 use futures_util::StreamExt;
 use indexmap::IndexMap;
 use itertools::chain;
+use metrics::increment_counter;
 #[cfg(test)]
 use mockall::automock;
-use papyrus_config::converters::{deserialize_optional_map, serialize_optional_map};
+use papyrus_common::pending_data::{PendingBlock, PendingData};
+use papyrus_config::converters::{
+    deserialize_milliseconds_to_duration, deserialize_optional_map, deserialize_optional_path,
+    serialize_optional_map, serialize_optional_path,
+};
 use papyrus_config::dumping::{append_sub_config_name, ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, SerializedParam};
-use papyrus_storage::header::StarknetVersion;
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::db::RO;
+use papyrus_storage::header::{HeaderStorageReader, StarknetVersion};
 use papyrus_storage::state::StateStorageReader;
-use papyrus_storage::{StorageError, StorageReader};
-use serde::{Deserialize, Serialize};
+use papyrus_storage::{StorageError, StorageReader, StorageResult, StorageTxn};
+use serde::{Deserialize, Deserializer, Serialize};
 use starknet_api::block::{Block, BlockHash, BlockNumber};
 use starknet_api::core::{ClassHash, CompiledClassHash};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
-use starknet_api::state::{ContractClass, StateDiff};
+use starknet_api::state::{ContractClass, StateDiff, StateNumber};
 use starknet_api::StarknetApiError;
 use starknet_client::reader::{
-    GenericContractClass, ReaderClientError, StarknetFeederGatewayClient, StarknetReader,
+    Endpoint, FailoverReader, GenericContractClass, ReaderClientError, StarknetFeederGatewayClient,
+    StarknetReader,
 };
-use starknet_client::{ClientCreationError, RetryConfig};
+use starknet_client::request_limiter::{RequestLimiter, RequestLimiterConfig};
+use starknet_client::{ClientCreationError, HttpClientConfig, RetryConfig};
 use tracing::{debug, trace};
 
-use self::state_update_stream::StateUpdateStream;
+use self::casm_compilation::{compile_declared_class, is_compiled_with_linked_compiler};
+use self::starknet_versions::check_starknet_version;
+pub use self::starknet_versions::UnknownStarknetVersionPolicy;
+pub(crate) use self::state_update_stream::client_to_central_state_diff;
+pub use self::state_update_stream::StateUpdateStreamConfig;
+use self::state_update_stream::{download_class_if_necessary, StateUpdateStream};
+use crate::sync_metrics;
 
 pub type CentralResult<T> = Result<T, CentralError>;
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct CentralSourceConfig {
     pub concurrent_requests: usize,
     pub url: String,
+    #[serde(deserialize_with = "deserialize_urls")]
+    pub fallback_urls: Vec<String>,
+    pub max_lag_blocks: u64,
+    pub verify_block_hashes: bool,
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub health_check_timeout: Duration,
     #[serde(deserialize_with = "deserialize_optional_map")]
     pub http_headers: Option<HashMap<String, String>>,
     pub retry_config: RetryConfig,
+    pub http_client_config: HttpClientConfig,
+    pub request_limiter_config: RequestLimiterConfig,
+    pub state_update_stream_config: StateUpdateStreamConfig,
+    pub compile_classes_locally: bool,
+    pub unknown_starknet_version: UnknownStarknetVersionPolicy,
+    #[serde(deserialize_with = "deserialize_optional_path")]
+    pub recording_dir: Option<PathBuf>,
 }
 
 impl Default for CentralSourceConfig {
@@ -47,12 +79,26 @@ impl Default for CentralSourceConfig {
         CentralSourceConfig {
             concurrent_requests: 10,
             url: String::from("https://alpha-mainnet.starknet.io/"),
+            fallback_urls: Vec::new(),
+            max_lag_blocks: 10,
+            verify_block_hashes: false,
+            health_check_timeout: Duration::from_secs(5),
             http_headers: None,
             retry_config: RetryConfig {
                 retry_base_millis: 30,
                 retry_max_delay_millis: 30000,
                 max_retries: 10,
+                jitter: true,
+                endpoint_max_retries: HashMap::new(),
+                circuit_breaker_failure_threshold: 30,
+                circuit_breaker_cooldown_millis: 10000,
             },
+            http_client_config: HttpClientConfig::default(),
+            request_limiter_config: RequestLimiterConfig::default(),
+            state_update_stream_config: StateUpdateStreamConfig::default(),
+            compile_classes_locally: false,
+            unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+            recording_dir: None,
         }
     }
 }
@@ -64,17 +110,78 @@ impl SerializeConfig for CentralSourceConfig {
                 "concurrent_requests",
                 &self.concurrent_requests,
                 "Maximum number of concurrent requests to Starknet feeder-gateway for getting a \
-                 type of data (for example, blocks).",
+                 type of data (for example, blocks). The requests of all the types are limited \
+                 together by the request limiter.",
+            ),
+            ser_param(
+                "url",
+                &self.url,
+                "Starknet feeder-gateway URL. It should match chain_id. A file:// URL syncs from \
+                 a local archive directory instead.",
+            ),
+            ser_param(
+                "fallback_urls",
+                &self.fallback_urls.join(" "),
+                "'url1 url2 ...' Starknet feeder-gateway URLs to fail over to, in order of \
+                 preference, when the feeder-gateway at url fails or lags behind them.",
+            ),
+            ser_param(
+                "max_lag_blocks",
+                &self.max_lag_blocks,
+                "Maximum number of blocks a feeder-gateway may lag behind the other \
+                 feeder-gateways before failing over from it.",
+            ),
+            ser_param(
+                "verify_block_hashes",
+                &self.verify_block_hashes,
+                "If true and there are fallback URLs, the hash of every downloaded block is \
+                 compared to its hash in another feeder-gateway before the block is accepted.",
+            ),
+            ser_param(
+                "health_check_timeout",
+                &self.health_check_timeout.as_millis(),
+                "Time in milliseconds to wait for the latest block of every feeder-gateway when \
+                 checking their health, if there are fallback URLs. A feeder-gateway that doesn't \
+                 respond in time is considered unhealthy.",
             ),
-            ser_param("url", &self.url, "Starknet feeder-gateway URL. It should match chain_id."),
             ser_param(
                 "http_headers",
                 &serialize_optional_map(&self.http_headers),
                 "'k1:v1 k2:v2 ...' headers for SN-client.",
             ),
+            ser_param(
+                "compile_classes_locally",
+                &self.compile_classes_locally,
+                "If true, compiles the declared Sierra classes to CASM instead of downloading the \
+                 compiled classes, when the compiler linked to the node matches the Starknet \
+                 version of the block. A class whose compiled class hash doesn't match the \
+                 declared one is downloaded.",
+            ),
+            ser_param(
+                "unknown_starknet_version",
+                &self.unknown_starknet_version,
+                "How to handle a block of a Starknet version whose format isn't known to be \
+                 supported. One of halt (keep the sync at the last supported block) or warn (log \
+                 and sync the block).",
+            ),
+            ser_param(
+                "recording_dir",
+                &serialize_optional_path(&self.recording_dir),
+                "Path to a directory to record every request to the feeder-gateway and its \
+                 response in, for replaying them later.",
+            ),
         ]);
-        chain!(self_params_dump, append_sub_config_name(self.retry_config.dump(), "retry_config"))
-            .collect()
+        chain!(
+            self_params_dump,
+            append_sub_config_name(self.retry_config.dump(), "retry_config"),
+            append_sub_config_name(self.http_client_config.dump(), "http_client_config"),
+            append_sub_config_name(self.request_limiter_config.dump(), "request_limiter_config"),
+            append_sub_config_name(
+                self.state_update_stream_config.dump(),
+                "state_update_stream_config"
+            ),
+        )
+        .collect()
     }
 }
 
@@ -82,6 +189,42 @@ pub struct GenericCentralSource<TStarknetClient: StarknetReader + Send + Sync> {
     pub concurrent_requests: usize,
     pub starknet_client: Arc<TStarknetClient>,
     pub storage_reader: StorageReader,
+    pub state_update_stream_config: StateUpdateStreamConfig,
+    pub compile_classes_locally: bool,
+    pub unknown_starknet_version: UnknownStarknetVersionPolicy,
+    pub pending_classes: PendingClasses,
+}
+
+/// The classes of the pending block that were already downloaded, so the next polls of the same
+/// pending block download only the classes that were declared since.
+#[derive(Default)]
+pub struct PendingClasses(Mutex<(BlockHash, IndexMap<ClassHash, ApiContractClass>)>);
+
+impl PendingClasses {
+    // Returns the classes downloaded for the pending block with the given parent. The classes of
+    // another pending block are dropped.
+    fn get(&self, parent_block_hash: BlockHash) -> IndexMap<ClassHash, ApiContractClass> {
+        let mut pending_classes =
+            self.0.lock().expect("Pending classes lock should not be poisoned.");
+        if pending_classes.0 != parent_block_hash {
+            *pending_classes = (parent_block_hash, IndexMap::new());
+        }
+        pending_classes.1.clone()
+    }
+
+    // Adds classes downloaded for the pending block with the given parent, unless the pending
+    // block was replaced meanwhile.
+    fn extend(
+        &self,
+        parent_block_hash: BlockHash,
+        classes: impl IntoIterator<Item = (ClassHash, ApiContractClass)>,
+    ) {
+        let mut pending_classes =
+            self.0.lock().expect("Pending classes lock should not be poisoned.");
+        if pending_classes.0 == parent_block_hash {
+            pending_classes.1.extend(classes);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -137,6 +280,12 @@ pub enum CentralError {
     StorageError(#[from] StorageError),
     #[error("Wrong type of contract class")]
     BadContractClassType,
+    #[error(
+        "Block {block_number} has Starknet version {starknet_version:?}, whose format isn't \
+         supported by the node. Upgrade the node, or set central.unknown_starknet_version to warn \
+         to sync it anyway."
+    )]
+    UnsupportedStarknetVersion { block_number: BlockNumber, starknet_version: String },
 }
 
 #[cfg_attr(test, automock)]
@@ -153,6 +302,11 @@ pub trait CentralSourceTrait {
         initial_block_number: BlockNumber,
         up_to_block_number: BlockNumber,
     ) -> StateUpdatesStream<'_>;
+    fn stream_blocks_with_state_updates(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> BlocksWithStateUpdatesStream<'_>;
 
     async fn get_block_hash(
         &self,
@@ -164,13 +318,17 @@ pub trait CentralSourceTrait {
         initial_block_number: BlockNumber,
         up_to_block_number: BlockNumber,
     ) -> CompiledClassesStream<'_>;
+
+    async fn get_pending_data(&self) -> Result<Option<PendingData>, CentralError>;
 }
 
 pub(crate) type BlocksStream<'a> =
     BoxStream<'a, Result<(BlockNumber, Block, StarknetVersion), CentralError>>;
-type CentralStateUpdate =
+pub(crate) type CentralStateUpdate =
     (BlockNumber, BlockHash, StateDiff, IndexMap<ClassHash, DeprecatedContractClass>);
 pub(crate) type StateUpdatesStream<'a> = BoxStream<'a, CentralResult<CentralStateUpdate>>;
+pub(crate) type BlocksWithStateUpdatesStream<'a> =
+    BoxStream<'a, CentralResult<(Block, StarknetVersion, CentralStateUpdate)>>;
 type CentralCompiledClass = (ClassHash, CompiledClassHash, CasmContractClass);
 pub(crate) type CompiledClassesStream<'a> = BoxStream<'a, CentralResult<CentralCompiledClass>>;
 
@@ -210,7 +368,36 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
             up_to_block_number,
             self.starknet_client.clone(),
             self.storage_reader.clone(),
+            self.state_update_stream_config,
+            false,
+        )
+        .map(|maybe_state_update| maybe_state_update.map(|(_block, state_update)| state_update))
+        .boxed()
+    }
+
+    // Returns a stream of blocks with their state updates downloaded from the central source,
+    // requesting each block together with its state update.
+    fn stream_blocks_with_state_updates(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> BlocksWithStateUpdatesStream<'_> {
+        let unknown_starknet_version = self.unknown_starknet_version;
+        StateUpdateStream::new(
+            initial_block_number,
+            up_to_block_number,
+            self.starknet_client.clone(),
+            self.storage_reader.clone(),
+            self.state_update_stream_config,
+            true,
         )
+        .map(move |maybe_state_update| {
+            let (client_block, state_update) = maybe_state_update?;
+            let block_number = state_update.0;
+            let (block, starknet_version) =
+                client_to_central_block(block_number, Ok(client_block), unknown_starknet_version)?;
+            Ok((block, starknet_version, state_update))
+        })
         .boxed()
     }
 
@@ -229,7 +416,11 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
                     .buffered(self.concurrent_requests);
             while let Some((current_block_number, maybe_client_block)) = res.next().await {
                 let maybe_central_block =
-                    client_to_central_block(current_block_number, maybe_client_block);
+                    client_to_central_block(
+                        current_block_number,
+                        maybe_client_block,
+                        self.unknown_starknet_version,
+                    );
                 match maybe_central_block {
                     Ok(block_and_version) => {
                         yield Ok((current_block_number, block_and_version.0, block_and_version.1));
@@ -244,7 +435,8 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
         .boxed()
     }
 
-    // Returns a stream of compiled classes downloaded from the central source.
+    // Returns a stream of compiled classes downloaded from the central source, skipping classes
+    // that are already stored.
     fn stream_compiled_classes(
         &self,
         initial_block_number: BlockNumber,
@@ -260,15 +452,32 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
                         // TODO(yair): Consider expecting, since the state diffs should not contain
                         // holes and we suppose to never exceed the state marker.
                         Ok(None) => Err(CentralError::StateUpdateNotFound),
-                        Ok(Some(state_diff)) => Ok(state_diff),
+                        Ok(Some(state_diff)) => Ok((bn, state_diff)),
                     }
                 })
                 .flat_map(|maybe_state_diff| match maybe_state_diff {
-                    Ok(state_diff) => {
+                    Ok((block_number, state_diff)) => {
+                        let n_classes = state_diff.declared_classes.len();
                         state_diff
                             .declared_classes
                             .into_iter()
-                            .map(Ok)
+                            .enumerate()
+                            .filter_map(|(index, (class_hash, compiled_class_hash))| {
+                                let class_progress = ClassProgress { block_number, index, n_classes };
+                                match txn.has_casm(&class_hash) {
+                                    Ok(true) => {
+                                        debug!("Skipping already stored compiled class {class_hash} ({class_progress}).");
+                                        increment_counter!(sync_metrics::SKIPPED_COMPILED_CLASSES);
+                                        None
+                                    }
+                                    Ok(false) => Some(
+                                        self.class_to_compile(&txn, block_number, &class_hash)
+                                            .map(|class| (class_progress, class_hash, compiled_class_hash, class))
+                                            .map_err(CentralError::StorageError),
+                                    ),
+                                    Err(err) => Some(Err(CentralError::StorageError(err))),
+                                }
+                            })
                             .collect()
                     }
                     Err(err) => vec![Err(err)],
@@ -277,10 +486,23 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
             let mut compiled_classes = futures_util::stream::iter(class_hashes_iter)
                 .map(|maybe_class_hashes| async move {
                     match maybe_class_hashes {
-                        Ok((class_hash, compiled_class_hash)) => {
-                            trace!("Downloading compiled class {:?}.", class_hash);
+                        Ok((class_progress, class_hash, compiled_class_hash, class)) => {
+                            if let Some(class) = class {
+                                trace!("Compiling class {class_hash} ({class_progress}).");
+                                if let Some(compiled_class) =
+                                    compile_declared_class(class_hash, compiled_class_hash, class).await
+                                {
+                                    increment_counter!(sync_metrics::LOCALLY_COMPILED_CLASSES);
+                                    return Ok((class_hash, compiled_class_hash, compiled_class));
+                                }
+                            }
+                            trace!("Downloading compiled class {class_hash} ({class_progress}).");
                             match self.starknet_client.compiled_class_by_hash(class_hash).await {
-                                Ok(Some(compiled_class)) => Ok((class_hash, compiled_class_hash, compiled_class)),
+                                Ok(Some(compiled_class)) => {
+                                    debug!("Received compiled class {class_hash} ({class_progress}).");
+                                    increment_counter!(sync_metrics::DOWNLOADED_COMPILED_CLASSES);
+                                    Ok((class_hash, compiled_class_hash, compiled_class))
+                                }
                                 Ok(None) => Err(CentralError::CompiledClassNotFound{class_hash}),
                                 Err(err) => Err(CentralError::ClientError(Arc::new(err))),
                             }
@@ -304,16 +526,120 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> CentralSourceTrait
         }
         .boxed()
     }
+    // Returns the pending block and its state diff from the central source, with the definitions of
+    // the classes declared in the pending block.
+    async fn get_pending_data(&self) -> Result<Option<PendingData>, CentralError> {
+        let Some(client_pending_data) =
+            self.starknet_client.pending_data().await.map_err(Arc::new)?
+        else {
+            return Ok(None);
+        };
+        let starknet_client::reader::PendingData { block, state_update } = client_pending_data;
+        debug!("Received pending block with parent hash {}.", block.parent_block_hash);
+
+        let class_hashes = state_update.state_diff.class_hashes();
+        let mut known_classes = self.pending_classes.get(block.parent_block_hash);
+        let new_class_hashes: Vec<_> = class_hashes
+            .iter()
+            .filter(|class_hash| !known_classes.contains_key(*class_hash))
+            .copied()
+            .collect();
+        let new_classes = futures_util::stream::iter(new_class_hashes)
+            .map(|class_hash| async move {
+                let class = download_class_if_necessary(
+                    class_hash,
+                    self.starknet_client.clone(),
+                    self.storage_reader.clone(),
+                )
+                .await?
+                .ok_or(CentralError::ClassNotFound)?;
+                Ok::<_, CentralError>((class_hash, class))
+            })
+            .buffered(self.concurrent_requests)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<CentralResult<IndexMap<_, _>>>()?;
+        self.pending_classes.extend(block.parent_block_hash, new_classes.clone());
+        known_classes.extend(new_classes);
+        let classes = class_hashes
+            .into_iter()
+            .filter_map(|class_hash| {
+                known_classes.remove(&class_hash).map(|class| (class_hash, class))
+            })
+            .collect();
+        // Since 0.11 new classes can not be implicitly declared by deployment, so the pending
+        // block can't have classes of deployed contracts that were not declared.
+        let (state_diff, _deployed_contract_class_definitions) =
+            client_to_central_state_diff(state_update.state_diff, classes);
+
+        let pending_block = PendingBlock {
+            parent_block_hash: block.parent_block_hash,
+            gas_price: block.gas_price,
+            timestamp: block.timestamp,
+            sequencer_address: block.sequencer_address,
+            body: block.to_starknet_api_block_body().map_err(Arc::new)?,
+        };
+        trace!("Pending block: {pending_block:#?}, pending state diff: {state_diff:?}.");
+        Ok(Some(PendingData { block: pending_block, state_diff }))
+    }
+}
+
+impl<TStarknetClient: StarknetReader + Send + Sync> GenericCentralSource<TStarknetClient> {
+    // Returns the definition of a class declared in the given block if it should be compiled
+    // locally instead of downloading its compiled class.
+    fn class_to_compile(
+        &self,
+        txn: &StorageTxn<'_, RO>,
+        block_number: BlockNumber,
+        class_hash: &ClassHash,
+    ) -> StorageResult<Option<ContractClass>> {
+        if !self.compile_classes_locally {
+            return Ok(None);
+        }
+        let Some(starknet_version) = txn.get_starknet_version(block_number)? else {
+            return Ok(None);
+        };
+        if !is_compiled_with_linked_compiler(&starknet_version) {
+            trace!(
+                "Not compiling class {class_hash} of Starknet version {starknet_version:?} \
+                 locally."
+            );
+            return Ok(None);
+        }
+        txn.get_state_reader()?
+            .get_class_definition_at(StateNumber::right_after_block(block_number), class_hash)
+    }
+}
+
+// The position of a class among the declared classes of its block, for reporting the progress.
+#[derive(Clone, Copy)]
+struct ClassProgress {
+    block_number: BlockNumber,
+    index: usize,
+    n_classes: usize,
+}
+
+impl std::fmt::Display for ClassProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "class {}/{} of block {}", self.index + 1, self.n_classes, self.block_number)
+    }
 }
 
 fn client_to_central_block(
     current_block_number: BlockNumber,
     maybe_client_block: Result<Option<starknet_client::reader::Block>, ReaderClientError>,
+    unknown_starknet_version: UnknownStarknetVersionPolicy,
 ) -> CentralResult<(Block, StarknetVersion)> {
     let res = match maybe_client_block {
         Ok(Some(block)) => {
             debug!("Received new block {current_block_number} with hash {}.", block.block_hash);
             trace!("Block: {block:#?}.");
+            check_starknet_version(
+                unknown_starknet_version,
+                current_block_number,
+                &StarknetVersion(block.starknet_version.clone()),
+            )?;
             Ok(block
                 .to_starknet_api_block_and_version()
                 .map_err(|err| CentralError::ClientError(Arc::new(err)))?)
@@ -330,7 +656,15 @@ fn client_to_central_block(
     }
 }
 
-pub type CentralSource = GenericCentralSource<StarknetFeederGatewayClient>;
+fn deserialize_urls<'de, D>(de: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    Ok(raw_str.split_whitespace().map(|url| url.to_owned()).collect())
+}
+
+pub type CentralSource = GenericCentralSource<FailoverReader<StarknetFeederGatewayClient>>;
 
 impl CentralSource {
     pub fn new(
@@ -338,17 +672,45 @@ impl CentralSource {
         node_version: &'static str,
         storage_reader: StorageReader,
     ) -> Result<CentralSource, ClientCreationError> {
-        let starknet_client = StarknetFeederGatewayClient::new(
-            &config.url,
-            config.http_headers,
-            node_version,
-            config.retry_config,
-        )?;
+        // Every feeder gateway has its own request limiter, and its own recordings directory.
+        let endpoints = std::iter::once(&config.url)
+            .chain(&config.fallback_urls)
+            .enumerate()
+            .map(|(index, url)| {
+                let starknet_client = StarknetFeederGatewayClient::new(
+                    url,
+                    config.http_headers.clone(),
+                    node_version,
+                    config.retry_config.clone(),
+                )?
+                .with_http_client_config(&config.http_client_config)?
+                .with_request_limiter(Arc::new(RequestLimiter::new(config.request_limiter_config)));
+                let starknet_client = match &config.recording_dir {
+                    Some(recording_dir) if index == 0 => {
+                        starknet_client.with_recording(recording_dir)?
+                    }
+                    Some(recording_dir) => starknet_client
+                        .with_recording(&recording_dir.join(format!("fallback_{index}")))?,
+                    None => starknet_client,
+                };
+                Ok(Endpoint { url: url.clone(), reader: starknet_client })
+            })
+            .collect::<Result<Vec<_>, ClientCreationError>>()?;
+        let starknet_client = FailoverReader::new(
+            endpoints,
+            config.max_lag_blocks,
+            config.verify_block_hashes,
+            config.health_check_timeout,
+        );
 
         Ok(CentralSource {
             concurrent_requests: config.concurrent_requests,
             starknet_client: Arc::new(starknet_client),
             storage_reader,
+            state_update_stream_config: config.state_update_stream_config,
+            compile_classes_locally: config.compile_classes_locally,
+            unknown_starknet_version: config.unknown_starknet_version,
+            pending_classes: PendingClasses::default(),
         })
     }
 }
@@ -0,0 +1,130 @@
+//! Local compilation of declared Sierra classes to CASM, as an alternative to downloading the
+//! compiled classes from the feeder gateway.
+
+#[cfg(test)]
+#[path = "casm_compilation_test.rs"]
+mod casm_compilation_test;
+
+use cairo_lang_starknet::casm_contract_class::{CasmContractClass, StarknetSierraCompilationError};
+use cairo_lang_starknet::contract_class::{
+    ContractClass as CairoLangContractClass, ContractEntryPoint, ContractEntryPoints,
+};
+use cairo_lang_utils::bigint::BigUintAsHex;
+use num_bigint::BigUint;
+use papyrus_common::class_hash::calculate_compiled_class_hash;
+use papyrus_storage::header::StarknetVersion;
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::{ContractClass, EntryPointType};
+use tracing::{debug, warn};
+
+// The Starknet versions, as [major, minor, patch], in which the gateway compiled the declared
+// classes with the compiler linked to the node (cairo-lang 2.1). The first version is included and
+// the second one isn't. Classes declared in other versions may compile to a different CASM, so they
+// are downloaded.
+const LINKED_COMPILER_STARKNET_VERSIONS: ([u64; 3], [u64; 3]) = ([0, 12, 1], [0, 12, 3]);
+
+// Starknet API drops the version of the class, and all the classes declared so far have this one.
+const CONTRACT_CLASS_VERSION: &str = "0.1.0";
+
+/// Returns whether classes declared in the given Starknet version were compiled by the gateway with
+/// the compiler linked to the node.
+pub(crate) fn is_compiled_with_linked_compiler(starknet_version: &StarknetVersion) -> bool {
+    let (first_version, first_unsupported_version) = LINKED_COMPILER_STARKNET_VERSIONS;
+    parse_starknet_version(&starknet_version.0)
+        .is_some_and(|version| (first_version..first_unsupported_version).contains(&version))
+}
+
+/// Compiles the class and returns the compiled class if its hash matches the declared compiled
+/// class hash. Otherwise, returns None and the compiled class should be downloaded.
+pub(crate) async fn compile_declared_class(
+    class_hash: ClassHash,
+    compiled_class_hash: CompiledClassHash,
+    class: ContractClass,
+) -> Option<CasmContractClass> {
+    // Compilation is CPU bound, so it shouldn't block the runtime.
+    let compiled_class = match tokio::task::spawn_blocking(move || compile_class(&class)).await {
+        Ok(Ok(compiled_class)) => compiled_class,
+        Ok(Err(err)) => {
+            warn!("Failed to compile class {class_hash}: {err}. Downloading it instead.");
+            return None;
+        }
+        Err(err) => {
+            warn!("Compilation of class {class_hash} panicked: {err}. Downloading it instead.");
+            return None;
+        }
+    };
+    let calculated_compiled_class_hash = match calculate_compiled_class_hash(&compiled_class) {
+        Ok(calculated_compiled_class_hash) => calculated_compiled_class_hash,
+        Err(reason) => {
+            warn!(
+                "Failed to calculate the compiled class hash of class {class_hash}: {reason}. \
+                 Downloading it instead."
+            );
+            return None;
+        }
+    };
+    if calculated_compiled_class_hash != compiled_class_hash {
+        warn!(
+            "Class {class_hash} compiled to compiled class hash {calculated_compiled_class_hash} \
+             instead of the declared {compiled_class_hash}. Downloading it instead."
+        );
+        return None;
+    }
+    debug!("Compiled class {class_hash} locally.");
+    Some(compiled_class)
+}
+
+// Compiles a Sierra class to CASM with the pythonic hints, as returned by the feeder gateway.
+fn compile_class(
+    class: &ContractClass,
+) -> Result<CasmContractClass, StarknetSierraCompilationError> {
+    let entry_points = |entry_point_type: EntryPointType| {
+        let mut entry_points: Vec<_> = class
+            .entry_point_by_type
+            .get(&entry_point_type)
+            .into_iter()
+            .flatten()
+            .map(|entry_point| ContractEntryPoint {
+                selector: felt_as_big_uint(&entry_point.selector.0),
+                function_idx: entry_point.function_idx.0,
+            })
+            .collect();
+        // The compiler expects the entry points to be sorted by their selectors.
+        entry_points.sort_by(|first, second| first.selector.cmp(&second.selector));
+        entry_points
+    };
+    let class = CairoLangContractClass {
+        sierra_program: class
+            .sierra_program
+            .iter()
+            .map(|felt| BigUintAsHex { value: felt_as_big_uint(felt) })
+            .collect(),
+        sierra_program_debug_info: None,
+        contract_class_version: CONTRACT_CLASS_VERSION.to_owned(),
+        entry_points_by_type: ContractEntryPoints {
+            external: entry_points(EntryPointType::External),
+            l1_handler: entry_points(EntryPointType::L1Handler),
+            constructor: entry_points(EntryPointType::Constructor),
+        },
+        abi: None,
+    };
+    CasmContractClass::from_contract_class(class, true)
+}
+
+fn felt_as_big_uint(felt: &StarkFelt) -> BigUint {
+    BigUint::from_bytes_be(felt.bytes())
+}
+
+// Parses a version of the form "major.minor.patch", where a missing patch is 0.
+fn parse_starknet_version(version: &str) -> Option<[u64; 3]> {
+    let mut parsed_version = [0; 3];
+    let mut components = version.split('.');
+    for (parsed_component, component) in parsed_version.iter_mut().zip(components.by_ref()) {
+        *parsed_component = component.parse().ok()?;
+    }
+    if components.next().is_some() {
+        return None;
+    }
+    Some(parsed_version)
+}
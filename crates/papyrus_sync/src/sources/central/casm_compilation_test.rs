@@ -0,0 +1,25 @@
+use papyrus_storage::header::StarknetVersion;
+use pretty_assertions::assert_eq;
+
+use crate::sources::central::casm_compilation::is_compiled_with_linked_compiler;
+
+#[test]
+fn linked_compiler_starknet_versions() {
+    for (version, expected) in [
+        ("0.11.2", false),
+        ("0.12.0", false),
+        ("0.12.1", true),
+        ("0.12.2", true),
+        ("0.12.3", false),
+        ("0.13.0", false),
+        ("0.12", false),
+        ("0.12.1.1", false),
+        ("", false),
+    ] {
+        assert_eq!(
+            is_compiled_with_linked_compiler(&StarknetVersion(version.to_owned())),
+            expected,
+            "{version}"
+        );
+    }
+}
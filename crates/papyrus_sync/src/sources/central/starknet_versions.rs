@@ -0,0 +1,110 @@
+//! The Starknet versions whose blocks and state updates the central source knows how to parse.
+//!
+//! The feeder gateway changes the format of its objects in some Starknet versions. A block of a
+//! version newer than the versions the node knows might be parsed wrongly without failing, so the
+//! central source checks the version of every block before it's returned. The state updates are
+//! checked through their blocks, since a state update is synced only after its block.
+
+#[cfg(test)]
+#[path = "starknet_versions_test.rs"]
+mod starknet_versions_test;
+
+use papyrus_storage::header::StarknetVersion;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use tracing::warn;
+
+use super::{CentralError, CentralResult};
+
+/// A Starknet version that changed the format of the blocks or the state updates.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct FormatTransition {
+    /// The first Starknet version with the format, as its numeric components.
+    pub version: &'static [u64],
+    /// The changes in the format.
+    pub changes: &'static str,
+}
+
+/// The supported formats, ordered by their first Starknet version. Blocks before Starknet 0.9.1
+/// don't report their version, and have the format of the first entry.
+pub(crate) const FORMAT_TRANSITIONS: &[FormatTransition] = &[
+    FormatTransition { version: &[], changes: "The initial format." },
+    FormatTransition { version: &[0, 9, 1], changes: "Blocks report their Starknet version." },
+    FormatTransition {
+        version: &[0, 10, 0],
+        changes: "Invoke transactions of version 1 with a nonce, declare transactions of version \
+                  1 and nonces in the state diff.",
+    },
+    FormatTransition { version: &[0, 10, 1], changes: "Deploy account transactions." },
+    FormatTransition {
+        version: &[0, 11, 0],
+        changes: "Sierra classes, declare transactions of version 2, and declared and replaced \
+                  classes in the state diff.",
+    },
+    FormatTransition {
+        version: &[0, 12, 1],
+        changes: "Reverted transactions, with an execution status and a revert error in their \
+                  receipts.",
+    },
+];
+
+// Starknet 0.13.0 introduced transactions of version 3, which the node can't parse.
+const FIRST_UNSUPPORTED_VERSION: &[u64] = &[0, 13, 0];
+
+/// How the central source handles a block of a Starknet version whose format isn't supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownStarknetVersionPolicy {
+    /// Keep the sync at the last supported block, and retry the block until the node is
+    /// upgraded or the policy is changed.
+    #[default]
+    Halt,
+    /// Log a warning and parse the block as the latest supported format.
+    Warn,
+}
+
+/// Returns the format transition that determines the format of the Starknet version, or None if
+/// the version isn't supported.
+pub(crate) fn format_of(starknet_version: &StarknetVersion) -> Option<&'static FormatTransition> {
+    if starknet_version.0.is_empty() {
+        return FORMAT_TRANSITIONS.first();
+    }
+    let version = starknet_version
+        .0
+        .split('.')
+        .map(|component| component.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if version.as_slice() >= FIRST_UNSUPPORTED_VERSION {
+        return None;
+    }
+    FORMAT_TRANSITIONS.iter().rev().find(|transition| transition.version <= version.as_slice())
+}
+
+/// Checks that the format of the block is supported, and handles an unsupported Starknet version
+/// according to the policy.
+pub(crate) fn check_starknet_version(
+    policy: UnknownStarknetVersionPolicy,
+    block_number: BlockNumber,
+    starknet_version: &StarknetVersion,
+) -> CentralResult<()> {
+    if format_of(starknet_version).is_some() {
+        return Ok(());
+    }
+    match policy {
+        UnknownStarknetVersionPolicy::Halt => Err(CentralError::UnsupportedStarknetVersion {
+            block_number,
+            starknet_version: starknet_version.0.clone(),
+        }),
+        UnknownStarknetVersionPolicy::Warn => {
+            let latest_format =
+                FORMAT_TRANSITIONS.last().expect("There should be a supported format.");
+            warn!(
+                "Block {block_number} has Starknet version {:?}, whose format isn't supported. \
+                 Syncing it anyway as the latest supported format, of Starknet {:?}: {}",
+                starknet_version.0, latest_format.version, latest_format.changes
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use assert_matches::assert_matches;
+use papyrus_storage::header::StarknetVersion;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+use starknet_client::reader::Block;
+use test_utils::get_absolute_path;
+
+use crate::sources::central::starknet_versions::{
+    check_starknet_version, format_of, UnknownStarknetVersionPolicy, FIRST_UNSUPPORTED_VERSION,
+    FORMAT_TRANSITIONS,
+};
+use crate::sources::central::{client_to_central_block, CentralError};
+
+fn version(version: &str) -> StarknetVersion {
+    StarknetVersion(version.to_owned())
+}
+
+#[test]
+fn format_transitions_are_ordered() {
+    for transitions in FORMAT_TRANSITIONS.windows(2) {
+        assert!(transitions[0].version < transitions[1].version, "{transitions:?}");
+    }
+    assert!(FORMAT_TRANSITIONS.last().unwrap().version < FIRST_UNSUPPORTED_VERSION);
+}
+
+// A block of an unsupported version is refused even though its format is known.
+#[test]
+fn unsupported_block_is_refused() {
+    let path = get_absolute_path("crates/starknet_client/resources/reader/block.json");
+    let client_block: Block =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let client_block = Block { starknet_version: "0.13.0".to_owned(), ..client_block };
+    let err = client_to_central_block(
+        BlockNumber(273466),
+        Ok(Some(client_block)),
+        UnknownStarknetVersionPolicy::Halt,
+    )
+    .unwrap_err();
+    assert_matches!(
+        err,
+        CentralError::UnsupportedStarknetVersion { block_number: BlockNumber(273466), starknet_version }
+        if starknet_version == "0.13.0"
+    );
+}
+
+#[test]
+fn unsupported_versions() {
+    for starknet_version in ["0.13.0", "0.13.1", "1.0.0", "0.12.x", "0..1", "latest"] {
+        assert_eq!(format_of(&version(starknet_version)), None, "{starknet_version}");
+    }
+}
+
+#[test]
+fn check_by_policy() {
+    let block_number = BlockNumber(1);
+    for policy in [UnknownStarknetVersionPolicy::Halt, UnknownStarknetVersionPolicy::Warn] {
+        check_starknet_version(policy, block_number, &version("0.12.1")).unwrap();
+    }
+    check_starknet_version(UnknownStarknetVersionPolicy::Warn, block_number, &version("0.13.0"))
+        .unwrap();
+    let err = check_starknet_version(
+        UnknownStarknetVersionPolicy::Halt,
+        block_number,
+        &version("0.13.0"),
+    )
+    .unwrap_err();
+    assert_matches!(
+        err,
+        CentralError::UnsupportedStarknetVersion { block_number: BlockNumber(1), starknet_version }
+        if starknet_version == "0.13.0"
+    );
+}
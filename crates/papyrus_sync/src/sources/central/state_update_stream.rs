@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Poll;
@@ -6,35 +6,94 @@ use std::task::Poll;
 use futures_util::stream::FuturesOrdered;
 use futures_util::{Future, Stream, StreamExt};
 use indexmap::IndexMap;
+use papyrus_config::dumping::{ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, SerializedParam};
 use papyrus_storage::state::StateStorageReader;
 use papyrus_storage::StorageReader;
+use serde::{Deserialize, Serialize};
 use starknet_api::block::BlockNumber;
 use starknet_api::core::ClassHash;
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::{StateDiff, StateNumber};
-use starknet_client::reader::{ReaderClientResult, StarknetReader, StateUpdate};
+use starknet_client::reader::{
+    Block, ReaderClientResult, StarknetReader, StateUpdate, StateUpdateWithBlock,
+};
 use tracing::log::trace;
 use tracing::{debug, instrument};
 
 use super::{ApiContractClass, CentralResult, CentralStateUpdate};
 use crate::CentralError;
 
-// TODO (dan): take from config.
-const MAX_STATE_UPDATES_TO_DOWNLOAD: usize = 100;
-const MAX_STATE_UPDATES_TO_STORE_IN_MEMORY: usize = 100;
-const MAX_CLASSES_TO_DOWNLOAD: usize = 100;
-const CLASSES_INITIAL_CAPACITY: usize = MAX_STATE_UPDATES_TO_STORE_IN_MEMORY * 5;
+// The expected number of classes per state update, for preallocating the class queues.
+const CLASSES_PER_STATE_UPDATE_CAPACITY: usize = 5;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StateUpdateStreamConfig {
+    pub max_state_updates_to_download: usize,
+    pub max_state_updates_to_store_in_memory: usize,
+    pub max_classes_to_download: usize,
+    pub max_classes_in_memory: usize,
+}
+
+impl Default for StateUpdateStreamConfig {
+    fn default() -> Self {
+        StateUpdateStreamConfig {
+            max_state_updates_to_download: 100,
+            max_state_updates_to_store_in_memory: 100,
+            max_classes_to_download: 100,
+            max_classes_in_memory: 200,
+        }
+    }
+}
+
+impl SerializeConfig for StateUpdateStreamConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "max_state_updates_to_download",
+                &self.max_state_updates_to_download,
+                "Maximum number of state updates to download at a given time.",
+            ),
+            ser_param(
+                "max_state_updates_to_store_in_memory",
+                &self.max_state_updates_to_store_in_memory,
+                "Maximum number of downloaded state updates to keep in memory until their classes \
+                 are downloaded.",
+            ),
+            ser_param(
+                "max_classes_to_download",
+                &self.max_classes_to_download,
+                "Maximum number of classes to download at a given time.",
+            ),
+            ser_param(
+                "max_classes_in_memory",
+                &self.max_classes_in_memory,
+                "Maximum number of classes that are downloaded or kept in memory until their \
+                 state update is returned, unless a single state update has more classes. \
+                 Together with the maximum response size, it bounds the memory of the classes.",
+            ),
+        ])
+    }
+}
 
 type TasksQueue<T> = FuturesOrdered<Pin<Box<dyn Future<Output = T> + Send>>>;
 type NumberOfClasses = usize;
+// A downloaded state update, with its block if the blocks are downloaded together with the state
+// updates.
+type DownloadedStateUpdate = (Option<Block>, StateUpdate);
 
 pub(crate) struct StateUpdateStream<TStarknetClient: StarknetReader + Send + 'static> {
     initial_block_number: BlockNumber,
     up_to_block_number: BlockNumber,
     starknet_client: Arc<TStarknetClient>,
     storage_reader: StorageReader,
-    download_state_update_tasks: TasksQueue<(BlockNumber, ReaderClientResult<Option<StateUpdate>>)>,
+    config: StateUpdateStreamConfig,
+    // Whether to download each state update together with its block in a single request.
+    with_blocks: bool,
+    download_state_update_tasks:
+        TasksQueue<(BlockNumber, ReaderClientResult<Option<DownloadedStateUpdate>>)>,
     // Contains NumberOfClasses so we don't need to calculate it from the StateUpdate.
-    downloaded_state_updates: VecDeque<(BlockNumber, NumberOfClasses, StateUpdate)>,
+    downloaded_state_updates: VecDeque<(BlockNumber, NumberOfClasses, DownloadedStateUpdate)>,
     classes_to_download: VecDeque<ClassHash>,
     download_class_tasks: TasksQueue<CentralResult<Option<ApiContractClass>>>,
     downloaded_classes: VecDeque<ApiContractClass>,
@@ -43,7 +102,7 @@ pub(crate) struct StateUpdateStream<TStarknetClient: StarknetReader + Send + 'st
 impl<TStarknetClient: StarknetReader + Send + Sync + 'static> Stream
     for StateUpdateStream<TStarknetClient>
 {
-    type Item = CentralResult<CentralStateUpdate>;
+    type Item = CentralResult<(Option<Block>, CentralStateUpdate)>;
 
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
@@ -86,33 +145,45 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
         up_to_block_number: BlockNumber,
         starknet_client: Arc<TStarknetClient>,
         storage_reader: StorageReader,
+        config: StateUpdateStreamConfig,
+        with_blocks: bool,
     ) -> Self {
+        let classes_initial_capacity =
+            config.max_state_updates_to_store_in_memory * CLASSES_PER_STATE_UPDATE_CAPACITY;
         StateUpdateStream {
             initial_block_number,
             up_to_block_number,
             starknet_client,
             storage_reader,
+            config,
+            with_blocks,
             download_state_update_tasks: futures::stream::FuturesOrdered::new(),
-            downloaded_state_updates: VecDeque::with_capacity(MAX_STATE_UPDATES_TO_STORE_IN_MEMORY),
-            classes_to_download: VecDeque::with_capacity(CLASSES_INITIAL_CAPACITY),
+            downloaded_state_updates: VecDeque::with_capacity(
+                config.max_state_updates_to_store_in_memory,
+            ),
+            classes_to_download: VecDeque::with_capacity(classes_initial_capacity),
             download_class_tasks: futures::stream::FuturesOrdered::new(),
-            downloaded_classes: VecDeque::with_capacity(CLASSES_INITIAL_CAPACITY),
+            downloaded_classes: VecDeque::with_capacity(classes_initial_capacity),
         }
     }
 
-    // Returns data needed for the next block CentralStateUpdate, or None if it is not yet ready.
-    fn next_output(&mut self) -> Option<CentralResult<CentralStateUpdate>> {
+    // Returns data needed for the next block CentralStateUpdate, with the block if it was
+    // downloaded, or None if it is not yet ready.
+    fn next_output(&mut self) -> Option<CentralResult<(Option<Block>, CentralStateUpdate)>> {
         let (_, n_classes, _) = self.downloaded_state_updates.front()?;
         if self.downloaded_classes.len() < *n_classes {
             return None;
         }
-        let (block_number, n_classes, state_update) =
+        let (block_number, n_classes, (block, state_update)) =
             self.downloaded_state_updates.pop_front().expect("Should have a value");
         let class_hashes = state_update.state_diff.class_hashes();
         let classes = self.downloaded_classes.drain(..n_classes);
         let classes: IndexMap<ClassHash, ApiContractClass> =
             class_hashes.into_iter().zip(classes).collect();
-        Some(client_to_central_state_update(block_number, Ok((state_update, classes))))
+        Some(
+            client_to_central_state_update(block_number, Ok((state_update, classes)))
+                .map(|state_update| (block, state_update)),
+        )
     }
 
     // Advances scheduling logic. Propagates errors to be returned from the stream.
@@ -135,7 +206,9 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
 
     // Adds more class downloading tasks.
     fn schedule_class_downloads(self: &mut std::pin::Pin<&mut Self>, should_poll_again: &mut bool) {
-        while self.download_class_tasks.len() < MAX_CLASSES_TO_DOWNLOAD {
+        while self.download_class_tasks.len() < self.config.max_classes_to_download
+            && self.can_hold_another_class()
+        {
             let Some(class_hash) = self.classes_to_download.pop_front() else {
                 break;
             };
@@ -150,6 +223,17 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
         }
     }
 
+    // Whether another class may be downloaded without exceeding the maximal number of classes in
+    // memory. The classes are downloaded in order, and the classes of the next state update to
+    // return are always allowed so that the stream can't get stuck on a state update with more
+    // classes than the maximum.
+    fn can_hold_another_class(&self) -> bool {
+        let n_classes_in_memory = self.download_class_tasks.len() + self.downloaded_classes.len();
+        let n_classes_of_next_state_update =
+            self.downloaded_state_updates.front().map_or(0, |(_, n_classes, _)| *n_classes);
+        n_classes_in_memory < self.config.max_classes_in_memory.max(n_classes_of_next_state_update)
+    }
+
     // Checks for finished class downloading tasks and adds the result to `downloaded_classes`.
     fn handle_downloaded_classes(
         self: &mut std::pin::Pin<&mut Self>,
@@ -180,13 +264,31 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
         should_poll_again: &mut bool,
     ) {
         while self.initial_block_number < self.up_to_block_number
-            && self.download_state_update_tasks.len() < MAX_STATE_UPDATES_TO_DOWNLOAD
+            && self.download_state_update_tasks.len() < self.config.max_state_updates_to_download
         {
             let current_block_number = self.initial_block_number;
             let starknet_client = self.starknet_client.clone();
+            let with_blocks = self.with_blocks;
             *should_poll_again = true;
             self.download_state_update_tasks.push_back(Box::pin(async move {
-                (current_block_number, starknet_client.state_update(current_block_number).await)
+                let maybe_state_update = if with_blocks {
+                    starknet_client.state_update_with_block(current_block_number).await.map(
+                        |maybe_state_update| {
+                            maybe_state_update.map(
+                                |StateUpdateWithBlock { block, state_update }| {
+                                    (Some(block), state_update)
+                                },
+                            )
+                        },
+                    )
+                } else {
+                    starknet_client.state_update(current_block_number).await.map(
+                        |maybe_state_update| {
+                            maybe_state_update.map(|state_update| (None, state_update))
+                        },
+                    )
+                };
+                (current_block_number, maybe_state_update)
             }));
             self.initial_block_number = self.initial_block_number.next();
         }
@@ -199,7 +301,7 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
         cx: &mut std::task::Context<'_>,
         should_poll_again: &mut bool,
     ) -> CentralResult<()> {
-        if self.downloaded_state_updates.len() >= MAX_STATE_UPDATES_TO_STORE_IN_MEMORY {
+        if self.downloaded_state_updates.len() >= self.config.max_state_updates_to_store_in_memory {
             return Ok(());
         }
 
@@ -213,11 +315,15 @@ impl<TStarknetClient: StarknetReader + Send + Sync + 'static> StateUpdateStream<
         match maybe_state_update {
             // Add to downloaded state updates. Adds the results to `downloaded_state_updates` and
             // the corresponding classes needed to   be downloaded to `classes_to_download`.
-            Ok(Some(state_update)) => {
+            Ok(Some((block, state_update))) => {
                 let hashes = state_update.state_diff.class_hashes();
                 let n_classes = hashes.len();
                 self.classes_to_download.append(&mut VecDeque::from(hashes));
-                self.downloaded_state_updates.push_back((block_number, n_classes, state_update));
+                self.downloaded_state_updates.push_back((
+                    block_number,
+                    n_classes,
+                    (block, state_update),
+                ));
                 Ok(())
             }
             // Class was not found.
@@ -233,71 +339,9 @@ fn client_to_central_state_update(
     maybe_client_state_update: CentralResult<(StateUpdate, IndexMap<ClassHash, ApiContractClass>)>,
 ) -> CentralResult<CentralStateUpdate> {
     match maybe_client_state_update {
-        Ok((state_update, mut declared_classes)) => {
-            // Destruct the state diff to avoid partial move.
-            let starknet_client::reader::StateDiff {
-                storage_diffs,
-                deployed_contracts,
-                declared_classes: declared_class_hashes,
-                old_declared_contracts: old_declared_contract_hashes,
-                nonces,
-                replaced_classes,
-            } = state_update.state_diff;
-
-            // Separate the declared classes to new classes, old classes and classes of deployed
-            // contracts (both new and old).
-            let n_declared_classes = declared_class_hashes.len();
-            let mut deprecated_classes = declared_classes.split_off(n_declared_classes);
-            let n_deprecated_declared_classes = old_declared_contract_hashes.len();
-            let deployed_contract_class_definitions =
-                deprecated_classes.split_off(n_deprecated_declared_classes);
-
-            let state_diff = StateDiff {
-                deployed_contracts: IndexMap::from_iter(
-                    deployed_contracts.iter().map(|dc| (dc.address, dc.class_hash)),
-                ),
-                storage_diffs: IndexMap::from_iter(storage_diffs.into_iter().map(
-                    |(address, entries)| {
-                        (address, entries.into_iter().map(|se| (se.key, se.value)).collect())
-                    },
-                )),
-                declared_classes: declared_classes
-                    .into_iter()
-                    .map(|(class_hash, class)| {
-                        (class_hash, class.into_cairo1().expect("Expected Cairo1 class."))
-                    })
-                    .zip(
-                        declared_class_hashes
-                            .into_iter()
-                            .map(|hash_entry| hash_entry.compiled_class_hash),
-                    )
-                    .map(|((class_hash, class), compiled_class_hash)| {
-                        (class_hash, (compiled_class_hash, class))
-                    })
-                    .collect(),
-                deprecated_declared_classes: deprecated_classes
-                    .into_iter()
-                    .map(|(class_hash, generic_class)| {
-                        (class_hash, generic_class.into_cairo0().expect("Expected Cairo0 class."))
-                    })
-                    .collect(),
-                nonces,
-                replaced_classes: replaced_classes
-                    .into_iter()
-                    .map(|replaced_class| (replaced_class.address, replaced_class.class_hash))
-                    .collect(),
-            };
-            // Filter out deployed contracts of new classes because since 0.11 new classes can not
-            // be implicitly declared by deployment.
-            let deployed_contract_class_definitions = deployed_contract_class_definitions
-                .into_iter()
-                .filter_map(|(class_hash, contract_class)| match contract_class {
-                    ApiContractClass::DeprecatedContractClass(deprecated_contract_class) => {
-                        Some((class_hash, deprecated_contract_class))
-                    }
-                    ApiContractClass::ContractClass(_) => None,
-                })
-                .collect();
+        Ok((state_update, declared_classes)) => {
+            let (state_diff, deployed_contract_class_definitions) =
+                client_to_central_state_diff(state_update.state_diff, declared_classes);
             let block_hash = state_update.block_hash;
             debug!(
                 "Received new state update of block {current_block_number} with hash {block_hash}."
@@ -315,11 +359,80 @@ fn client_to_central_state_update(
     }
 }
 
+// Converts the client state diff to a starknet_api state diff. `declared_classes` holds the classes
+// of the state diff in the order of [`starknet_client::reader::StateDiff::class_hashes`]. Returns
+// also the class definitions of deployed contracts with classes that were not declared in this
+// state diff.
+pub(crate) fn client_to_central_state_diff(
+    client_state_diff: starknet_client::reader::StateDiff,
+    mut declared_classes: IndexMap<ClassHash, ApiContractClass>,
+) -> (StateDiff, IndexMap<ClassHash, DeprecatedContractClass>) {
+    // Destruct the state diff to avoid partial move.
+    let starknet_client::reader::StateDiff {
+        storage_diffs,
+        deployed_contracts,
+        declared_classes: declared_class_hashes,
+        old_declared_contracts: old_declared_contract_hashes,
+        nonces,
+        replaced_classes,
+    } = client_state_diff;
+
+    // Separate the declared classes to new classes, old classes and classes of deployed
+    // contracts (both new and old).
+    let n_declared_classes = declared_class_hashes.len();
+    let mut deprecated_classes = declared_classes.split_off(n_declared_classes);
+    let n_deprecated_declared_classes = old_declared_contract_hashes.len();
+    let deployed_contract_class_definitions =
+        deprecated_classes.split_off(n_deprecated_declared_classes);
+
+    let state_diff = StateDiff {
+        deployed_contracts: IndexMap::from_iter(
+            deployed_contracts.iter().map(|dc| (dc.address, dc.class_hash)),
+        ),
+        storage_diffs: IndexMap::from_iter(storage_diffs.into_iter().map(|(address, entries)| {
+            (address, entries.into_iter().map(|se| (se.key, se.value)).collect())
+        })),
+        declared_classes: declared_classes
+            .into_iter()
+            .map(|(class_hash, class)| {
+                (class_hash, class.into_cairo1().expect("Expected Cairo1 class."))
+            })
+            .zip(declared_class_hashes.into_iter().map(|hash_entry| hash_entry.compiled_class_hash))
+            .map(|((class_hash, class), compiled_class_hash)| {
+                (class_hash, (compiled_class_hash, class))
+            })
+            .collect(),
+        deprecated_declared_classes: deprecated_classes
+            .into_iter()
+            .map(|(class_hash, generic_class)| {
+                (class_hash, generic_class.into_cairo0().expect("Expected Cairo0 class."))
+            })
+            .collect(),
+        nonces,
+        replaced_classes: replaced_classes
+            .into_iter()
+            .map(|replaced_class| (replaced_class.address, replaced_class.class_hash))
+            .collect(),
+    };
+    // Filter out deployed contracts of new classes because since 0.11 new classes can not
+    // be implicitly declared by deployment.
+    let deployed_contract_class_definitions = deployed_contract_class_definitions
+        .into_iter()
+        .filter_map(|(class_hash, contract_class)| match contract_class {
+            ApiContractClass::DeprecatedContractClass(deprecated_contract_class) => {
+                Some((class_hash, deprecated_contract_class))
+            }
+            ApiContractClass::ContractClass(_) => None,
+        })
+        .collect();
+    (state_diff, deployed_contract_class_definitions)
+}
+
 // Given a class hash, returns the corresponding class definition.
 // First tries to retrieve the class from the storage.
 // If not found in the storage, the class is downloaded.
 #[instrument(skip(starknet_client, storage_reader), level = "debug", err)]
-async fn download_class_if_necessary<TStarknetClient: StarknetReader>(
+pub(super) async fn download_class_if_necessary<TStarknetClient: StarknetReader>(
     class_hash: ClassHash,
     starknet_client: Arc<TStarknetClient>,
     storage_reader: StorageReader,
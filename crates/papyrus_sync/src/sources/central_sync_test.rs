@@ -6,29 +6,39 @@ use async_stream::stream;
 use async_trait::async_trait;
 use futures::StreamExt;
 use indexmap::IndexMap;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use papyrus_common::pending_data::{PendingBlock, PendingData};
 use papyrus_common::SyncingState;
-use papyrus_storage::header::{HeaderStorageReader, StarknetVersion};
-use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::body::BodyStorageWriter;
+use papyrus_storage::db::RO;
+use papyrus_storage::header::{HeaderStorageReader, HeaderStorageWriter, StarknetVersion};
+use papyrus_storage::state::{StateStorageReader, StateStorageWriter};
 use papyrus_storage::test_utils::get_test_storage;
-use papyrus_storage::{StorageError, StorageReader, StorageWriter};
-use starknet_api::block::{Block, BlockBody, BlockHash, BlockHeader, BlockNumber};
+use papyrus_storage::{StorageError, StorageReader, StorageTxn, StorageWriter};
+use starknet_api::block::{Block, BlockBody, BlockHash, BlockHeader, BlockNumber, BlockTimestamp};
+use starknet_api::core::{ChainId, ClassHash, CompiledClassHash};
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
-use starknet_api::state::StateDiff;
-use tokio::sync::{Mutex, RwLock};
+use starknet_api::state::{ContractClass, StateDiff};
+use starknet_api::transaction::{
+    L1HandlerTransaction, L1HandlerTransactionOutput, Transaction, TransactionExecutionStatus,
+    TransactionHash, TransactionOutput,
+};
+use tokio::sync::{watch, Mutex, RwLock};
 use tracing::{debug, error};
 
-use super::central::BlocksStream;
+use super::central::{BlocksStream, BlocksWithStateUpdatesStream};
 use crate::sources::central::{CompiledClassesStream, MockCentralSourceTrait, StateUpdatesStream};
 use crate::{
-    CentralError, CentralSourceTrait, GenericStateSync, StateSyncError, StateSyncResult, SyncConfig,
+    sync_metrics, CentralError, CentralSourceTrait, Checkpoints, GenericStateSync, StateSyncError,
+    StateSyncResult, SyncConfig, SyncHook, VerificationMode,
 };
 
 const SYNC_SLEEP_DURATION: Duration = Duration::from_millis(100); // 100ms
 const DURATION_BEFORE_CHECKING_STORAGE: Duration = SYNC_SLEEP_DURATION.saturating_mul(2); // 200ms twice the sleep duration of the sync loop.
 const MAX_CHECK_STORAGE_ITERATIONS: u8 = 3;
 const STREAM_SIZE: u32 = 1000;
-const STARKNET_VERSION: &str = "starknet_version";
+const STARKNET_VERSION: &str = "0.12.0";
 
 enum CheckStoragePredicateResult {
     InProgress,
@@ -67,23 +77,65 @@ async fn check_storage(
     false
 }
 
+fn get_test_sync_config() -> SyncConfig {
+    SyncConfig {
+        block_propagation_sleep_duration: SYNC_SLEEP_DURATION,
+        recoverable_error_sleep_duration: SYNC_SLEEP_DURATION,
+        blocks_max_stream_size: STREAM_SIZE,
+        state_updates_max_stream_size: STREAM_SIZE,
+        download_blocks_with_state_updates: false,
+        pending_sleep_duration: SYNC_SLEEP_DURATION,
+        halt_block_number: None,
+        initial_block_number: None,
+        max_revert_depth: 100,
+        allow_deep_revert: false,
+        checkpoints_file: None,
+        chain_id: ChainId("SN_MAIN".to_string()),
+        block_hash_verification: VerificationMode::Off,
+        transaction_hash_verification: VerificationMode::Off,
+        class_hash_verification: VerificationMode::Off,
+    }
+}
+
 // Runs sync loop with a mocked central - infinite loop unless panicking.
 async fn run_sync(
     reader: StorageReader,
     writer: StorageWriter,
     central: impl CentralSourceTrait + Send + Sync + 'static,
+) -> StateSyncResult {
+    run_sync_with_config(reader, writer, central, get_test_sync_config()).await
+}
+
+// Runs sync loop with a mocked central and the given config - infinite loop unless panicking or
+// reaching the halt block.
+async fn run_sync_with_config(
+    reader: StorageReader,
+    writer: StorageWriter,
+    central: impl CentralSourceTrait + Send + Sync + 'static,
+    config: SyncConfig,
+) -> StateSyncResult {
+    run_sync_with_checkpoints(reader, writer, central, config, Checkpoints::default()).await
+}
+
+// Runs sync loop with a mocked central, the given config and checkpoints - infinite loop unless
+// panicking or reaching the halt block.
+async fn run_sync_with_checkpoints(
+    reader: StorageReader,
+    writer: StorageWriter,
+    central: impl CentralSourceTrait + Send + Sync + 'static,
+    config: SyncConfig,
+    checkpoints: Checkpoints,
 ) -> StateSyncResult {
     let mut state_sync = GenericStateSync {
-        config: SyncConfig {
-            block_propagation_sleep_duration: SYNC_SLEEP_DURATION,
-            recoverable_error_sleep_duration: SYNC_SLEEP_DURATION,
-            blocks_max_stream_size: STREAM_SIZE,
-            state_updates_max_stream_size: STREAM_SIZE,
-        },
+        config,
         shared_syncing_state: Arc::new(RwLock::new(SyncingState::default())),
+        pending_data: Arc::new(RwLock::new(PendingData::default())),
         central_source: Arc::new(central),
         reader,
         writer,
+        hooks: Vec::new(),
+        shutdown_receiver: None,
+        checkpoints,
     };
 
     state_sync.run().await?;
@@ -97,6 +149,7 @@ async fn sync_empty_chain() {
     // Mock central without any block.
     let mut mock = MockCentralSourceTrait::new();
     mock.expect_get_block_marker().returning(|| Ok(BlockNumber(0)));
+    mock.expect_get_pending_data().returning(|| Ok(None));
     let ((reader, writer), _temp_dir) = get_test_storage();
     let sync_future = run_sync(reader.clone(), writer, mock);
 
@@ -161,6 +214,7 @@ async fn sync_happy_flow() {
         state_stream
     });
     mock.expect_get_block_hash().returning(|bn| Ok(Some(create_block_hash(bn, false))));
+    mock.expect_get_pending_data().returning(|| Ok(None));
     let ((reader, writer), _temp_dir) = get_test_storage();
     let sync_future = run_sync(reader.clone(), writer, mock);
 
@@ -194,6 +248,826 @@ async fn sync_happy_flow() {
     }
 }
 
+#[tokio::test]
+async fn sync_pending_data() {
+    const N_BLOCKS: u64 = 2;
+    let _ = simple_logger::init_with_env();
+
+    // Mock a chain of N_BLOCKS with a pending block on top of it.
+    let mut mock = MockCentralSourceTrait::new();
+    mock.expect_get_block_marker().returning(|| Ok(BlockNumber(N_BLOCKS)));
+    mock.expect_stream_new_blocks().returning(move |initial, up_to| {
+        let blocks_stream: BlocksStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    parent_hash: create_block_hash(block_number.prev().unwrap_or_default(), false),
+                    ..BlockHeader::default()
+                };
+                yield Ok((block_number, Block { header, body: BlockBody::default() }, StarknetVersion(STARKNET_VERSION.to_string())));
+            }
+        }
+        .boxed();
+        blocks_stream
+    });
+    mock.expect_stream_state_updates().returning(move |initial, up_to| {
+        let state_stream: StateUpdatesStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                yield Ok((
+                    block_number,
+                    create_block_hash(block_number, false),
+                    StateDiff::default(),
+                    IndexMap::new(),
+                ));
+            }
+        }
+        .boxed();
+        state_stream
+    });
+    mock.expect_get_block_hash().returning(|bn| Ok(Some(create_block_hash(bn, false))));
+    let expected_pending_data = PendingData {
+        block: PendingBlock {
+            parent_block_hash: create_block_hash(BlockNumber(N_BLOCKS - 1), false),
+            timestamp: BlockTimestamp(1),
+            ..PendingBlock::default()
+        },
+        state_diff: StateDiff::default(),
+    };
+    let pending_data_clone = expected_pending_data.clone();
+    mock.expect_get_pending_data().returning(move || Ok(Some(pending_data_clone.clone())));
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let pending_data = Arc::new(RwLock::new(PendingData::default()));
+    let mut state_sync = GenericStateSync {
+        config: get_test_sync_config(),
+        shared_syncing_state: Arc::new(RwLock::new(SyncingState::default())),
+        pending_data: pending_data.clone(),
+        central_source: Arc::new(mock),
+        reader,
+        writer,
+        hooks: Vec::new(),
+        shutdown_receiver: None,
+        checkpoints: Checkpoints::default(),
+    };
+
+    let check_pending_data = async {
+        for _ in 0..MAX_CHECK_STORAGE_ITERATIONS {
+            tokio::time::sleep(DURATION_BEFORE_CHECKING_STORAGE).await;
+            if *pending_data.read().await == expected_pending_data {
+                return true;
+            }
+        }
+        false
+    };
+
+    tokio::select! {
+        sync_result = state_sync.run() => panic!("Sync stopped with {sync_result:?}."),
+        pending_data_result = check_pending_data => assert!(pending_data_result),
+    }
+}
+
+#[tokio::test]
+async fn pending_data_errors_dont_stop_sync() {
+    const N_BLOCKS: u64 = 2;
+    let _ = simple_logger::init_with_env();
+
+    // The first request for the pending data fails, and the following ones succeed.
+    let mut mock = get_mock_chain(N_BLOCKS);
+    let expected_pending_data = PendingData {
+        block: PendingBlock {
+            parent_block_hash: create_block_hash(BlockNumber(N_BLOCKS - 1), false),
+            timestamp: BlockTimestamp(1),
+            ..PendingBlock::default()
+        },
+        state_diff: StateDiff::default(),
+    };
+    let pending_data_clone = expected_pending_data.clone();
+    let failed_once = std::sync::Mutex::new(false);
+    mock.expect_get_pending_data().returning(move || {
+        let mut failed_once = failed_once.lock().unwrap();
+        if !*failed_once {
+            *failed_once = true;
+            return Err(CentralError::BlockNotFound { block_number: BlockNumber(N_BLOCKS) });
+        }
+        Ok(Some(pending_data_clone.clone()))
+    });
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let pending_data = Arc::new(RwLock::new(PendingData::default()));
+    let mut state_sync = GenericStateSync {
+        config: get_test_sync_config(),
+        shared_syncing_state: Arc::new(RwLock::new(SyncingState::default())),
+        pending_data: pending_data.clone(),
+        central_source: Arc::new(mock),
+        reader,
+        writer,
+        hooks: Vec::new(),
+        shutdown_receiver: None,
+        checkpoints: Checkpoints::default(),
+    };
+
+    let check_pending_data = async {
+        for _ in 0..MAX_CHECK_STORAGE_ITERATIONS {
+            tokio::time::sleep(DURATION_BEFORE_CHECKING_STORAGE).await;
+            if *pending_data.read().await == expected_pending_data {
+                return true;
+            }
+        }
+        false
+    };
+
+    // A single run of the sync, which would stop with the error if it wasn't skipped.
+    tokio::select! {
+        sync_result = state_sync.sync_while_ok() => panic!("Sync stopped with {sync_result:?}."),
+        pending_data_result = check_pending_data => assert!(pending_data_result),
+    }
+}
+
+// Mocks a chain of n_blocks blocks without declared classes.
+fn get_mock_chain(n_blocks: u64) -> MockCentralSourceTrait {
+    add_mock_chain(MockCentralSourceTrait::new(), n_blocks)
+}
+
+// Adds the expectations of a chain of n_blocks blocks to the mock. Expectations that were set
+// before take precedence.
+fn add_mock_chain(mut mock: MockCentralSourceTrait, n_blocks: u64) -> MockCentralSourceTrait {
+    mock.expect_get_block_marker().returning(move || Ok(BlockNumber(n_blocks)));
+    mock.expect_stream_new_blocks().returning(move |initial, up_to| {
+        let blocks_stream: BlocksStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    parent_hash: create_block_hash(block_number.prev().unwrap_or_default(), false),
+                    ..BlockHeader::default()
+                };
+                yield Ok((block_number, Block { header, body: BlockBody::default() }, StarknetVersion(STARKNET_VERSION.to_string())));
+            }
+        }
+        .boxed();
+        blocks_stream
+    });
+    mock.expect_stream_state_updates().returning(move |initial, up_to| {
+        let state_stream: StateUpdatesStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                yield Ok((
+                    block_number,
+                    create_block_hash(block_number, false),
+                    StateDiff::default(),
+                    IndexMap::new(),
+                ));
+            }
+        }
+        .boxed();
+        state_stream
+    });
+    mock.expect_stream_blocks_with_state_updates().returning(move |initial, up_to| {
+        let stream: BlocksWithStateUpdatesStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    parent_hash: create_block_hash(block_number.prev().unwrap_or_default(), false),
+                    ..BlockHeader::default()
+                };
+                yield Ok((
+                    Block { header, body: BlockBody::default() },
+                    StarknetVersion(STARKNET_VERSION.to_string()),
+                    (block_number, create_block_hash(block_number, false), StateDiff::default(), IndexMap::new()),
+                ));
+            }
+        }
+        .boxed();
+        stream
+    });
+    mock.expect_get_block_hash().returning(|bn| Ok(Some(create_block_hash(bn, false))));
+    mock
+}
+
+#[tokio::test]
+async fn sync_until_halt_block() {
+    const N_BLOCKS: u64 = 10;
+    const HALT_BLOCK_NUMBER: BlockNumber = BlockNumber(4);
+    let _ = simple_logger::init_with_env();
+
+    // The pending data isn't polled when the sync has a halt block.
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config =
+        SyncConfig { halt_block_number: Some(HALT_BLOCK_NUMBER), ..get_test_sync_config() };
+    let sync_future =
+        run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS), config);
+
+    tokio::time::timeout(Duration::from_secs(5), sync_future)
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+    assert_eq!(txn.get_state_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+}
+
+// A block of an unsupported Starknet version keeps the sync at the last supported block, without
+// stopping it.
+#[tokio::test]
+async fn sync_waits_at_unsupported_starknet_version() {
+    const N_BLOCKS: u64 = 4;
+    const UNSUPPORTED_BLOCK_NUMBER: BlockNumber = BlockNumber(2);
+    let _ = simple_logger::init_with_env();
+
+    let mut mock = MockCentralSourceTrait::new();
+    mock.expect_stream_new_blocks().returning(move |initial, up_to| {
+        let blocks_stream: BlocksStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                if block_number == UNSUPPORTED_BLOCK_NUMBER {
+                    yield Err(CentralError::UnsupportedStarknetVersion {
+                        block_number,
+                        starknet_version: "0.13.0".to_owned(),
+                    });
+                    return;
+                }
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    parent_hash: create_block_hash(block_number.prev().unwrap_or_default(), false),
+                    ..BlockHeader::default()
+                };
+                yield Ok((block_number, Block { header, body: BlockBody::default() }, StarknetVersion(STARKNET_VERSION.to_string())));
+            }
+        }
+        .boxed();
+        blocks_stream
+    });
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config =
+        SyncConfig { halt_block_number: Some(BlockNumber(N_BLOCKS - 1)), ..get_test_sync_config() };
+    let sync_future =
+        run_sync_with_config(reader.clone(), writer, add_mock_chain(mock, N_BLOCKS), config);
+
+    // The sync retries the unsupported block until the node is upgraded.
+    assert!(
+        tokio::time::timeout(SYNC_SLEEP_DURATION * 10, sync_future).await.is_err(),
+        "Sync stopped at the unsupported block."
+    );
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), UNSUPPORTED_BLOCK_NUMBER);
+    assert_eq!(txn.get_state_marker().unwrap(), UNSUPPORTED_BLOCK_NUMBER);
+}
+
+#[tokio::test]
+async fn sync_blocks_with_state_updates() {
+    const N_BLOCKS: u64 = 10;
+    const HALT_BLOCK_NUMBER: BlockNumber = BlockNumber(4);
+    let _ = simple_logger::init_with_env();
+
+    // The first block is stored without its state diff, which is downloaded on its own before the
+    // rest of the blocks are downloaded with their state updates.
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let header = BlockHeader {
+        block_hash: create_block_hash(BlockNumber(0), false),
+        parent_hash: create_block_hash(BlockNumber(0), false),
+        ..BlockHeader::default()
+    };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header)
+        .unwrap()
+        .append_body(BlockNumber(0), BlockBody::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    let config = SyncConfig {
+        download_blocks_with_state_updates: true,
+        halt_block_number: Some(HALT_BLOCK_NUMBER),
+        ..get_test_sync_config()
+    };
+    let sync_future =
+        run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS), config);
+
+    tokio::time::timeout(Duration::from_secs(5), sync_future)
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+    assert_eq!(txn.get_state_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+}
+
+#[tokio::test]
+async fn sync_metrics() {
+    const N_BLOCKS: u64 = 3;
+    const HALT_BLOCK_NUMBER: BlockNumber = BlockNumber(1);
+    let _ = simple_logger::init_with_env();
+    let handle = PrometheusBuilder::new().install_recorder().unwrap();
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config =
+        SyncConfig { halt_block_number: Some(HALT_BLOCK_NUMBER), ..get_test_sync_config() };
+    let sync_future = run_sync_with_config(reader, writer, get_mock_chain(N_BLOCKS), config);
+    tokio::time::timeout(Duration::from_secs(5), sync_future)
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+
+    // The recorder is global, so other tests may update the metrics concurrently and only the
+    // existence of the metrics is checked.
+    let metrics = handle.render();
+    for marker in [
+        sync_metrics::HEADER_MARKER,
+        sync_metrics::BODY_MARKER,
+        sync_metrics::STATE_MARKER,
+        sync_metrics::COMPILED_CLASS_MARKER,
+        sync_metrics::BASE_LAYER_MARKER,
+        sync_metrics::CENTRAL_BLOCK_MARKER,
+    ] {
+        assert!(
+            test_utils::prometheus_is_contained(metrics.clone(), marker, &[]).is_some(),
+            "Missing metric {marker}."
+        );
+    }
+    for latency in [sync_metrics::DOWNLOAD_LATENCY, sync_metrics::COMMIT_LATENCY] {
+        for event in [sync_metrics::BLOCK_EVENT, sync_metrics::STATE_DIFF_EVENT] {
+            assert!(
+                test_utils::prometheus_is_contained(
+                    metrics.clone(),
+                    latency,
+                    &[(sync_metrics::EVENT_LABEL, event)]
+                )
+                .is_some(),
+                "Missing metric {latency} of {event} events."
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn sync_from_initial_block() {
+    const N_BLOCKS: u64 = 10;
+    const INITIAL_BLOCK_NUMBER: BlockNumber = BlockNumber(3);
+    const HALT_BLOCK_NUMBER: BlockNumber = BlockNumber(5);
+    let _ = simple_logger::init_with_env();
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config = SyncConfig {
+        halt_block_number: Some(HALT_BLOCK_NUMBER),
+        initial_block_number: Some(INITIAL_BLOCK_NUMBER),
+        ..get_test_sync_config()
+    };
+    let sync_future =
+        run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS), config);
+
+    tokio::time::timeout(Duration::from_secs(5), sync_future)
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+    assert_eq!(txn.get_state_marker().unwrap(), HALT_BLOCK_NUMBER.next());
+    assert!(txn.get_block_header(INITIAL_BLOCK_NUMBER.prev().unwrap()).unwrap().is_none());
+    assert_eq!(
+        txn.get_block_header(INITIAL_BLOCK_NUMBER).unwrap().unwrap().block_hash,
+        create_block_hash(INITIAL_BLOCK_NUMBER, false)
+    );
+}
+
+#[tokio::test]
+async fn sync_stops_on_shutdown() {
+    const N_BLOCKS: u64 = 5;
+    let _ = simple_logger::init_with_env();
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let mut central = get_mock_chain(N_BLOCKS);
+    central.expect_get_pending_data().returning(|| Ok(None));
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+    let mut state_sync = GenericStateSync {
+        config: get_test_sync_config(),
+        shared_syncing_state: Arc::new(RwLock::new(SyncingState::default())),
+        pending_data: Arc::new(RwLock::new(PendingData::default())),
+        central_source: Arc::new(central),
+        reader: reader.clone(),
+        writer,
+        hooks: Vec::new(),
+        shutdown_receiver: Some(shutdown_receiver),
+        checkpoints: Checkpoints::default(),
+    };
+
+    let request_shutdown_when_synced = async {
+        let synced = check_storage(reader, Duration::from_secs(5), |reader| {
+            if reader.begin_ro_txn().unwrap().get_state_marker().unwrap() == BlockNumber(N_BLOCKS) {
+                return CheckStoragePredicateResult::Passed;
+            }
+            CheckStoragePredicateResult::InProgress
+        })
+        .await;
+        assert!(synced);
+        shutdown_sender.send(true).unwrap();
+    };
+
+    let (sync_result, ()) = tokio::time::timeout(Duration::from_secs(5), async {
+        tokio::join!(state_sync.run(), request_shutdown_when_synced)
+    })
+    .await
+    .expect("Sync didn't stop after the shutdown request.");
+    sync_result.unwrap();
+}
+
+// Stores a chain of n_blocks blocks that was reverted in central.
+fn store_reverted_chain(writer: &mut StorageWriter, n_blocks: u64) {
+    for block_number in BlockNumber(0).iter_up_to(BlockNumber(n_blocks)) {
+        let header = BlockHeader {
+            block_number,
+            block_hash: create_block_hash(block_number, true),
+            parent_hash: create_block_hash(block_number.prev().unwrap_or_default(), true),
+            ..BlockHeader::default()
+        };
+        writer
+            .begin_rw_txn()
+            .unwrap()
+            .append_header(block_number, &header)
+            .unwrap()
+            .append_body(block_number, BlockBody::default())
+            .unwrap()
+            .append_state_diff(block_number, StateDiff::default(), IndexMap::new())
+            .unwrap()
+            .commit()
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn deep_revert_requires_confirmation() {
+    const N_BLOCKS: u64 = 3;
+    let _ = simple_logger::init_with_env();
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    // The halt block is after the stored chain, so the sync checks it for reverts.
+    store_reverted_chain(&mut writer, N_BLOCKS);
+
+    // The revert is deeper than the maximum, the sync stops without reverting.
+    let config = SyncConfig {
+        halt_block_number: Some(BlockNumber(N_BLOCKS)),
+        max_revert_depth: N_BLOCKS - 1,
+        ..get_test_sync_config()
+    };
+    let sync_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS + 1), config),
+    )
+    .await
+    .expect("Sync didn't stop on the deep revert.");
+    assert_matches!(
+        sync_result,
+        Err(StateSyncError::RevertDepthExceeded { block_number, max_revert_depth })
+        if block_number == BlockNumber(0) && max_revert_depth == N_BLOCKS - 1
+    );
+    let header =
+        reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(N_BLOCKS - 1)).unwrap();
+    assert_eq!(header.unwrap().block_hash, create_block_hash(BlockNumber(N_BLOCKS - 1), true));
+}
+
+#[tokio::test]
+async fn confirmed_deep_revert() {
+    const N_BLOCKS: u64 = 3;
+    let _ = simple_logger::init_with_env();
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    store_reverted_chain(&mut writer, N_BLOCKS);
+
+    let config = SyncConfig {
+        halt_block_number: Some(BlockNumber(N_BLOCKS)),
+        max_revert_depth: N_BLOCKS - 1,
+        allow_deep_revert: true,
+        ..get_test_sync_config()
+    };
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS + 1), config),
+    )
+    .await
+    .expect("Sync didn't stop at the halt block.")
+    .unwrap();
+    let header =
+        reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(N_BLOCKS - 1)).unwrap();
+    assert_eq!(header.unwrap().block_hash, create_block_hash(BlockNumber(N_BLOCKS - 1), false));
+}
+
+#[tokio::test]
+async fn sync_stops_on_checkpoint_mismatch() {
+    const N_BLOCKS: u64 = 5;
+    const CHECKPOINT: BlockNumber = BlockNumber(2);
+    let _ = simple_logger::init_with_env();
+    let ((reader, writer), _temp_dir) = get_test_storage();
+
+    let checkpoints = Checkpoints::new([(CHECKPOINT, create_block_hash(CHECKPOINT, true))].into());
+    let config =
+        SyncConfig { halt_block_number: Some(BlockNumber(N_BLOCKS - 1)), ..get_test_sync_config() };
+    let sync_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_checkpoints(
+            reader.clone(),
+            writer,
+            get_mock_chain(N_BLOCKS),
+            config,
+            checkpoints,
+        ),
+    )
+    .await
+    .expect("Sync didn't stop on the checkpoint mismatch.");
+    assert_matches!(
+        sync_result,
+        Err(StateSyncError::CheckpointMismatch { block_number, .. }) if block_number == CHECKPOINT
+    );
+    assert_eq!(reader.begin_ro_txn().unwrap().get_header_marker().unwrap(), CHECKPOINT);
+}
+
+#[tokio::test]
+async fn sync_stops_on_block_hash_mismatch() {
+    const N_BLOCKS: u64 = 3;
+    let _ = simple_logger::init_with_env();
+
+    // The blocks of the mock chain don't have real hashes, so they're stored only when the block
+    // hashes aren't verified.
+    for (block_hash_verification, expected_header_marker) in [
+        (VerificationMode::Warn, BlockNumber(N_BLOCKS)),
+        (VerificationMode::Verify, BlockNumber(0)),
+    ] {
+        let ((reader, writer), _temp_dir) = get_test_storage();
+        let config = SyncConfig {
+            halt_block_number: Some(BlockNumber(N_BLOCKS - 1)),
+            block_hash_verification,
+            ..get_test_sync_config()
+        };
+        let sync_result = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_sync_with_config(reader.clone(), writer, get_mock_chain(N_BLOCKS), config),
+        )
+        .await
+        .expect("Sync didn't stop.");
+        if block_hash_verification == VerificationMode::Verify {
+            assert_matches!(sync_result, Err(StateSyncError::BlockHashError(_)));
+        } else {
+            sync_result.unwrap();
+        }
+        assert_eq!(
+            reader.begin_ro_txn().unwrap().get_header_marker().unwrap(),
+            expected_header_marker
+        );
+    }
+}
+
+#[tokio::test]
+async fn sync_stops_on_transaction_hash_mismatch() {
+    let _ = simple_logger::init_with_env();
+
+    // A single block with a transaction whose hash isn't its real hash.
+    let mut mock = MockCentralSourceTrait::new();
+    mock.expect_get_block_marker().returning(|| Ok(BlockNumber(1)));
+    mock.expect_stream_new_blocks().returning(|initial, up_to| {
+        let blocks_stream: BlocksStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    ..BlockHeader::default()
+                };
+                let body = BlockBody {
+                    transactions: vec![Transaction::L1Handler(L1HandlerTransaction::default())],
+                    transaction_outputs: vec![TransactionOutput::L1Handler(
+                        L1HandlerTransactionOutput::default(),
+                    )],
+                    transaction_hashes: vec![TransactionHash(stark_felt!("0x1"))],
+                    transaction_execution_statuses: vec![TransactionExecutionStatus::default()],
+                };
+                yield Ok((block_number, Block { header, body }, StarknetVersion(STARKNET_VERSION.to_string())));
+            }
+        }
+        .boxed();
+        blocks_stream
+    });
+    mock.expect_stream_state_updates().returning(|_, _| futures::stream::pending().boxed());
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config = SyncConfig {
+        transaction_hash_verification: VerificationMode::Verify,
+        ..get_test_sync_config()
+    };
+    let sync_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_config(reader.clone(), writer, mock, config),
+    )
+    .await
+    .expect("Sync didn't stop on the transaction hash mismatch.");
+    assert_matches!(sync_result, Err(StateSyncError::TransactionHashError(_)));
+    assert_eq!(reader.begin_ro_txn().unwrap().get_header_marker().unwrap(), BlockNumber(0));
+}
+
+#[tokio::test]
+async fn sync_stops_on_class_hash_mismatch() {
+    let _ = simple_logger::init_with_env();
+
+    // A single block whose state diff declares a class under a hash that isn't its real hash.
+    let mut mock = MockCentralSourceTrait::new();
+    mock.expect_get_block_marker().returning(|| Ok(BlockNumber(1)));
+    mock.expect_stream_new_blocks().returning(|initial, up_to| {
+        let blocks_stream: BlocksStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let header = BlockHeader {
+                    block_number,
+                    block_hash: create_block_hash(block_number, false),
+                    ..BlockHeader::default()
+                };
+                yield Ok((block_number, Block { header, body: BlockBody::default() }, StarknetVersion(STARKNET_VERSION.to_string())));
+            }
+        }
+        .boxed();
+        blocks_stream
+    });
+    mock.expect_stream_state_updates().returning(|initial, up_to| {
+        let state_stream: StateUpdatesStream<'_> = stream! {
+            for block_number in initial.iter_up_to(up_to) {
+                let state_diff = StateDiff {
+                    declared_classes: IndexMap::from([(
+                        ClassHash(stark_felt!("0x1")),
+                        (CompiledClassHash::default(), ContractClass::default()),
+                    )]),
+                    ..StateDiff::default()
+                };
+                yield Ok((block_number, create_block_hash(block_number, false), state_diff, IndexMap::new()));
+            }
+        }
+        .boxed();
+        state_stream
+    });
+
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let config =
+        SyncConfig { class_hash_verification: VerificationMode::Verify, ..get_test_sync_config() };
+    let sync_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_config(reader.clone(), writer, mock, config),
+    )
+    .await
+    .expect("Sync didn't stop on the class hash mismatch.");
+    assert_matches!(sync_result, Err(StateSyncError::ClassHashError(_)));
+    assert_eq!(reader.begin_ro_txn().unwrap().get_state_marker().unwrap(), BlockNumber(0));
+}
+
+#[tokio::test]
+async fn revert_below_checkpoint_is_refused() {
+    const N_BLOCKS: u64 = 3;
+    const CHECKPOINT: BlockNumber = BlockNumber(1);
+    let _ = simple_logger::init_with_env();
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    store_reverted_chain(&mut writer, N_BLOCKS);
+
+    // The stored chain matches the checkpoint, so central can't revert it even when deep reverts
+    // are allowed.
+    let checkpoints = Checkpoints::new([(CHECKPOINT, create_block_hash(CHECKPOINT, true))].into());
+    let config = SyncConfig {
+        halt_block_number: Some(BlockNumber(N_BLOCKS)),
+        allow_deep_revert: true,
+        ..get_test_sync_config()
+    };
+    let sync_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        run_sync_with_checkpoints(
+            reader.clone(),
+            writer,
+            get_mock_chain(N_BLOCKS + 1),
+            config,
+            checkpoints,
+        ),
+    )
+    .await
+    .expect("Sync didn't stop on the revert below the checkpoint.");
+    assert_matches!(
+        sync_result,
+        Err(StateSyncError::RevertBelowCheckpoint { block_number, checkpoint })
+        if block_number == CHECKPOINT && checkpoint == CHECKPOINT
+    );
+    let header =
+        reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(N_BLOCKS - 1)).unwrap();
+    assert_eq!(header.unwrap().block_hash, create_block_hash(BlockNumber(N_BLOCKS - 1), true));
+}
+
+#[derive(Debug, PartialEq)]
+enum HookEvent {
+    Block(BlockNumber),
+    StateDiff(BlockNumber, BlockHash),
+    Revert(BlockNumber, BlockHash),
+}
+
+#[derive(Default)]
+struct RecordingHook {
+    events: std::sync::Mutex<Vec<HookEvent>>,
+}
+
+impl SyncHook for RecordingHook {
+    fn on_block(&self, txn: &StorageTxn<'_, RO>, block_number: BlockNumber, block: &Block) {
+        // The block is already committed.
+        assert_eq!(
+            txn.get_block_header(block_number).unwrap().unwrap().block_hash,
+            block.header.block_hash
+        );
+        self.events.lock().unwrap().push(HookEvent::Block(block_number));
+    }
+
+    fn on_state_diff(
+        &self,
+        txn: &StorageTxn<'_, RO>,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        _state_diff: &StateDiff,
+    ) {
+        assert_eq!(txn.get_state_marker().unwrap(), block_number.next());
+        self.events.lock().unwrap().push(HookEvent::StateDiff(block_number, block_hash));
+    }
+
+    fn on_revert(
+        &self,
+        txn: &StorageTxn<'_, RO>,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) {
+        assert!(txn.get_block_header(block_number).unwrap().is_none());
+        self.events.lock().unwrap().push(HookEvent::Revert(block_number, block_hash));
+    }
+}
+
+#[tokio::test]
+async fn sync_calls_hooks() {
+    const N_BLOCKS: u64 = 3;
+    let _ = simple_logger::init_with_env();
+
+    // The stored chain ends before the halt block, so it's reverted before the sync halts.
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    store_reverted_chain(&mut writer, N_BLOCKS - 1);
+
+    let hook = Arc::new(RecordingHook::default());
+    let mut state_sync = GenericStateSync::new(
+        SyncConfig { halt_block_number: Some(BlockNumber(N_BLOCKS - 1)), ..get_test_sync_config() },
+        Arc::new(RwLock::new(SyncingState::default())),
+        Arc::new(RwLock::new(PendingData::default())),
+        get_mock_chain(N_BLOCKS),
+        reader,
+        writer,
+    );
+    state_sync.register_hook(hook.clone());
+    tokio::time::timeout(Duration::from_secs(5), state_sync.run())
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+
+    let events = hook.events.lock().unwrap();
+    assert_eq!(
+        events[..2],
+        [
+            HookEvent::Revert(BlockNumber(1), create_block_hash(BlockNumber(1), true)),
+            HookEvent::Revert(BlockNumber(0), create_block_hash(BlockNumber(0), true)),
+        ]
+    );
+    // The block and state diff streams run concurrently, so only their relative order is known.
+    let synced_events: Vec<_> = events[2..].iter().collect();
+    assert_eq!(synced_events.len(), 2 * N_BLOCKS as usize);
+    for block_number in BlockNumber(0).iter_up_to(BlockNumber(N_BLOCKS)) {
+        let block_position =
+            synced_events.iter().position(|event| **event == HookEvent::Block(block_number));
+        let state_diff_position = synced_events.iter().position(|event| {
+            **event == HookEvent::StateDiff(block_number, create_block_hash(block_number, false))
+        });
+        assert!(block_position.unwrap() < state_diff_position.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn sync_past_halt_block_doesnt_revert() {
+    const N_BLOCKS: u64 = 2;
+    let _ = simple_logger::init_with_env();
+
+    // The storage is already past the halt block, so the sync stops without checking for reverts.
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    store_reverted_chain(&mut writer, N_BLOCKS);
+
+    let hook = Arc::new(RecordingHook::default());
+    let mut state_sync = GenericStateSync::new(
+        SyncConfig { halt_block_number: Some(BlockNumber(N_BLOCKS - 1)), ..get_test_sync_config() },
+        Arc::new(RwLock::new(SyncingState::default())),
+        Arc::new(RwLock::new(PendingData::default())),
+        get_mock_chain(N_BLOCKS),
+        reader.clone(),
+        writer,
+    );
+    state_sync.register_hook(hook.clone());
+    tokio::time::timeout(Duration::from_secs(5), state_sync.run())
+        .await
+        .expect("Sync didn't stop at the halt block.")
+        .unwrap();
+
+    assert!(hook.events.lock().unwrap().is_empty());
+    let header =
+        reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(N_BLOCKS - 1)).unwrap();
+    assert_eq!(header.unwrap().block_hash, create_block_hash(BlockNumber(N_BLOCKS - 1), true));
+}
+
 #[tokio::test]
 async fn sync_with_revert() {
     let _ = simple_logger::init_with_env();
@@ -435,6 +1309,17 @@ async fn sync_with_revert() {
             }
         }
 
+        fn stream_blocks_with_state_updates(
+            &self,
+            _initial_block_number: BlockNumber,
+            _up_to_block_number: BlockNumber,
+        ) -> BlocksWithStateUpdatesStream<'_> {
+            panic!(
+                "stream_blocks_with_state_updates shouldn't be called, the blocks are downloaded \
+                 separately from the state updates."
+            )
+        }
+
         fn stream_compiled_classes(
             &self,
             _initial_block_number: BlockNumber,
@@ -449,6 +1334,10 @@ async fn sync_with_revert() {
             .boxed();
             res
         }
+
+        async fn get_pending_data(&self) -> Result<Option<PendingData>, CentralError> {
+            Ok(None)
+        }
     }
 }
 
@@ -462,6 +1351,7 @@ async fn test_unrecoverable_sync_error_flow() {
     // Mock central with one block but return wrong header.
     let mut mock = MockCentralSourceTrait::new();
     mock.expect_get_block_marker().returning(|| Ok(BLOCK_NUMBER));
+    mock.expect_get_pending_data().returning(|| Ok(None));
     mock.expect_stream_new_blocks().returning(move |_, _| {
         let blocks_stream: BlocksStream<'_> = stream! {
             let header = BlockHeader {
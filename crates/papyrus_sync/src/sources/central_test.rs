@@ -1,3 +1,5 @@
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
@@ -5,11 +7,15 @@ use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 use futures_util::pin_mut;
 use indexmap::{indexmap, IndexMap};
 use mockall::predicate;
+use papyrus_common::class_hash::calculate_compiled_class_hash;
+use papyrus_storage::compiled_class::CasmStorageWriter;
+use papyrus_storage::header::{HeaderStorageWriter, StarknetVersion};
 use papyrus_storage::state::StateStorageWriter;
 use papyrus_storage::test_utils::get_test_storage;
 use pretty_assertions::assert_eq;
 use reqwest::StatusCode;
-use starknet_api::block::{BlockHash, BlockNumber};
+use serde_json::Value;
+use starknet_api::block::{BlockHash, BlockHeader, BlockNumber};
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::hash::{StarkFelt, StarkHash};
@@ -17,11 +23,18 @@ use starknet_api::state::{ContractClass as sn_api_ContractClass, StateDiff, Stor
 use starknet_api::{patricia_key, stark_felt};
 use starknet_client::reader::{
     Block, ContractClass, DeclaredClassHashEntry, DeployedContract, GenericContractClass,
-    GlobalRoot, MockStarknetReader, ReaderClientError, ReplacedClass, StateUpdate, StorageEntry,
+    GlobalRoot, MockStarknetReader, PendingBlock, PendingData, PendingStateUpdate,
+    ReaderClientError, ReplacedClass, StarknetFeederGatewayClient, StateUpdate,
+    StateUpdateWithBlock, StorageEntry,
 };
+use starknet_client::recording::Recording;
+use test_utils::get_absolute_path;
 use tokio_stream::StreamExt;
 
-use crate::sources::central::{CentralError, CentralSourceTrait, GenericCentralSource};
+use crate::sources::central::{
+    CentralError, CentralSourceConfig, CentralSourceTrait, GenericCentralSource, PendingClasses,
+    StateUpdateStreamConfig, UnknownStarknetVersionPolicy,
+};
 
 const TEST_CONCURRENT_REQUESTS: usize = 300;
 
@@ -38,6 +51,10 @@ async fn last_block_number() {
         starknet_client: Arc::new(mock),
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
 
     let last_block_number = central_source.get_block_marker().await.unwrap().prev().unwrap();
@@ -62,6 +79,10 @@ async fn stream_block_headers() {
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         starknet_client: Arc::new(mock),
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
 
     let mut expected_block_num = BlockNumber(START_BLOCK_NUMBER);
@@ -75,6 +96,54 @@ async fn stream_block_headers() {
     assert_eq!(expected_block_num, BlockNumber(END_BLOCK_NUMBER));
 }
 
+#[tokio::test]
+async fn stream_blocks_with_state_updates() {
+    const START_BLOCK_NUMBER: u64 = 5;
+    const END_BLOCK_NUMBER: u64 = 9;
+    let mut mock = MockStarknetReader::new();
+
+    // The blocks and the state updates are requested together, so there are no expectations for
+    // separate block and state update requests.
+    for i in START_BLOCK_NUMBER..END_BLOCK_NUMBER {
+        mock.expect_state_update_with_block()
+            .with(predicate::eq(BlockNumber(i)))
+            .times(1)
+            .returning(move |block_number| {
+                let block_hash = BlockHash(stark_felt!(block_number.0));
+                Ok(Some(StateUpdateWithBlock {
+                    block: Block { block_number, block_hash, ..Block::default() },
+                    state_update: StateUpdate { block_hash, ..StateUpdate::default() },
+                }))
+            });
+    }
+    let ((reader, _), _temp_dir) = get_test_storage();
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(mock),
+        storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let mut expected_block_num = BlockNumber(START_BLOCK_NUMBER);
+    let stream = central_source
+        .stream_blocks_with_state_updates(expected_block_num, BlockNumber(END_BLOCK_NUMBER));
+    pin_mut!(stream);
+    while let Some(block_with_state_update) = stream.next().await {
+        let (block, _starknet_version, (block_number, block_hash, state_diff, _)) =
+            block_with_state_update.unwrap();
+        assert_eq!(block_number, expected_block_num);
+        assert_eq!(block.header.block_number, expected_block_num);
+        assert_eq!(block.header.block_hash, BlockHash(stark_felt!(expected_block_num.0)));
+        assert_eq!(block_hash, block.header.block_hash);
+        assert_eq!(state_diff, StateDiff::default());
+        expected_block_num = expected_block_num.next();
+    }
+    assert_eq!(expected_block_num, BlockNumber(END_BLOCK_NUMBER));
+}
+
 #[tokio::test]
 async fn stream_block_headers_some_are_missing() {
     const START_BLOCK_NUMBER: u64 = 5;
@@ -98,6 +167,10 @@ async fn stream_block_headers_some_are_missing() {
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         starknet_client: Arc::new(mock),
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
 
     let mut expected_block_num = BlockNumber(START_BLOCK_NUMBER);
@@ -120,6 +193,48 @@ async fn stream_block_headers_some_are_missing() {
     assert_eq!(expected_block_num, BlockNumber(MISSING_BLOCK_NUMBER + 1));
 }
 
+#[tokio::test]
+async fn stream_blocks_of_unsupported_starknet_version() {
+    const UNSUPPORTED_STARKNET_VERSION: &str = "0.13.0";
+    let mut mock = MockStarknetReader::new();
+    mock.expect_block().times(2).returning(|block_number| {
+        Ok(Some(Block {
+            block_number,
+            starknet_version: UNSUPPORTED_STARKNET_VERSION.to_owned(),
+            ..Default::default()
+        }))
+    });
+    let mock = Arc::new(mock);
+    let ((reader, _), _temp_dir) = get_test_storage();
+    let new_central_source = |unknown_starknet_version| GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: mock.clone(),
+        storage_reader: reader.clone(),
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version,
+        pending_classes: PendingClasses::default(),
+    };
+
+    let central_source = new_central_source(UnknownStarknetVersionPolicy::Halt);
+    let stream = central_source.stream_new_blocks(BlockNumber(0), BlockNumber(1));
+    pin_mut!(stream);
+    assert_matches!(
+        stream.next().await,
+        Some(Err(CentralError::UnsupportedStarknetVersion { block_number: BlockNumber(0), starknet_version }))
+        if starknet_version == UNSUPPORTED_STARKNET_VERSION
+    );
+    assert!(stream.next().await.is_none());
+
+    let central_source = new_central_source(UnknownStarknetVersionPolicy::Warn);
+    let stream = central_source.stream_new_blocks(BlockNumber(0), BlockNumber(1));
+    pin_mut!(stream);
+    let (block_number, _block, starknet_version) = stream.next().await.unwrap().unwrap();
+    assert_eq!(block_number, BlockNumber(0));
+    assert_eq!(starknet_version.0, UNSUPPORTED_STARKNET_VERSION);
+    assert!(stream.next().await.is_none());
+}
+
 #[tokio::test]
 async fn stream_block_headers_error() {
     const START_BLOCK_NUMBER: u64 = 5;
@@ -146,6 +261,10 @@ async fn stream_block_headers_error() {
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         starknet_client: Arc::new(mock),
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
 
     let mut expected_block_num = BlockNumber(START_BLOCK_NUMBER);
@@ -281,6 +400,10 @@ async fn stream_state_updates() {
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         starknet_client: Arc::new(mock),
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
     let initial_block_num = BlockNumber(START_BLOCK_NUMBER);
 
@@ -344,6 +467,68 @@ async fn stream_state_updates() {
     assert!(stream.next().await.is_none());
 }
 
+#[tokio::test]
+async fn stream_state_updates_bounds_classes_in_memory() {
+    const START_BLOCK_NUMBER: u64 = 5;
+    const END_BLOCK_NUMBER: u64 = 8;
+    const N_CLASSES_PER_STATE_UPDATE: u64 = 2;
+
+    let mut mock = MockStarknetReader::new();
+    mock.expect_state_update().times(3).returning(|block_number| {
+        let old_declared_contracts = (0..N_CLASSES_PER_STATE_UPDATE)
+            .map(|i| ClassHash(StarkHash::from(block_number.0 * N_CLASSES_PER_STATE_UPDATE + i)))
+            .collect();
+        Ok(Some(StateUpdate {
+            state_diff: starknet_client::reader::StateDiff {
+                old_declared_contracts,
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+    });
+    let class_requests = Arc::new(AtomicUsize::new(0));
+    let class_requests_clone = class_requests.clone();
+    mock.expect_class_by_hash().times(6).returning(move |_| {
+        class_requests_clone.fetch_add(1, Ordering::SeqCst);
+        Ok(Some(GenericContractClass::Cairo0ContractClass(DeprecatedContractClass::default())))
+    });
+    let ((reader, _), _temp_dir) = get_test_storage();
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(mock),
+        storage_reader: reader,
+        // Less than the classes of a single state update.
+        state_update_stream_config: StateUpdateStreamConfig {
+            max_classes_in_memory: 1,
+            ..Default::default()
+        },
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let stream = central_source
+        .stream_state_updates(BlockNumber(START_BLOCK_NUMBER), BlockNumber(END_BLOCK_NUMBER));
+    pin_mut!(stream);
+    for block_number in START_BLOCK_NUMBER..END_BLOCK_NUMBER {
+        let Some(Ok((current_block_number, _, state_diff, _))) = stream.next().await else {
+            panic!("Match of streamed state_update failed!");
+        };
+        assert_eq!(current_block_number, BlockNumber(block_number));
+        assert_eq!(
+            state_diff.deprecated_declared_classes.len(),
+            N_CLASSES_PER_STATE_UPDATE as usize
+        );
+        // Only the classes of the returned state updates were downloaded, although the other state
+        // updates were already downloaded.
+        assert_eq!(
+            class_requests.load(Ordering::SeqCst) as u64,
+            (block_number - START_BLOCK_NUMBER + 1) * N_CLASSES_PER_STATE_UPDATE
+        );
+    }
+    assert!(stream.next().await.is_none());
+}
+
 #[tokio::test]
 async fn stream_compiled_classes() {
     let ((reader, mut writer), _temp_dir) = get_test_storage();
@@ -390,6 +575,10 @@ async fn stream_compiled_classes() {
         concurrent_requests: TEST_CONCURRENT_REQUESTS,
         starknet_client: Arc::new(mock),
         storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
     };
 
     let stream = central_source.stream_compiled_classes(BlockNumber(0), BlockNumber(2));
@@ -406,3 +595,296 @@ async fn stream_compiled_classes() {
         assert_eq!(compiled_class, expected_compiled_class);
     }
 }
+
+#[tokio::test]
+async fn stream_compiled_classes_skips_stored_classes() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let class_hashes: Vec<_> =
+        (0..4).map(|i| ClassHash(stark_felt!(format!("0x{i}").as_str()))).collect();
+    let declared_classes = |class_hashes: &[ClassHash]| {
+        class_hashes
+            .iter()
+            .map(|class_hash| {
+                (*class_hash, (CompiledClassHash(class_hash.0), sn_api_ContractClass::default()))
+            })
+            .collect()
+    };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(0),
+            StateDiff { declared_classes: declared_classes(&class_hashes[..2]), ..StateDiff::default() },
+            indexmap! {},
+        )
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(1),
+            StateDiff { declared_classes: declared_classes(&class_hashes[2..]), ..StateDiff::default() },
+            indexmap! {},
+        )
+        .unwrap()
+        // Store a class from the middle of each block, as if a previous stream was interrupted.
+        .append_casm(&class_hashes[1], &CasmContractClass::default())
+        .unwrap()
+        .append_casm(&class_hashes[2], &CasmContractClass::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // Only the classes that are not stored are downloaded.
+    let mut mock = MockStarknetReader::new();
+    for class_hash in [class_hashes[0], class_hashes[3]] {
+        mock.expect_compiled_class_by_hash()
+            .with(predicate::eq(class_hash))
+            .times(1)
+            .returning(move |_x| Ok(Some(CasmContractClass::default())));
+    }
+
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(mock),
+        storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let stream = central_source.stream_compiled_classes(BlockNumber(0), BlockNumber(2));
+    pin_mut!(stream);
+    for expected_class_hash in [class_hashes[0], class_hashes[3]] {
+        let (class_hash, compiled_class_hash, _compiled_class) =
+            stream.next().await.unwrap().unwrap();
+        assert_eq!(class_hash, expected_class_hash);
+        assert_eq!(compiled_class_hash, CompiledClassHash(expected_class_hash.0));
+    }
+    assert!(stream.next().await.is_none());
+}
+
+// The Sierra class of a declare transaction of the gateway client tests, with the compiled class
+// hash that was declared with it.
+fn read_declared_sierra_class() -> (sn_api_ContractClass, CompiledClassHash) {
+    let path = get_absolute_path("crates/starknet_client/resources/writer/declare_v2.json");
+    let declare: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let mut class = declare["contract_class"].clone();
+    let compressed_program = base64::decode(class["sierra_program"].as_str().unwrap()).unwrap();
+    let mut program = String::new();
+    flate2::read::GzDecoder::new(compressed_program.as_slice())
+        .read_to_string(&mut program)
+        .unwrap();
+    class["sierra_program"] = serde_json::from_str(&program).unwrap();
+    let class: ContractClass = serde_json::from_value(class).unwrap();
+    let compiled_class_hash =
+        serde_json::from_value(declare["compiled_class_hash"].clone()).unwrap();
+    (class.into(), compiled_class_hash)
+}
+
+#[tokio::test]
+async fn stream_compiled_classes_compiles_classes_locally() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let (class, compiled_class_hash) = read_declared_sierra_class();
+    let compiled_class_class_hash = ClassHash(stark_felt!("0x0"));
+    let mismatching_class_hash = ClassHash(stark_felt!("0x1"));
+    let invalid_class_hash = ClassHash(stark_felt!("0x2"));
+    let unsupported_version_class_hash = ClassHash(stark_felt!("0x3"));
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &BlockHeader::default())
+        .unwrap()
+        .update_starknet_version(&BlockNumber(0), &StarknetVersion("0.12.1".to_owned()))
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(0),
+            StateDiff {
+                declared_classes: indexmap! {
+                    compiled_class_class_hash => (compiled_class_hash, class.clone()),
+                    mismatching_class_hash => (CompiledClassHash(stark_felt!("0x1")), class.clone()),
+                    invalid_class_hash => (compiled_class_hash, sn_api_ContractClass::default()),
+                },
+                ..StateDiff::default()
+            },
+            indexmap! {},
+        )
+        .unwrap()
+        .append_header(
+            BlockNumber(1),
+            &BlockHeader { block_hash: BlockHash(stark_felt!("0x1")), ..BlockHeader::default() },
+        )
+        .unwrap()
+        .update_starknet_version(&BlockNumber(1), &StarknetVersion("0.11.2".to_owned()))
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(1),
+            StateDiff {
+                declared_classes: indexmap! {
+                    unsupported_version_class_hash => (compiled_class_hash, class),
+                },
+                ..StateDiff::default()
+            },
+            indexmap! {},
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // Only the classes that can't be compiled to their compiled class hash are downloaded.
+    let mut mock = MockStarknetReader::new();
+    for class_hash in [mismatching_class_hash, invalid_class_hash, unsupported_version_class_hash] {
+        mock.expect_compiled_class_by_hash()
+            .with(predicate::eq(class_hash))
+            .times(1)
+            .returning(move |_x| Ok(Some(CasmContractClass::default())));
+    }
+
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(mock),
+        storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: true,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let stream = central_source.stream_compiled_classes(BlockNumber(0), BlockNumber(2));
+    pin_mut!(stream);
+    let (class_hash, _compiled_class_hash, compiled_class) = stream.next().await.unwrap().unwrap();
+    assert_eq!(class_hash, compiled_class_class_hash);
+    assert_eq!(calculate_compiled_class_hash(&compiled_class).unwrap(), compiled_class_hash);
+    for expected_class_hash in
+        [mismatching_class_hash, invalid_class_hash, unsupported_version_class_hash]
+    {
+        let (class_hash, _compiled_class_hash, compiled_class) =
+            stream.next().await.unwrap().unwrap();
+        assert_eq!(class_hash, expected_class_hash);
+        assert_eq!(compiled_class, CasmContractClass::default());
+    }
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn get_pending_data() {
+    let parent_block_hash = BlockHash(stark_felt!("0x1"));
+    let class_hash = ClassHash(stark_felt!("0x123"));
+    let deprecated_class_hash = ClassHash(stark_felt!("0x456"));
+    let compiled_class_hash = CompiledClassHash(stark_felt!("0x789"));
+    let contract_address = ContractAddress(patricia_key!("0xabc"));
+    let nonce = Nonce(stark_felt!("0x1"));
+
+    let client_pending_data = PendingData {
+        block: PendingBlock { parent_block_hash, ..PendingBlock::default() },
+        state_update: PendingStateUpdate {
+            old_root: GlobalRoot::default(),
+            state_diff: starknet_client::reader::StateDiff {
+                declared_classes: vec![DeclaredClassHashEntry { class_hash, compiled_class_hash }],
+                old_declared_contracts: vec![deprecated_class_hash],
+                nonces: IndexMap::from([(contract_address, nonce)]),
+                ..starknet_client::reader::StateDiff::default()
+            },
+        },
+    };
+
+    let mut mock = MockStarknetReader::new();
+    // The classes are downloaded once, though the pending block is polled twice.
+    mock.expect_pending_data().times(2).returning(move || Ok(Some(client_pending_data.clone())));
+    mock.expect_class_by_hash().with(predicate::eq(class_hash)).times(1).returning(|_x| {
+        Ok(Some(GenericContractClass::Cairo1ContractClass(ContractClass::default())))
+    });
+    mock.expect_class_by_hash().with(predicate::eq(deprecated_class_hash)).times(1).returning(
+        |_x| {
+            Ok(Some(GenericContractClass::Cairo0ContractClass(DeprecatedContractClass::default())))
+        },
+    );
+    mock.expect_pending_data().times(1).returning(|| Ok(None));
+
+    let ((reader, _), _temp_dir) = get_test_storage();
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(mock),
+        storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let pending_data = central_source.get_pending_data().await.unwrap().unwrap();
+    assert_eq!(pending_data.block.parent_block_hash, parent_block_hash);
+    let expected_state_diff = StateDiff {
+        declared_classes: indexmap! {
+            class_hash => (compiled_class_hash, sn_api_ContractClass::from(ContractClass::default()))
+        },
+        deprecated_declared_classes: indexmap! {
+            deprecated_class_hash => DeprecatedContractClass::default()
+        },
+        nonces: indexmap! { contract_address => nonce },
+        ..StateDiff::default()
+    };
+    assert_eq!(pending_data.state_diff, expected_state_diff);
+
+    // The same pending block again, with the classes that were already downloaded.
+    let pending_data = central_source.get_pending_data().await.unwrap().unwrap();
+    assert_eq!(pending_data.state_diff, expected_state_diff);
+
+    // No pending block.
+    assert!(central_source.get_pending_data().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn stream_blocks_from_recordings() {
+    const END_BLOCK_NUMBER: u64 = 3;
+    let recordings_dir = tempfile::tempdir().unwrap();
+    for i in 0..END_BLOCK_NUMBER {
+        let block = Block {
+            block_number: BlockNumber(i),
+            block_hash: BlockHash(stark_felt!(i + 1)),
+            parent_block_hash: BlockHash(stark_felt!(i)),
+            ..Block::default()
+        };
+        // The feeder gateway returns global roots without the "0x" prefix.
+        let mut block = serde_json::to_value(block).unwrap();
+        block["state_root"] = Value::from("0".repeat(64));
+        let recording = Recording {
+            method: "GET".to_owned(),
+            path: format!("/feeder_gateway/get_block?blockNumber={i}"),
+            body: None,
+            status: StatusCode::OK.as_u16(),
+            response: block.to_string(),
+        };
+        std::fs::write(
+            recordings_dir.path().join(format!("{i:08}.json")),
+            serde_json::to_string(&recording).unwrap(),
+        )
+        .unwrap();
+    }
+    let starknet_client = StarknetFeederGatewayClient::from_recordings(
+        "https://replay.url/",
+        recordings_dir.path(),
+        CentralSourceConfig::default().retry_config,
+    )
+    .unwrap();
+
+    let ((reader, _), _temp_dir) = get_test_storage();
+    let central_source = GenericCentralSource {
+        concurrent_requests: TEST_CONCURRENT_REQUESTS,
+        starknet_client: Arc::new(starknet_client),
+        storage_reader: reader,
+        state_update_stream_config: StateUpdateStreamConfig::default(),
+        compile_classes_locally: false,
+        unknown_starknet_version: UnknownStarknetVersionPolicy::default(),
+        pending_classes: PendingClasses::default(),
+    };
+
+    let stream = central_source.stream_new_blocks(BlockNumber(0), BlockNumber(END_BLOCK_NUMBER));
+    pin_mut!(stream);
+    let mut expected_block_number = BlockNumber(0);
+    while let Some(res) = stream.next().await {
+        let (block_number, block, _starknet_version) = res.unwrap();
+        assert_eq!(block_number, expected_block_number);
+        assert_eq!(block.header.block_hash, BlockHash(stark_felt!(block_number.0 + 1)));
+        expected_block_number = expected_block_number.next();
+    }
+    assert_eq!(expected_block_number, BlockNumber(END_BLOCK_NUMBER));
+}
@@ -1,9 +1,15 @@
+mod archive;
+#[cfg(test)]
+mod archive_test;
 mod central;
 #[cfg(test)]
 mod central_sync_test;
 #[cfg(test)]
 mod central_test;
 
+pub use archive::{ArchiveReader, ArchiveSource};
+pub(crate) use central::{client_to_central_state_diff, ApiContractClass, CentralStateUpdate};
 pub use central::{
     CentralError, CentralResult, CentralSource, CentralSourceConfig, CentralSourceTrait,
+    StateUpdateStreamConfig, UnknownStarknetVersionPolicy,
 };
@@ -0,0 +1,64 @@
+use metrics::gauge;
+use papyrus_storage::base_layer::BaseLayerStorageReader;
+use papyrus_storage::body::BodyStorageReader;
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::db::RO;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageResult, StorageTxn};
+
+use crate::StateSyncError;
+
+// Name of the metrics.
+pub(crate) const HEADER_MARKER: &str = "papyrus_header_marker";
+pub(crate) const BODY_MARKER: &str = "papyrus_body_marker";
+pub(crate) const STATE_MARKER: &str = "papyrus_state_marker";
+pub(crate) const COMPILED_CLASS_MARKER: &str = "papyrus_compiled_class_marker";
+pub(crate) const BASE_LAYER_MARKER: &str = "papyrus_base_layer_marker";
+pub(crate) const CENTRAL_BLOCK_MARKER: &str = "papyrus_central_block_marker";
+pub(crate) const REVERTED_BLOCKS: &str = "papyrus_reverted_blocks";
+pub(crate) const DEEP_REVERTS: &str = "papyrus_deep_reverts";
+pub(crate) const RECOVERABLE_ERRORS: &str = "papyrus_sync_recoverable_errors";
+pub(crate) const DOWNLOADED_COMPILED_CLASSES: &str = "papyrus_downloaded_compiled_classes";
+pub(crate) const LOCALLY_COMPILED_CLASSES: &str = "papyrus_locally_compiled_classes";
+pub(crate) const SKIPPED_COMPILED_CLASSES: &str = "papyrus_skipped_compiled_classes";
+pub(crate) const DOWNLOAD_LATENCY: &str = "papyrus_sync_download_latency_seconds";
+pub(crate) const COMMIT_LATENCY: &str = "papyrus_sync_commit_latency_seconds";
+
+// Labels for the metrics.
+pub(crate) const EVENT_LABEL: &str = "event";
+pub(crate) const ERROR_KIND_LABEL: &str = "kind";
+
+// Values of the event label.
+pub(crate) const BLOCK_EVENT: &str = "block";
+pub(crate) const STATE_DIFF_EVENT: &str = "state_diff";
+pub(crate) const COMPILED_CLASS_EVENT: &str = "compiled_class";
+pub(crate) const PENDING_DATA_EVENT: &str = "pending_data";
+
+// Sets the marker gauges to the markers in the storage.
+pub(crate) fn update_marker_metrics(txn: &StorageTxn<'_, RO>) -> StorageResult<()> {
+    gauge!(HEADER_MARKER, txn.get_header_marker()?.0 as f64);
+    gauge!(BODY_MARKER, txn.get_body_marker()?.0 as f64);
+    gauge!(STATE_MARKER, txn.get_state_marker()?.0 as f64);
+    gauge!(COMPILED_CLASS_MARKER, txn.get_compiled_class_marker()?.0 as f64);
+    gauge!(BASE_LAYER_MARKER, txn.get_base_layer_block_marker()?.0 as f64);
+    Ok(())
+}
+
+// Returns the value of the error kind label of a recoverable error.
+pub(crate) fn recoverable_error_kind(err: &StateSyncError) -> &'static str {
+    match err {
+        StateSyncError::StorageError(_) => "storage",
+        StateSyncError::CentralSourceError(_) => "central_source",
+        StateSyncError::ParentBlockHashMismatch { .. } => "parent_block_hash_mismatch",
+        StateSyncError::StateDiffWithoutMatchingHeader { .. } => {
+            "state_diff_without_matching_header"
+        }
+        StateSyncError::RevertDepthExceeded { .. } => "revert_depth_exceeded",
+        StateSyncError::CheckpointMismatch { .. } => "checkpoint_mismatch",
+        StateSyncError::RevertBelowCheckpoint { .. } => "revert_below_checkpoint",
+        StateSyncError::BlockHashError(_) => "block_hash",
+        StateSyncError::TransactionHashError(_) => "transaction_hash",
+        StateSyncError::ClassHashError(_) => "class_hash",
+    }
+}
@@ -0,0 +1,117 @@
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use indexmap::IndexMap;
+use papyrus_common::block_hash::{verify_block_hash, BlockHashError};
+use papyrus_common::class_hash::{
+    verify_class_hash, verify_compiled_class_hash, verify_deprecated_class_hash,
+};
+use papyrus_common::transaction_hash::verify_transaction_hash;
+use papyrus_storage::header::StarknetVersion;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{Block, BlockBody};
+use starknet_api::core::{ChainId, ClassHash, CompiledClassHash};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::StateDiff;
+use tracing::{debug, warn};
+
+use crate::{StateSyncError, StateSyncResult};
+
+/// How the sync handles data whose hash doesn't match the hash calculated by the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationMode {
+    /// Stop the sync with an error.
+    Verify,
+    /// Log a warning and store the data.
+    Warn,
+    /// Don't calculate the hash.
+    #[default]
+    Off,
+}
+
+// Recalculates the hash of the block and handles a mismatch according to the verification mode.
+pub(crate) fn verify_block(
+    mode: VerificationMode,
+    block: &Block,
+    starknet_version: &StarknetVersion,
+    chain_id: &ChainId,
+) -> StateSyncResult {
+    if mode == VerificationMode::Off {
+        return Ok(());
+    }
+    match verify_block_hash(block, &starknet_version.0, chain_id) {
+        Ok(()) => Ok(()),
+        Err(err @ BlockHashError::UnsupportedVersion(_)) => {
+            debug!("Skipping the block hash verification: {err}");
+            Ok(())
+        }
+        Err(err) => handle_mismatch(mode, err.into()),
+    }
+}
+
+// Recalculates the hashes of the transactions of the block and handles a mismatch according to the
+// verification mode.
+pub(crate) fn verify_transactions(
+    mode: VerificationMode,
+    body: &BlockBody,
+    chain_id: &ChainId,
+) -> StateSyncResult {
+    if mode == VerificationMode::Off {
+        return Ok(());
+    }
+    for (transaction, transaction_hash) in body.transactions.iter().zip(&body.transaction_hashes) {
+        if let Err(err) = verify_transaction_hash(transaction, transaction_hash, chain_id) {
+            handle_mismatch(mode, err.into())?;
+        }
+    }
+    Ok(())
+}
+
+// Recalculates the hashes of the classes that are declared in the state diff, and of the deprecated
+// classes of the contracts it deploys, and handles a mismatch according to the verification mode.
+pub(crate) fn verify_classes(
+    mode: VerificationMode,
+    state_diff: &StateDiff,
+    deployed_contract_class_definitions: &IndexMap<ClassHash, DeprecatedContractClass>,
+) -> StateSyncResult {
+    if mode == VerificationMode::Off {
+        return Ok(());
+    }
+    for (class_hash, (_, class)) in &state_diff.declared_classes {
+        if let Err(err) = verify_class_hash(class, class_hash) {
+            handle_mismatch(mode, err.into())?;
+        }
+    }
+    let deprecated_classes =
+        state_diff.deprecated_declared_classes.iter().chain(deployed_contract_class_definitions);
+    for (class_hash, class) in deprecated_classes {
+        if let Err(err) = verify_deprecated_class_hash(class, class_hash) {
+            handle_mismatch(mode, err.into())?;
+        }
+    }
+    Ok(())
+}
+
+// Recalculates the compiled class hash of the compiled class and handles a mismatch according to
+// the verification mode.
+pub(crate) fn verify_compiled_class(
+    mode: VerificationMode,
+    class_hash: &ClassHash,
+    compiled_class_hash: &CompiledClassHash,
+    compiled_class: &CasmContractClass,
+) -> StateSyncResult {
+    if mode == VerificationMode::Off {
+        return Ok(());
+    }
+    match verify_compiled_class_hash(compiled_class, class_hash, compiled_class_hash) {
+        Ok(()) => Ok(()),
+        Err(err) => handle_mismatch(mode, err.into()),
+    }
+}
+
+fn handle_mismatch(mode: VerificationMode, err: StateSyncError) -> StateSyncResult {
+    if mode == VerificationMode::Warn {
+        warn!("{err}");
+        return Ok(());
+    }
+    Err(err)
+}
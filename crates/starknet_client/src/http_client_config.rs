@@ -0,0 +1,137 @@
+#[cfg(test)]
+#[path = "http_client_config_test.rs"]
+mod http_client_config_test;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use papyrus_config::converters::{
+    deserialize_optional_number, deserialize_optional_path, deserialize_optional_string,
+    serialize_optional_number, serialize_optional_path, serialize_optional_string,
+};
+use papyrus_config::dumping::{ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, SerializedParam};
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::ClientCreationError;
+
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
+/// A configuration for the HTTP client that sends the requests.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct HttpClientConfig {
+    /// The URL of an HTTP or HTTPS proxy to send the requests through, or None to send them
+    /// directly.
+    #[serde(deserialize_with = "deserialize_optional_string")]
+    pub proxy_url: Option<String>,
+    /// A path to a PEM file with root certificates to trust in addition to the system ones.
+    #[serde(deserialize_with = "deserialize_optional_path")]
+    pub root_certificates_path: Option<PathBuf>,
+    /// The timeout in milliseconds for connecting to the server, or None for no timeout.
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub connect_timeout_millis: Option<u64>,
+    /// The timeout in milliseconds for a request, from connecting until the response is read, or
+    /// None for no timeout.
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub request_timeout_millis: Option<u64>,
+    /// If true, asks for compressed responses and decompresses them.
+    pub decompress_responses: bool,
+    /// If true, uses HTTP/2 without negotiating it, for servers that are known to support it.
+    pub http2_prior_knowledge: bool,
+    /// The maximal size in bytes of a response body, or None for no limit. Larger responses fail
+    /// with [`ResponseTooLarge`](crate::ClientError::ResponseTooLarge).
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub max_response_size: Option<usize>,
+}
+
+impl SerializeConfig for HttpClientConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "proxy_url",
+                &serialize_optional_string(&self.proxy_url),
+                "URL of an HTTP or HTTPS proxy to send the requests through. If empty, the \
+                 requests are sent directly.",
+            ),
+            ser_param(
+                "root_certificates_path",
+                &serialize_optional_path(&self.root_certificates_path),
+                "Path to a PEM file with root certificates to trust in addition to the system \
+                 ones.",
+            ),
+            ser_param(
+                "connect_timeout_millis",
+                &serialize_optional_number(&self.connect_timeout_millis),
+                "Timeout for connecting to the server. If empty, there is no timeout.",
+            ),
+            ser_param(
+                "request_timeout_millis",
+                &serialize_optional_number(&self.request_timeout_millis),
+                "Timeout for a request, from connecting until the response is read. If empty, \
+                 there is no timeout.",
+            ),
+            ser_param(
+                "decompress_responses",
+                &self.decompress_responses,
+                "If true, asks for gzip or brotli compressed responses and decompresses them.",
+            ),
+            ser_param(
+                "http2_prior_knowledge",
+                &self.http2_prior_knowledge,
+                "If true, sends the requests with HTTP/2 without negotiating it. Over TLS, HTTP/2 \
+                 is used when the server supports it anyway.",
+            ),
+            ser_param(
+                "max_response_size",
+                &serialize_optional_number(&self.max_response_size),
+                "Maximum size in bytes of a response. If empty, the size is not limited.",
+            ),
+        ])
+    }
+}
+
+impl HttpClientConfig {
+    /// Applies the configuration to the builder of the HTTP client.
+    pub(crate) fn apply(
+        &self,
+        mut builder: ClientBuilder,
+    ) -> Result<ClientBuilder, ClientCreationError> {
+        if let Some(proxy_url) = &self.proxy_url {
+            builder = builder.proxy(Proxy::all(proxy_url)?);
+        }
+        if let Some(root_certificates_path) = &self.root_certificates_path {
+            let pem = fs::read_to_string(root_certificates_path).map_err(|err| {
+                ClientCreationError::RootCertificatesError {
+                    path: root_certificates_path.clone(),
+                    message: err.to_string(),
+                }
+            })?;
+            for certificate in pem_certificates(&pem) {
+                builder =
+                    builder.add_root_certificate(Certificate::from_pem(certificate.as_bytes())?);
+            }
+        }
+        if let Some(connect_timeout_millis) = self.connect_timeout_millis {
+            builder = builder.connect_timeout(Duration::from_millis(connect_timeout_millis));
+        }
+        if let Some(request_timeout_millis) = self.request_timeout_millis {
+            builder = builder.timeout(Duration::from_millis(request_timeout_millis));
+        }
+        builder = builder.gzip(self.decompress_responses).brotli(self.decompress_responses);
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        Ok(builder)
+    }
+}
+
+// Splits a PEM file to its certificates, since a reqwest certificate holds a single one.
+fn pem_certificates(pem: &str) -> Vec<String> {
+    pem.split_inclusive(PEM_CERTIFICATE_END)
+        .filter(|certificate| certificate.contains(PEM_CERTIFICATE_END))
+        .map(|certificate| certificate.trim().to_owned())
+        .collect()
+}
@@ -0,0 +1,68 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use assert_matches::assert_matches;
+use mockito::mock;
+use pretty_assertions::assert_eq;
+use reqwest::Client;
+
+use super::{pem_certificates, HttpClientConfig};
+use crate::test_utils::read_resource::read_resource_file;
+use crate::ClientCreationError;
+
+fn root_certificates_path() -> PathBuf {
+    Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("resources/root_certificates.pem")
+}
+
+#[test]
+fn split_pem_certificates() {
+    let certificates = pem_certificates(&read_resource_file("root_certificates.pem"));
+    assert_eq!(certificates.len(), 2);
+    for certificate in certificates {
+        assert!(certificate.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(certificate.ends_with("-----END CERTIFICATE-----"));
+    }
+}
+
+#[test]
+fn missing_root_certificates() {
+    let config = HttpClientConfig {
+        root_certificates_path: Some(PathBuf::from("/no/such/root_certificates.pem")),
+        ..Default::default()
+    };
+    let err = config.apply(Client::builder()).unwrap_err();
+    assert_matches!(err, ClientCreationError::RootCertificatesError { path, .. } if path == config.root_certificates_path.unwrap());
+}
+
+#[test]
+fn bad_proxy_url() {
+    let config = HttpClientConfig { proxy_url: Some("not a url".to_owned()), ..Default::default() };
+    let err = config.apply(Client::builder()).unwrap_err();
+    assert_matches!(err, ClientCreationError::BuildError(_));
+}
+
+#[tokio::test]
+async fn request_with_config() {
+    let config = HttpClientConfig {
+        proxy_url: None,
+        root_certificates_path: Some(root_certificates_path()),
+        connect_timeout_millis: Some(1000),
+        request_timeout_millis: Some(5000),
+        decompress_responses: true,
+        http2_prior_knowledge: false,
+        max_response_size: None,
+    };
+    let client = config.apply(Client::builder()).unwrap().build().unwrap();
+    // "body" in a brotli stream of an uncompressed meta-block followed by an empty last one.
+    let brotli_body = [0x30, 0x00, 0x10, b'b', b'o', b'd', b'y', 0x03];
+    let mock = mock("GET", "/http_client_config")
+        .match_header("accept-encoding", "gzip, br")
+        .with_status(200)
+        .with_header("content-encoding", "br")
+        .with_body(brotli_body)
+        .create();
+    let response =
+        client.get(format!("{}/http_client_config", mockito::server_url())).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "body");
+    mock.assert();
+}
@@ -4,7 +4,10 @@
 //!
 //! [`Starknet`]: https://starknet.io/
 
+pub mod http_client_config;
 pub mod reader;
+pub mod recording;
+pub mod request_limiter;
 pub mod retry;
 #[cfg(test)]
 mod test_utils;
@@ -12,14 +15,31 @@ pub mod writer;
 
 use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use reqwest::header::HeaderMap;
-use reqwest::{Client, RequestBuilder, StatusCode};
+use async_trait::async_trait;
+use metrics::counter;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 use tracing::warn;
 
-use self::retry::Retry;
+pub use self::http_client_config::HttpClientConfig;
+use self::recording::{request_key, Recorder, RecordingError, Replayer};
+use self::request_limiter::RequestLimiter;
 pub use self::retry::RetryConfig;
+use self::retry::{CircuitBreaker, Retry};
+
+// Name of the metrics.
+const DOWNLOADED_BYTES: &str = "starknet_client_downloaded_bytes";
+
+// The number of chunks of a response body that are read ahead of its deserialization.
+const BODY_CHUNKS_TO_READ_AHEAD: usize = 16;
 
 /// A [`Result`] in which the error is a [`ClientError`].
 type ClientResult<T> = Result<T, ClientError>;
@@ -27,8 +47,14 @@ type ClientResult<T> = Result<T, ClientError>;
 /// A starknet client.
 struct StarknetClient {
     http_headers: HeaderMap,
+    user_agent: String,
     pub internal_client: Client,
+    max_response_size: Option<usize>,
     retry_config: RetryConfig,
+    circuit_breaker: CircuitBreaker,
+    request_limiter: Option<Arc<RequestLimiter>>,
+    recorder: Option<Recorder>,
+    replayer: Option<Replayer>,
 }
 
 /// Error codes returned by the starknet gateway.
@@ -60,6 +86,10 @@ pub enum ClientCreationError {
     BuildError(#[from] reqwest::Error),
     #[error(transparent)]
     HttpHeaderError(#[from] http::Error),
+    #[error(transparent)]
+    RecordingError(#[from] RecordingError),
+    #[error("Failed reading the root certificates from {path:?}: {message}.")]
+    RootCertificatesError { path: PathBuf, message: String },
 }
 
 /// Errors that might be solved by retrying mechanism.
@@ -70,6 +100,7 @@ pub enum RetryErrorCode {
     TooManyRequests,
     ServiceUnavailable,
     Disconnect,
+    CircuitBreakerOpen,
 }
 
 /// Errors that may be returned by a reader or writer client.
@@ -77,7 +108,7 @@ pub enum RetryErrorCode {
 pub enum ClientError {
     /// A client error representing bad status http responses.
     #[error("Bad response status code: {:?} message: {:?}.", code, message)]
-    BadResponseStatus { code: StatusCode, message: String },
+    BadResponseStatus { code: StatusCode, message: String, retry_after: Option<Duration> },
     /// A client error representing http request errors.
     #[error(transparent)]
     RequestError(#[from] reqwest::Error),
@@ -90,6 +121,15 @@ pub enum ClientError {
     /// A client error representing errors returned by the starknet client.
     #[error(transparent)]
     StarknetError(#[from] StarknetError),
+    /// A client error representing a request with no recorded response to replay.
+    #[error("No recorded response to {} request {}.", method, path)]
+    MissingRecording { method: String, path: String },
+    /// A client error representing a request that wasn't sent since the circuit breaker is open.
+    #[error("The circuit breaker is open for another {:?}.", retry_after)]
+    CircuitBreakerOpen { retry_after: Duration },
+    /// A client error representing a response larger than the maximal response size.
+    #[error("The response is larger than the maximal response size of {} bytes.", max_size)]
+    ResponseTooLarge { max_size: usize },
 }
 
 impl Display for StarknetError {
@@ -129,14 +169,56 @@ impl StarknetClient {
         );
         Ok(StarknetClient {
             http_headers: header_map,
-            internal_client: Client::builder().user_agent(app_user_agent).build()?,
+            internal_client: Client::builder().user_agent(app_user_agent.clone()).build()?,
+            user_agent: app_user_agent,
+            max_response_size: None,
+            circuit_breaker: CircuitBreaker::new(&retry_config),
             retry_config,
+            request_limiter: None,
+            recorder: None,
+            replayer: None,
         })
     }
 
+    /// Creates a client that sends no requests, and serves the responses recorded in
+    /// `recordings_dir` instead.
+    pub fn from_recordings(
+        recordings_dir: &Path,
+        retry_config: RetryConfig,
+    ) -> Result<Self, ClientCreationError> {
+        Ok(StarknetClient {
+            http_headers: HeaderMap::new(),
+            user_agent: String::new(),
+            internal_client: Client::builder().build()?,
+            max_response_size: None,
+            circuit_breaker: CircuitBreaker::new(&retry_config),
+            retry_config,
+            request_limiter: None,
+            recorder: None,
+            replayer: Some(Replayer::new(recordings_dir)?),
+        })
+    }
+
+    /// Records the requests of the client and their responses to `recordings_dir`.
+    pub fn record_to(&mut self, recordings_dir: &Path) -> Result<(), ClientCreationError> {
+        self.recorder = Some(Recorder::new(recordings_dir)?);
+        Ok(())
+    }
+
+    /// Rebuilds the HTTP client of the client with the given configuration.
+    pub fn set_http_client_config(
+        &mut self,
+        config: &HttpClientConfig,
+    ) -> Result<(), ClientCreationError> {
+        let builder = Client::builder().user_agent(self.user_agent.clone());
+        self.internal_client = config.apply(builder)?.build()?;
+        self.max_response_size = config.max_response_size;
+        Ok(())
+    }
+
     fn get_retry_error_code(err: &ClientError) -> Option<RetryErrorCode> {
         match err {
-            ClientError::BadResponseStatus { code, message: _ } => match *code {
+            ClientError::BadResponseStatus { code, .. } => match *code {
                 StatusCode::TEMPORARY_REDIRECT => Some(RetryErrorCode::Redirect),
                 StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => {
                     Some(RetryErrorCode::Timeout)
@@ -160,6 +242,23 @@ impl StarknetClient {
                 }
             }
 
+            ClientError::CircuitBreakerOpen { .. } => Some(RetryErrorCode::CircuitBreakerOpen),
+
+            _ => None,
+        }
+    }
+
+    // The minimal waiting time before retrying the request, as requested by the server or by the
+    // circuit breaker.
+    fn retry_after(err: &RequestWithRetryError) -> Option<Duration> {
+        match err {
+            RequestWithRetryError::ClientError(ClientError::BadResponseStatus {
+                retry_after,
+                ..
+            }) => *retry_after,
+            RequestWithRetryError::ClientError(ClientError::CircuitBreakerOpen { retry_after }) => {
+                Some(*retry_after)
+            }
             _ => None,
         }
     }
@@ -172,12 +271,36 @@ impl StarknetClient {
     }
 
     // If the request_builder is unclonable, the function will not retry the request upon failure.
+    // The number of retries is by the endpoint of the request, the last segment of its path.
     pub async fn request_with_retry(
         &self,
         request_builder: RequestBuilder,
     ) -> ClientResult<String> {
-        let res = Retry::new(&self.retry_config)
-            .start_with_condition(
+        self.request_body_with_retry(request_builder).await
+    }
+
+    // Like request_with_retry, but deserializes the object in the response while its body is
+    // downloaded, instead of holding the whole body in memory.
+    pub async fn request_object_with_retry<T: DeserializeOwned + Send + 'static>(
+        &self,
+        request_builder: RequestBuilder,
+    ) -> ClientResult<T> {
+        Ok(self.request_body_with_retry::<Deserialized<T>>(request_builder).await?.0)
+    }
+
+    async fn request_body_with_retry<Body: ResponseBody>(
+        &self,
+        request_builder: RequestBuilder,
+    ) -> ClientResult<Body> {
+        let endpoint = request_builder
+            .try_clone()
+            .and_then(|request_builder| request_builder.build().ok())
+            .and_then(|request| {
+                request.url().path_segments()?.next_back().map(|endpoint| endpoint.to_owned())
+            })
+            .unwrap_or_default();
+        let res = Retry::for_endpoint(&self.retry_config, &endpoint)
+            .start_with_condition_and_min_delay(
                 || async {
                     match request_builder.try_clone() {
                         Some(request_builder) => self
@@ -188,11 +311,12 @@ impl StarknetClient {
                     }
                 },
                 Self::should_retry,
+                Self::retry_after,
             )
             .await;
 
         match res {
-            Ok(string) => Ok(string),
+            Ok(body) => Ok(body),
             Err(RequestWithRetryError::ClientError(err)) => Err(Self::get_retry_error_code(&err)
                 .map(|code| ClientError::RetryError { code, message: err.to_string() })
                 .unwrap_or(err)),
@@ -203,17 +327,82 @@ impl StarknetClient {
         }
     }
 
-    async fn request(&self, request_builder: RequestBuilder) -> ClientResult<String> {
-        let res = request_builder.headers(self.http_headers.clone()).send().await;
-        let (code, message) = match res {
-            Ok(response) => (response.status(), response.text().await?),
-            Err(err) => {
-                let msg = err.to_string();
-                (err.status().ok_or(err)?, msg)
+    async fn request<Body: ResponseBody>(
+        &self,
+        request_builder: RequestBuilder,
+    ) -> ClientResult<Body> {
+        self.circuit_breaker
+            .check()
+            .map_err(|retry_after| ClientError::CircuitBreakerOpen { retry_after })?;
+        let res = match &self.request_limiter {
+            None => self.send_request(request_builder).await,
+            Some(request_limiter) => {
+                let permit = request_limiter.acquire().await;
+                let res = self.send_request(request_builder).await;
+                match &res {
+                    Ok(_) => permit.on_success(),
+                    Err(ClientError::BadResponseStatus { code, .. })
+                        if *code == StatusCode::TOO_MANY_REQUESTS
+                            || *code == StatusCode::SERVICE_UNAVAILABLE =>
+                    {
+                        permit.on_throttled()
+                    }
+                    Err(_) => {}
+                }
+                res
+            }
+        };
+        // Only the errors that might be solved by retrying indicate that the server is down.
+        match &res {
+            Err(err) if Self::get_retry_error_code(err).is_some() => {
+                self.circuit_breaker.on_failure()
+            }
+            _ => self.circuit_breaker.on_success(),
+        }
+        res
+    }
+
+    async fn send_request<Body: ResponseBody>(
+        &self,
+        request_builder: RequestBuilder,
+    ) -> ClientResult<Body> {
+        let request = request_builder.headers(self.http_headers.clone()).build()?;
+        let (code, message, retry_after) = match &self.replayer {
+            Some(replayer) => {
+                let (code, message) = replayer.replay(&request)?;
+                (code, message, None)
+            }
+            None => {
+                let recorded_request_key = self.recorder.as_ref().map(|_| request_key(&request));
+                let res = self.internal_client.execute(request).await;
+                let (code, message, retry_after) = match res {
+                    // Unless it's recorded, the body of a successful response is read directly.
+                    Ok(response)
+                        if response.status() == StatusCode::OK && self.recorder.is_none() =>
+                    {
+                        return Body::read(self, response).await;
+                    }
+                    Ok(response) => {
+                        let retry_after = retry_after(response.headers());
+                        (response.status(), self.read_body(response).await?, retry_after)
+                    }
+                    Err(err) => {
+                        let msg = err.to_string();
+                        (err.status().ok_or(err)?, msg, None)
+                    }
+                };
+                if let (Some(recorder), Some(request_key)) = (&self.recorder, recorded_request_key)
+                {
+                    recorder.record(request_key, code, &message);
+                }
+                (code, message, retry_after)
             }
         };
         match code {
-            StatusCode::OK => Ok(message),
+            StatusCode::OK => {
+                counter!(DOWNLOADED_BYTES, message.len() as u64);
+                Body::from_text(message)
+            }
             // TODO(Omri): The error code returned from SN changed from error 500 to error 400. For
             // now, keeping both options. In the future, remove the '500' (INTERNAL_SERVER_ERROR)
             // option.
@@ -221,7 +410,137 @@ impl StarknetClient {
                 let starknet_error: StarknetError = serde_json::from_str(&message)?;
                 Err(ClientError::StarknetError(starknet_error))
             }
-            _ => Err(ClientError::BadResponseStatus { code, message }),
+            _ => Err(ClientError::BadResponseStatus { code, message, retry_after }),
+        }
+    }
+
+    fn check_response_size(&self, size: usize) -> ClientResult<()> {
+        match self.max_response_size {
+            Some(max_size) if size > max_size => Err(ClientError::ResponseTooLarge { max_size }),
+            _ => Ok(()),
+        }
+    }
+
+    // Reads the body of the response to a string, failing as soon as it exceeds the maximal
+    // response size.
+    async fn read_body(&self, mut response: Response) -> ClientResult<String> {
+        if let Some(content_length) = response.content_length() {
+            self.check_response_size(content_length.try_into().unwrap_or(usize::MAX))?;
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            self.check_response_size(body.len())?;
+        }
+        Ok(String::from_utf8(body)
+            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()))
+    }
+
+    // Deserializes the object in the body of the response on a blocking thread, which receives the
+    // chunks of the body as they are downloaded. Only a few chunks are held in memory at a time.
+    async fn deserialize_body<T: DeserializeOwned + Send + 'static>(
+        &self,
+        mut response: Response,
+    ) -> ClientResult<T> {
+        if let Some(content_length) = response.content_length() {
+            self.check_response_size(content_length.try_into().unwrap_or(usize::MAX))?;
+        }
+        let (sender, receiver) = mpsc::channel(BODY_CHUNKS_TO_READ_AHEAD);
+        let deserialization = tokio::task::spawn_blocking(move || {
+            serde_json::from_reader::<_, T>(ChunksReader { receiver, chunk: Vec::new(), offset: 0 })
+        });
+        let mut size = 0;
+        let download: ClientResult<()> = async {
+            while let Some(chunk) = response.chunk().await? {
+                size += chunk.len();
+                self.check_response_size(size)?;
+                // The deserialization stops early only if it fails.
+                if sender.send(chunk.to_vec()).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        .await;
+        // Closing the channel ends the body for the deserialization.
+        drop(sender);
+        let object = deserialization.await.expect("The deserialization of a response panicked.");
+        // A failure to download the body fails its deserialization too, so it takes precedence.
+        download?;
+        counter!(DOWNLOADED_BYTES, size as u64);
+        Ok(object?)
+    }
+}
+
+// The body of a successful response.
+#[async_trait]
+trait ResponseBody: Sized + Send {
+    // Reads the body from the response.
+    async fn read(client: &StarknetClient, response: Response) -> ClientResult<Self>;
+    // Converts the body from its text, for responses that were already read.
+    fn from_text(text: String) -> ClientResult<Self>;
+}
+
+#[async_trait]
+impl ResponseBody for String {
+    async fn read(client: &StarknetClient, response: Response) -> ClientResult<Self> {
+        let body = client.read_body(response).await?;
+        counter!(DOWNLOADED_BYTES, body.len() as u64);
+        Ok(body)
+    }
+
+    fn from_text(text: String) -> ClientResult<Self> {
+        Ok(text)
+    }
+}
+
+// An object deserialized from the body of a response.
+struct Deserialized<T>(T);
+
+#[async_trait]
+impl<T: DeserializeOwned + Send + 'static> ResponseBody for Deserialized<T> {
+    async fn read(client: &StarknetClient, response: Response) -> ClientResult<Self> {
+        Ok(Deserialized(client.deserialize_body(response).await?))
+    }
+
+    fn from_text(text: String) -> ClientResult<Self> {
+        Ok(Deserialized(serde_json::from_str(&text)?))
+    }
+}
+
+// A blocking reader of the chunks of a response body, which are received over a channel until it's
+// closed.
+struct ChunksReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    // The number of bytes of the current chunk that were already read.
+    offset: usize,
+}
+
+impl Read for ChunksReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.offset == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.offset = 0;
+                }
+                None => return Ok(0),
+            }
         }
+        let n_bytes = buf.len().min(self.chunk.len() - self.offset);
+        buf[..n_bytes].copy_from_slice(&self.chunk[self.offset..self.offset + n_bytes]);
+        self.offset += n_bytes;
+        Ok(n_bytes)
     }
 }
+
+// The waiting time requested by the server in the Retry-After header. Only the delay in seconds is
+// supported, and not an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let retry_after = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    retry_after.trim().parse().ok().map(Duration::from_secs)
+}
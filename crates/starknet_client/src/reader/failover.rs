@@ -0,0 +1,322 @@
+//! A [`StarknetReader`] that reads from several feeder gateway endpoints, for example a private
+//! feeder gateway proxy and the public feeder gateway as its fallback.
+//!
+//! The requests are sent to a single active endpoint. If the active endpoint fails, the request is
+//! sent to the other endpoints in their order, and the first one that succeeds becomes the active
+//! endpoint. Every request for the latest block number checks the health of all the endpoints and
+//! makes the first healthy endpoint that doesn't lag behind the others the active one, so that the
+//! reader returns to the preferred endpoints once they recover. An endpoint that doesn't respond to
+//! the health check in time is considered unhealthy, so it can't delay the check.
+
+#[cfg(test)]
+#[path = "failover_test.rs"]
+mod failover_test;
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use futures::future::join_all;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::TransactionHash;
+use tracing::{debug, warn};
+
+use crate::reader::{
+    Block, BlockSignatureData, ContractAddresses, GenericContractClass, PendingBlock, PendingData,
+    ReaderClientError, ReaderClientResult, StarknetReader, StateUpdate, StateUpdateWithBlock,
+    TransactionInfo, TransactionReceiptInfo, TransactionStatusInfo,
+};
+
+/// An endpoint of a [`FailoverReader`].
+pub struct Endpoint<TReader> {
+    /// The URL of the endpoint, for the logs.
+    pub url: String,
+    pub reader: TReader,
+}
+
+/// A reader that fails over between several endpoints, ordered by preference.
+pub struct FailoverReader<TReader: StarknetReader + Send + Sync> {
+    endpoints: Vec<Endpoint<TReader>>,
+    max_lag_blocks: u64,
+    verify_block_hashes: bool,
+    health_check_timeout: Duration,
+    // The index of the endpoint the requests are sent to first.
+    active_endpoint: AtomicUsize,
+}
+
+impl<TReader: StarknetReader + Send + Sync> FailoverReader<TReader> {
+    /// Creates a reader of the endpoints, which must not be empty. An endpoint is considered
+    /// lagging if its latest block is more than `max_lag_blocks` behind the latest block of another
+    /// endpoint. If `verify_block_hashes` is true, the hash of every block is compared to the hash
+    /// of the block in another endpoint before the block is returned. The health check waits up to
+    /// `health_check_timeout` for the latest block number of every endpoint.
+    pub fn new(
+        endpoints: Vec<Endpoint<TReader>>,
+        max_lag_blocks: u64,
+        verify_block_hashes: bool,
+        health_check_timeout: Duration,
+    ) -> Self {
+        assert!(!endpoints.is_empty(), "A failover reader needs at least one endpoint.");
+        FailoverReader {
+            endpoints,
+            max_lag_blocks,
+            verify_block_hashes,
+            health_check_timeout,
+            active_endpoint: AtomicUsize::new(0),
+        }
+    }
+
+    fn set_active_endpoint(&self, index: usize, reason: &str) {
+        let previous = self.active_endpoint.swap(index, Ordering::Relaxed);
+        if previous != index {
+            warn!(
+                "Switching the feeder gateway endpoint from {} to {}: {reason}.",
+                self.endpoints[previous].url, self.endpoints[index].url
+            );
+        }
+    }
+
+    // Sends the request to the active endpoint, and upon a failure to the other endpoints by their
+    // order. Returns the response along with the index of the endpoint that returned it.
+    async fn read<'a, T, F, Fut>(&'a self, request: F) -> ReaderClientResult<(T, usize)>
+    where
+        F: Fn(&'a TReader) -> Fut,
+        Fut: Future<Output = ReaderClientResult<T>>,
+    {
+        let active = self.active_endpoint.load(Ordering::Relaxed);
+        let indices =
+            std::iter::once(active).chain((0..self.endpoints.len()).filter(|i| *i != active));
+        let mut first_err = None;
+        for index in indices {
+            let endpoint = &self.endpoints[index];
+            match request(&endpoint.reader).await {
+                Ok(response) => {
+                    if index != active {
+                        self.set_active_endpoint(index, "the previous endpoint failed");
+                    }
+                    return Ok((response, index));
+                }
+                Err(err) if is_endpoint_failure(&err) => {
+                    warn!("Request to the feeder gateway at {} failed: {err}.", endpoint.url);
+                    first_err.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(first_err.expect("There should be an error from every endpoint."))
+    }
+
+    // Compares the hash of the block to its hash in the first other endpoint that has the block.
+    // The block is accepted if no other endpoint has it.
+    async fn verify_block_hash(
+        &self,
+        served_by: usize,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> ReaderClientResult<()> {
+        if !self.verify_block_hashes {
+            return Ok(());
+        }
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            if index == served_by {
+                continue;
+            }
+            match endpoint.reader.block(block_number).await {
+                Ok(Some(block)) if block.block_hash == block_hash => return Ok(()),
+                Ok(Some(block)) => {
+                    return Err(ReaderClientError::InconsistentBlockHash {
+                        block_number,
+                        block_hash,
+                        url: self.endpoints[served_by].url.clone(),
+                        other_block_hash: block.block_hash,
+                        other_url: endpoint.url.clone(),
+                    });
+                }
+                Ok(None) => {}
+                Err(err) => debug!(
+                    "Failed verifying the hash of block {block_number} with {}: {err}.",
+                    endpoint.url
+                ),
+            }
+        }
+        debug!("No other endpoint has block {block_number}, accepting it without verification.");
+        Ok(())
+    }
+}
+
+// Whether the error means that the endpoint is unavailable or misbehaves, rather than an error
+// that every endpoint would return.
+fn is_endpoint_failure(err: &ReaderClientError) -> bool {
+    matches!(
+        err,
+        ReaderClientError::BadResponseStatus { .. }
+            | ReaderClientError::RequestError(_)
+            | ReaderClientError::RetryError { .. }
+            | ReaderClientError::SerdeError(_)
+            | ReaderClientError::IoError(_)
+            | ReaderClientError::HealthCheckTimeout { .. }
+    )
+}
+
+#[async_trait]
+impl<TReader: StarknetReader + Send + Sync> StarknetReader for FailoverReader<TReader> {
+    // Checks the health of all the endpoints, and returns the latest block number of the first
+    // healthy endpoint that doesn't lag, which becomes the active endpoint.
+    async fn block_number(&self) -> ReaderClientResult<Option<BlockNumber>> {
+        if self.endpoints.len() == 1 {
+            return self.endpoints[0].reader.block_number().await;
+        }
+        // The requests of an endpoint are retried until they succeed or the retries run out, so
+        // without a timeout an unavailable endpoint would delay the check by the whole backoff.
+        let responses = join_all(self.endpoints.iter().map(|endpoint| async {
+            tokio::time::timeout(self.health_check_timeout, endpoint.reader.block_number())
+                .await
+                .unwrap_or_else(|_| {
+                    Err(ReaderClientError::HealthCheckTimeout {
+                        url: endpoint.url.clone(),
+                        timeout: self.health_check_timeout,
+                    })
+                })
+        }))
+        .await;
+        // The number of blocks of each endpoint that responded.
+        let heights = responses
+            .iter()
+            .map(|response| {
+                response.as_ref().ok().map(|block_number| block_number.map_or(0, |bn| bn.0 + 1))
+            })
+            .collect::<Vec<_>>();
+        let Some(max_height) = heights.iter().flatten().max().copied() else {
+            let active = self.active_endpoint.load(Ordering::Relaxed);
+            return responses.into_iter().nth(active).expect("The active endpoint should exist.");
+        };
+        for (index, (response, height)) in responses.into_iter().zip(heights).enumerate() {
+            let endpoint = &self.endpoints[index];
+            match (response, height) {
+                (Ok(block_number), Some(height)) if max_height - height <= self.max_lag_blocks => {
+                    self.set_active_endpoint(index, "it is the first healthy endpoint");
+                    return Ok(block_number);
+                }
+                (Ok(_), Some(height)) => debug!(
+                    "The feeder gateway at {} lags {} blocks behind.",
+                    endpoint.url,
+                    max_height - height
+                ),
+                (Err(err), _) => {
+                    warn!("Health check of the feeder gateway at {} failed: {err}.", endpoint.url)
+                }
+                (Ok(_), None) => unreachable!("Every successful response has a height."),
+            }
+        }
+        unreachable!("The endpoint with the most blocks doesn't lag.")
+    }
+
+    async fn block(&self, block_number: BlockNumber) -> ReaderClientResult<Option<Block>> {
+        let (block, served_by) = self.read(|reader| reader.block(block_number)).await?;
+        if let Some(block) = &block {
+            self.verify_block_hash(served_by, block_number, block.block_hash).await?;
+        }
+        Ok(block)
+    }
+
+    async fn class_by_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> ReaderClientResult<Option<GenericContractClass>> {
+        Ok(self.read(|reader| reader.class_by_hash(class_hash)).await?.0)
+    }
+
+    async fn compiled_class_by_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> ReaderClientResult<Option<CasmContractClass>> {
+        Ok(self.read(|reader| reader.compiled_class_by_hash(class_hash)).await?.0)
+    }
+
+    async fn state_update(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdate>> {
+        let (state_update, served_by) =
+            self.read(|reader| reader.state_update(block_number)).await?;
+        if let Some(state_update) = &state_update {
+            self.verify_block_hash(served_by, block_number, state_update.block_hash).await?;
+        }
+        Ok(state_update)
+    }
+
+    async fn pending_data(&self) -> ReaderClientResult<Option<PendingData>> {
+        Ok(self.read(|reader| reader.pending_data()).await?.0)
+    }
+
+    async fn block_by_hash(&self, block_hash: BlockHash) -> ReaderClientResult<Option<Block>> {
+        Ok(self.read(|reader| reader.block_by_hash(block_hash)).await?.0)
+    }
+
+    async fn pending_block(&self) -> ReaderClientResult<Option<PendingBlock>> {
+        Ok(self.read(|reader| reader.pending_block()).await?.0)
+    }
+
+    async fn transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionInfo>> {
+        Ok(self.read(|reader| reader.transaction(transaction_hash)).await?.0)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionReceiptInfo>> {
+        Ok(self.read(|reader| reader.transaction_receipt(transaction_hash)).await?.0)
+    }
+
+    async fn transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<TransactionStatusInfo> {
+        Ok(self.read(|reader| reader.transaction_status(transaction_hash)).await?.0)
+    }
+
+    async fn block_signature(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<BlockSignatureData>> {
+        Ok(self.read(|reader| reader.block_signature(block_number)).await?.0)
+    }
+
+    async fn public_key(&self) -> ReaderClientResult<StarkFelt> {
+        Ok(self.read(|reader| reader.public_key()).await?.0)
+    }
+
+    async fn contract_addresses(&self) -> ReaderClientResult<ContractAddresses> {
+        Ok(self.read(|reader| reader.contract_addresses()).await?.0)
+    }
+
+    async fn state_update_with_block(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdateWithBlock>> {
+        let (state_update_with_block, served_by) =
+            self.read(|reader| reader.state_update_with_block(block_number)).await?;
+        if let Some(state_update_with_block) = &state_update_with_block {
+            let block_hash = state_update_with_block.block.block_hash;
+            self.verify_block_hash(served_by, block_number, block_hash).await?;
+        }
+        Ok(state_update_with_block)
+    }
+
+    async fn storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StarkFelt>> {
+        Ok(self.read(|reader| reader.storage_at(contract_address, key, block_number)).await?.0)
+    }
+}
@@ -0,0 +1,157 @@
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use assert_matches::assert_matches;
+use mockito::mock;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+
+use super::{Endpoint, FailoverReader};
+use crate::reader::{
+    Block, MockStarknetReader, ReaderClientError, StarknetFeederGatewayClient, StarknetReader,
+};
+use crate::retry::RetryConfig;
+use crate::test_utils::read_resource::read_resource_file;
+use crate::test_utils::retry::get_test_config;
+use crate::RetryErrorCode;
+
+const PRIMARY_URL: &str = "https://primary.url";
+const FALLBACK_URL: &str = "https://fallback.url";
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_millis(200);
+const NODE_VERSION: &str = "NODE VERSION";
+
+fn endpoints(
+    primary: MockStarknetReader,
+    fallback: MockStarknetReader,
+) -> Vec<Endpoint<MockStarknetReader>> {
+    vec![
+        Endpoint { url: PRIMARY_URL.to_owned(), reader: primary },
+        Endpoint { url: FALLBACK_URL.to_owned(), reader: fallback },
+    ]
+}
+
+fn unavailable() -> ReaderClientError {
+    ReaderClientError::RetryError {
+        code: RetryErrorCode::ServiceUnavailable,
+        message: "Service unavailable.".to_owned(),
+    }
+}
+
+fn block(block_hash: BlockHash) -> Block {
+    Block { block_hash, ..Default::default() }
+}
+
+#[tokio::test]
+async fn fail_over_on_error() {
+    let block_hash = BlockHash(stark_felt!("0x1"));
+    let mut primary = MockStarknetReader::new();
+    primary.expect_block().times(1).returning(|_| Err(unavailable()));
+    let mut fallback = MockStarknetReader::new();
+    fallback.expect_block().times(2).returning(move |_| Ok(Some(block(block_hash))));
+
+    let reader = FailoverReader::new(endpoints(primary, fallback), 10, false, HEALTH_CHECK_TIMEOUT);
+    assert_eq!(reader.block(BlockNumber(0)).await.unwrap(), Some(block(block_hash)));
+    // The fallback became the active endpoint, so the primary isn't requested again.
+    assert_eq!(reader.block(BlockNumber(0)).await.unwrap(), Some(block(block_hash)));
+}
+
+#[tokio::test]
+async fn error_of_every_endpoint() {
+    let mut primary = MockStarknetReader::new();
+    primary.expect_block().times(1).returning(|_| Err(unavailable()));
+    let mut fallback = MockStarknetReader::new();
+    fallback.expect_block().times(1).returning(|_| Err(unavailable()));
+
+    let reader = FailoverReader::new(endpoints(primary, fallback), 10, false, HEALTH_CHECK_TIMEOUT);
+    let err = reader.block(BlockNumber(0)).await.unwrap_err();
+    assert_matches!(err, ReaderClientError::RetryError { .. });
+}
+
+#[tokio::test]
+async fn fail_over_on_lag_and_back() {
+    let mut primary = MockStarknetReader::new();
+    let mut primary_block_numbers = [Ok(Some(BlockNumber(100))), Err(unavailable())].into_iter();
+    primary
+        .expect_block_number()
+        .times(3)
+        .returning(move || primary_block_numbers.next().unwrap_or(Ok(Some(BlockNumber(115)))));
+    primary.expect_block().times(1).returning(|_| Ok(None));
+    let mut fallback = MockStarknetReader::new();
+    fallback.expect_block_number().times(3).returning(|| Ok(Some(BlockNumber(120))));
+    fallback.expect_block().times(2).returning(|_| Ok(None));
+
+    let reader = FailoverReader::new(endpoints(primary, fallback), 10, false, HEALTH_CHECK_TIMEOUT);
+    // The primary lags, and then fails.
+    for _ in 0..2 {
+        assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(120)));
+        reader.block(BlockNumber(0)).await.unwrap();
+    }
+    // The primary recovered.
+    assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(115)));
+    reader.block(BlockNumber(0)).await.unwrap();
+}
+
+#[tokio::test]
+async fn verify_block_hashes() {
+    let block_hash = BlockHash(stark_felt!("0x1"));
+    let other_block_hash = BlockHash(stark_felt!("0x2"));
+    let mut primary = MockStarknetReader::new();
+    primary.expect_block().times(3).returning(move |_| Ok(Some(block(block_hash))));
+    let mut fallback = MockStarknetReader::new();
+    let mut fallback_blocks =
+        [None, Some(block(block_hash)), Some(block(other_block_hash))].into_iter();
+    fallback.expect_block().times(3).returning(move |_| Ok(fallback_blocks.next().unwrap()));
+
+    let reader = FailoverReader::new(endpoints(primary, fallback), 10, true, HEALTH_CHECK_TIMEOUT);
+    // A block that no other endpoint has yet is accepted.
+    assert_eq!(reader.block(BlockNumber(0)).await.unwrap(), Some(block(block_hash)));
+    assert_eq!(reader.block(BlockNumber(0)).await.unwrap(), Some(block(block_hash)));
+    let err = reader.block(BlockNumber(0)).await.unwrap_err();
+    assert_matches!(
+        err,
+        ReaderClientError::InconsistentBlockHash { block_number, block_hash: hash, url, other_block_hash: other_hash, other_url }
+        if block_number == BlockNumber(0) && hash == block_hash && url == PRIMARY_URL
+            && other_hash == other_block_hash && other_url == FALLBACK_URL
+    );
+}
+
+// A fallback that never responds and a fallback that is unavailable for longer than its retries
+// take don't delay the latest block number of the primary beyond the health check timeout.
+#[tokio::test]
+async fn health_check_doesnt_wait_for_unresponsive_endpoints() {
+    let primary_url = format!("{}/health_check_primary/", mockito::server_url());
+    let primary_mock =
+        mock("GET", "/health_check_primary/feeder_gateway/get_block?blockNumber=latest")
+            .with_status(200)
+            .with_body(read_resource_file("reader/block.json"))
+            .create();
+    // The connection is accepted by the backlog of the listener, but the request is never read.
+    let hanging_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let hanging_url = format!("http://{}/", hanging_listener.local_addr().unwrap());
+    let unavailable_url = format!("{}/health_check_unavailable/", mockito::server_url());
+    let _unavailable_mock =
+        mock("GET", "/health_check_unavailable/feeder_gateway/get_block?blockNumber=latest")
+            .with_status(503)
+            .create();
+    let slow_retry_config =
+        RetryConfig { retry_base_millis: 1000, retry_max_delay_millis: 60000, ..get_test_config() };
+    let endpoints = [
+        (primary_url, get_test_config()),
+        (hanging_url, get_test_config()),
+        (unavailable_url, slow_retry_config),
+    ]
+    .into_iter()
+    .map(|(url, retry_config)| Endpoint {
+        reader: StarknetFeederGatewayClient::new(&url, None, NODE_VERSION, retry_config).unwrap(),
+        url,
+    })
+    .collect();
+    let reader = FailoverReader::new(endpoints, 10, false, HEALTH_CHECK_TIMEOUT);
+
+    let start = Instant::now();
+    assert_eq!(reader.block_number().await.unwrap(), Some(BlockNumber(273466)));
+    assert!(start.elapsed() < HEALTH_CHECK_TIMEOUT * 5, "{:?}", start.elapsed());
+    primary_mock.assert();
+}
@@ -2,36 +2,55 @@
 //!
 //! [`Starknet`]: https://starknet.io/
 
+mod failover;
 mod objects;
 #[cfg(test)]
 mod starknet_feeder_gateway_client_test;
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 #[cfg(any(feature = "testing", test))]
 use mockall::automock;
+use num_bigint::BigUint;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::BlockNumber;
-use starknet_api::core::ClassHash;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ClassHash, ContractAddress};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
 use starknet_api::transaction::TransactionHash;
 use starknet_api::StarknetApiError;
 use tracing::{debug, instrument};
 use url::Url;
 
-pub use crate::reader::objects::block::{Block, GlobalRoot, TransactionReceiptsError};
+pub use crate::reader::failover::{Endpoint, FailoverReader};
+pub use crate::reader::objects::block::{
+    Block, BlockSignatureData, BlockSignatureInput, BlockStatus, ContractAddresses, GlobalRoot,
+    TransactionReceiptsError,
+};
+pub use crate::reader::objects::pending_data::{PendingBlock, PendingData, PendingStateUpdate};
 pub use crate::reader::objects::state::{
     ContractClass, DeclaredClassHashEntry, DeployedContract, ReplacedClass, StateDiff, StateUpdate,
-    StorageEntry,
+    StateUpdateWithBlock, StorageEntry,
+};
+pub use crate::reader::objects::transaction::{
+    BuiltinInstanceCounter, DeployAccountTransaction, DeployTransaction, ExecutionResources,
+    IntermediateDeclareTransaction, IntermediateInvokeTransaction, L1HandlerTransaction,
+    L1ToL2Message, L1ToL2Nonce, L2ToL1Message, Transaction, TransactionBlockInfo,
+    TransactionFailureReason, TransactionFinalityStatus, TransactionInfo, TransactionReceipt,
+    TransactionReceiptInfo, TransactionStatus, TransactionStatusInfo,
 };
-#[cfg(doc)]
-pub use crate::reader::objects::transaction::TransactionReceipt;
+use crate::request_limiter::RequestLimiter;
 use crate::retry::RetryConfig;
 use crate::{
-    ClientCreationError, ClientError, RetryErrorCode, StarknetClient, StarknetError,
-    StarknetErrorCode, StatusCode,
+    ClientCreationError, ClientError, HttpClientConfig, RetryErrorCode, StarknetClient,
+    StarknetError, StarknetErrorCode, StatusCode,
 };
 
 /// Errors that may be returned from a reader client.
@@ -54,6 +73,41 @@ pub enum ReaderClientError {
     /// A client error representing errors returned by the starknet client.
     #[error(transparent)]
     StarknetError(#[from] StarknetError),
+    /// A client error representing errors reading local files.
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    /// A client error representing a request that the reader doesn't support.
+    #[error("The {} request is not supported by the {}.", request, reader)]
+    Unsupported { request: String, reader: String },
+    /// A client error representing a request with no recorded response to replay.
+    #[error("No recorded response to {} request {}.", method, path)]
+    MissingRecording { method: String, path: String },
+    /// A client error representing a response larger than the maximal response size.
+    #[error("The response is larger than the maximal response size of {} bytes.", max_size)]
+    ResponseTooLarge { max_size: usize },
+    /// A client error representing an endpoint that didn't respond to a health check in time.
+    #[error(
+        "The feeder gateway at {} didn't respond to the health check within {:?}.",
+        url,
+        timeout
+    )]
+    HealthCheckTimeout { url: String, timeout: Duration },
+    /// A client error representing endpoints that returned different hashes for the same block.
+    #[error(
+        "Block {} has hash {} in {} but hash {} in {}.",
+        block_number,
+        block_hash,
+        url,
+        other_block_hash,
+        other_url
+    )]
+    InconsistentBlockHash {
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        url: String,
+        other_block_hash: BlockHash,
+        other_url: String,
+    },
     /// A client error representing errors from [`starknet_api`].
     #[error(transparent)]
     StarknetApiError(#[from] StarknetApiError),
@@ -93,6 +147,58 @@ pub trait StarknetReader {
         &self,
         block_number: BlockNumber,
     ) -> ReaderClientResult<Option<StateUpdate>>;
+    /// Returns the [`PendingData`] of the block that is currently being constructed, returning
+    /// [`None`] in case there is no pending block.
+    async fn pending_data(&self) -> ReaderClientResult<Option<PendingData>>;
+    /// Returns a [`Block`] corresponding to `block_hash`, returning [`None`] in case no such block
+    /// exists in the system.
+    async fn block_by_hash(&self, block_hash: BlockHash) -> ReaderClientResult<Option<Block>>;
+    /// Returns the [`PendingBlock`] that is currently being constructed, returning [`None`] in
+    /// case there is no pending block.
+    async fn pending_block(&self) -> ReaderClientResult<Option<PendingBlock>>;
+    /// Returns a [`TransactionInfo`] corresponding to `transaction_hash`, returning [`None`] in
+    /// case the transaction wasn't received by the system.
+    async fn transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionInfo>>;
+    /// Returns a [`TransactionReceiptInfo`] corresponding to `transaction_hash`, returning
+    /// [`None`] in case the transaction wasn't received by the system.
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionReceiptInfo>>;
+    /// Returns the [`TransactionStatusInfo`] of `transaction_hash`. The status is
+    /// [`TransactionStatus::NotReceived`] in case the transaction wasn't received by the system.
+    async fn transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<TransactionStatusInfo>;
+    /// Returns the [`BlockSignatureData`] of `block_number`, returning [`None`] in case no such
+    /// block exists in the system.
+    async fn block_signature(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<BlockSignatureData>>;
+    /// Returns the public key that block signatures are verified with.
+    async fn public_key(&self) -> ReaderClientResult<StarkFelt>;
+    /// Returns the [`ContractAddresses`] of the Starknet contracts on L1.
+    async fn contract_addresses(&self) -> ReaderClientResult<ContractAddresses>;
+    /// Returns a [`StateUpdateWithBlock`] corresponding to `block_number`, returning [`None`] in
+    /// case no such block exists in the system.
+    async fn state_update_with_block(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdateWithBlock>>;
+    /// Returns the value of `key` in the storage of `contract_address` after `block_number`,
+    /// returning [`None`] in case no such block exists in the system. The value of keys that
+    /// weren't written is zero.
+    async fn storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StarkFelt>>;
 }
 
 /// A client for the [`Starknet`] feeder gateway.
@@ -109,6 +215,13 @@ struct StarknetUrls {
     get_contract_by_hash: Url,
     get_compiled_class_by_class_hash: Url,
     get_state_update: Url,
+    get_transaction: Url,
+    get_transaction_receipt: Url,
+    get_transaction_status: Url,
+    get_signature: Url,
+    get_public_key: Url,
+    get_contract_addresses: Url,
+    get_storage_at: Url,
 }
 
 const GET_BLOCK_URL: &str = "feeder_gateway/get_block";
@@ -116,8 +229,21 @@ const GET_CONTRACT_BY_HASH_URL: &str = "feeder_gateway/get_class_by_hash";
 const GET_COMPILED_CLASS_BY_CLASS_HASH_URL: &str =
     "feeder_gateway/get_compiled_class_by_class_hash";
 const GET_STATE_UPDATE_URL: &str = "feeder_gateway/get_state_update";
+const GET_TRANSACTION_URL: &str = "feeder_gateway/get_transaction";
+const GET_TRANSACTION_RECEIPT_URL: &str = "feeder_gateway/get_transaction_receipt";
+const GET_TRANSACTION_STATUS_URL: &str = "feeder_gateway/get_transaction_status";
+const GET_SIGNATURE_URL: &str = "feeder_gateway/get_signature";
+const GET_PUBLIC_KEY_URL: &str = "feeder_gateway/get_public_key";
+const GET_CONTRACT_ADDRESSES_URL: &str = "feeder_gateway/get_contract_addresses";
+const GET_STORAGE_AT_URL: &str = "feeder_gateway/get_storage_at";
 const BLOCK_NUMBER_QUERY: &str = "blockNumber";
+const BLOCK_HASH_QUERY: &str = "blockHash";
+const TRANSACTION_HASH_QUERY: &str = "transactionHash";
+const INCLUDE_BLOCK_QUERY: &str = "includeBlock";
+const CONTRACT_ADDRESS_QUERY: &str = "contractAddress";
+const KEY_QUERY: &str = "key";
 const LATEST_BLOCK_NUMBER: &str = "latest";
+const PENDING_BLOCK_NUMBER: &str = "pending";
 const CLASS_HASH_QUERY: &str = "classHash";
 
 impl StarknetUrls {
@@ -129,6 +255,13 @@ impl StarknetUrls {
             get_compiled_class_by_class_hash: base_url
                 .join(GET_COMPILED_CLASS_BY_CLASS_HASH_URL)?,
             get_state_update: base_url.join(GET_STATE_UPDATE_URL)?,
+            get_transaction: base_url.join(GET_TRANSACTION_URL)?,
+            get_transaction_receipt: base_url.join(GET_TRANSACTION_RECEIPT_URL)?,
+            get_transaction_status: base_url.join(GET_TRANSACTION_STATUS_URL)?,
+            get_signature: base_url.join(GET_SIGNATURE_URL)?,
+            get_public_key: base_url.join(GET_PUBLIC_KEY_URL)?,
+            get_contract_addresses: base_url.join(GET_CONTRACT_ADDRESSES_URL)?,
+            get_storage_at: base_url.join(GET_STORAGE_AT_URL)?,
         })
     }
 }
@@ -146,6 +279,42 @@ impl StarknetFeederGatewayClient {
         })
     }
 
+    /// Creates a client that sends no requests, and serves the responses recorded in
+    /// `recordings_dir` by a client for a feeder gateway with the same URL path as `url_str`.
+    pub fn from_recordings(
+        url_str: &str,
+        recordings_dir: &Path,
+        retry_config: RetryConfig,
+    ) -> Result<Self, ClientCreationError> {
+        Ok(StarknetFeederGatewayClient {
+            urls: StarknetUrls::new(url_str)?,
+            client: StarknetClient::from_recordings(recordings_dir, retry_config)?,
+        })
+    }
+
+    /// Records every request of the client and its response to `recordings_dir`. See
+    /// [`recording`](crate::recording).
+    pub fn with_recording(mut self, recordings_dir: &Path) -> Result<Self, ClientCreationError> {
+        self.client.record_to(recordings_dir)?;
+        Ok(self)
+    }
+
+    /// Sends the requests of the client with an HTTP client built from the given configuration.
+    pub fn with_http_client_config(
+        mut self,
+        config: &HttpClientConfig,
+    ) -> Result<Self, ClientCreationError> {
+        self.client.set_http_client_config(config)?;
+        Ok(self)
+    }
+
+    /// Limits the requests of the client with the given request limiter, which may be shared with
+    /// other clients.
+    pub fn with_request_limiter(mut self, request_limiter: Arc<RequestLimiter>) -> Self {
+        self.client.request_limiter = Some(request_limiter);
+        self
+    }
+
     async fn request_with_retry_url(&self, url: Url) -> ReaderClientResult<String> {
         self.client
             .request_with_retry(self.client.internal_client.get(url))
@@ -153,6 +322,18 @@ impl StarknetFeederGatewayClient {
             .map_err(Into::<ReaderClientError>::into)
     }
 
+    // Requests the object at `url`, deserializing it while the response is downloaded. Used for
+    // objects that might be large, such as classes.
+    async fn request_object_with_retry_url<T: DeserializeOwned + Send + 'static>(
+        &self,
+        url: Url,
+    ) -> ReaderClientResult<T> {
+        self.client
+            .request_object_with_retry(self.client.internal_client.get(url))
+            .await
+            .map_err(Into::<ReaderClientError>::into)
+    }
+
     async fn request_block(
         &self,
         block_number: Option<BlockNumber>,
@@ -169,6 +350,23 @@ impl StarknetFeederGatewayClient {
             format!("Failed to get block number {:?} from starknet server.", block_number),
         )
     }
+
+    // Requests an object of the transaction from `url`, returning the block info of the
+    // transaction along with the raw object, or None if the transaction wasn't received.
+    async fn request_transaction_object(
+        &self,
+        mut url: Url,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<(TransactionBlockInfo, serde_json::Value)>> {
+        url.query_pairs_mut().append_pair(TRANSACTION_HASH_QUERY, &query_value(&transaction_hash)?);
+        let raw_object = self.request_with_retry_url(url).await?;
+        let object: serde_json::Value = serde_json::from_str(&raw_object)?;
+        let block_info: TransactionBlockInfo = serde_json::from_value(object.clone())?;
+        if block_info.status == TransactionStatus::NotReceived {
+            return Ok(None);
+        }
+        Ok(Some((block_info, object)))
+    }
 }
 
 #[async_trait]
@@ -192,8 +390,8 @@ impl StarknetReader for StarknetFeederGatewayClient {
         let class_hash = serde_json::to_string(&class_hash)?;
         url.query_pairs_mut()
             .append_pair(CLASS_HASH_QUERY, &class_hash.as_str()[1..class_hash.len() - 1]);
-        let response = self.request_with_retry_url(url).await;
-        load_object_from_response(
+        let response = self.request_object_with_retry_url(url).await;
+        object_or_none(
             response,
             StarknetErrorCode::UndeclaredClass,
             format!("Failed to get class with hash {:?} from starknet server.", class_hash),
@@ -226,6 +424,29 @@ impl StarknetReader for StarknetFeederGatewayClient {
         })
     }
 
+    #[instrument(skip(self), level = "warn")]
+    async fn pending_data(&self) -> ReaderClientResult<Option<PendingData>> {
+        // The block is requested together with the state update, so both are of the same pending
+        // block.
+        let mut url = self.urls.get_state_update.clone();
+        url.query_pairs_mut()
+            .append_pair(BLOCK_NUMBER_QUERY, PENDING_BLOCK_NUMBER)
+            .append_pair(INCLUDE_BLOCK_QUERY, "true");
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            "Failed to get the pending data from starknet server.".to_string(),
+        )
+        .map(|option| {
+            option.map(|mut pending_data: PendingData| {
+                // Same as in state_update, remove empty storage diffs.
+                pending_data.state_update.state_diff.storage_diffs.retain(|_k, v| !v.is_empty());
+                pending_data
+            })
+        })
+    }
+
     #[instrument(skip(self), level = "warn")]
     async fn compiled_class_by_hash(
         &self,
@@ -277,8 +498,8 @@ impl StarknetReader for StarknetFeederGatewayClient {
         let class_hash = serde_json::to_string(&class_hash)?;
         url.query_pairs_mut()
             .append_pair(CLASS_HASH_QUERY, &class_hash.as_str()[1..class_hash.len() - 1]);
-        let response = self.request_with_retry_url(url).await;
-        load_object_from_response(
+        let response = self.request_object_with_retry_url(url).await;
+        object_or_none(
             response,
             StarknetErrorCode::UndeclaredClass,
             format!(
@@ -287,6 +508,158 @@ impl StarknetReader for StarknetFeederGatewayClient {
             ),
         )
     }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn block_by_hash(&self, block_hash: BlockHash) -> ReaderClientResult<Option<Block>> {
+        let mut url = self.urls.get_block.clone();
+        url.query_pairs_mut().append_pair(BLOCK_HASH_QUERY, &query_value(&block_hash)?);
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            format!("Failed to get block with hash {} from starknet server.", block_hash),
+        )
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn pending_block(&self) -> ReaderClientResult<Option<PendingBlock>> {
+        let mut url = self.urls.get_block.clone();
+        url.query_pairs_mut().append_pair(BLOCK_NUMBER_QUERY, PENDING_BLOCK_NUMBER);
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            "Failed to get the pending block from starknet server.".to_string(),
+        )
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionInfo>> {
+        let Some((block_info, mut object)) = self
+            .request_transaction_object(self.urls.get_transaction.clone(), transaction_hash)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let transaction = serde_json::from_value(object["transaction"].take())?;
+        Ok(Some(TransactionInfo { block_info, transaction }))
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<Option<TransactionReceiptInfo>> {
+        let Some((block_info, object)) = self
+            .request_transaction_object(self.urls.get_transaction_receipt.clone(), transaction_hash)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let receipt = serde_json::from_value(object)?;
+        Ok(Some(TransactionReceiptInfo { block_info, receipt }))
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> ReaderClientResult<TransactionStatusInfo> {
+        let mut url = self.urls.get_transaction_status.clone();
+        url.query_pairs_mut().append_pair(TRANSACTION_HASH_QUERY, &query_value(&transaction_hash)?);
+        Ok(serde_json::from_str(&self.request_with_retry_url(url).await?)?)
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn block_signature(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<BlockSignatureData>> {
+        let mut url = self.urls.get_signature.clone();
+        url.query_pairs_mut().append_pair(BLOCK_NUMBER_QUERY, &block_number.to_string());
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            format!(
+                "Failed to get the signature of block number {block_number} from starknet server."
+            ),
+        )
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn public_key(&self) -> ReaderClientResult<StarkFelt> {
+        let url = self.urls.get_public_key.clone();
+        Ok(serde_json::from_str(&self.request_with_retry_url(url).await?)?)
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn contract_addresses(&self) -> ReaderClientResult<ContractAddresses> {
+        let url = self.urls.get_contract_addresses.clone();
+        Ok(serde_json::from_str(&self.request_with_retry_url(url).await?)?)
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn state_update_with_block(
+        &self,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StateUpdateWithBlock>> {
+        let mut url = self.urls.get_state_update.clone();
+        url.query_pairs_mut()
+            .append_pair(BLOCK_NUMBER_QUERY, &block_number.to_string())
+            .append_pair(INCLUDE_BLOCK_QUERY, "true");
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            format!(
+                "Failed to get state update with block for block number {} from starknet server.",
+                block_number
+            ),
+        )
+        .map(|option| {
+            option.map(|mut state_update_with_block: StateUpdateWithBlock| {
+                // Same as in state_update, remove empty storage diffs.
+                state_update_with_block
+                    .state_update
+                    .state_diff
+                    .storage_diffs
+                    .retain(|_k, v| !v.is_empty());
+                state_update_with_block
+            })
+        })
+    }
+
+    #[instrument(skip(self), level = "warn")]
+    async fn storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        block_number: BlockNumber,
+    ) -> ReaderClientResult<Option<StarkFelt>> {
+        let mut url = self.urls.get_storage_at.clone();
+        // The gateway expects the key as a decimal number.
+        let key = BigUint::from_bytes_be(key.0.key().bytes()).to_string();
+        url.query_pairs_mut()
+            .append_pair(CONTRACT_ADDRESS_QUERY, &query_value(&contract_address)?)
+            .append_pair(KEY_QUERY, &key)
+            .append_pair(BLOCK_NUMBER_QUERY, &block_number.to_string());
+        let response = self.request_with_retry_url(url).await;
+        load_object_from_response(
+            response,
+            StarknetErrorCode::BlockNotFound,
+            format!(
+                "Failed to get the storage of contract {} at key {} after block number {} from \
+                 starknet server.",
+                contract_address.0.key(),
+                key,
+                block_number
+            ),
+        )
+    }
 }
 
 /// Load an object from a json string response. If there was a StarknetError with
@@ -295,9 +668,22 @@ fn load_object_from_response<Object: for<'a> Deserialize<'a>>(
     response: ReaderClientResult<String>,
     none_error_code: StarknetErrorCode,
     error_message: String,
+) -> ReaderClientResult<Option<Object>> {
+    match object_or_none(response, none_error_code, error_message)? {
+        Some(raw_object) => Ok(Some(serde_json::from_str(&raw_object)?)),
+        None => Ok(None),
+    }
+}
+
+/// Returns the object of a response. If there was a StarknetError with `none_error_code`, return
+/// None. If there was a different error, log `error_message`.
+fn object_or_none<Object>(
+    response: ReaderClientResult<Object>,
+    none_error_code: StarknetErrorCode,
+    error_message: String,
 ) -> ReaderClientResult<Option<Object>> {
     match response {
-        Ok(raw_object) => Ok(Some(serde_json::from_str(&raw_object)?)),
+        Ok(object) => Ok(Some(object)),
         Err(ReaderClientError::StarknetError(StarknetError { code: error_code, message: _ }))
             if error_code == none_error_code =>
         {
@@ -310,10 +696,17 @@ fn load_object_from_response<Object: for<'a> Deserialize<'a>>(
     }
 }
 
+// Returns the value of a hash or an address as a query value, which is its serialization without
+// the quotes.
+fn query_value<T: Serialize>(value: &T) -> ReaderClientResult<String> {
+    let value = serde_json::to_string(value)?;
+    Ok(value.trim_matches('"').to_owned())
+}
+
 impl From<ClientError> for ReaderClientError {
     fn from(error: ClientError) -> Self {
         match error {
-            ClientError::BadResponseStatus { code, message } => {
+            ClientError::BadResponseStatus { code, message, .. } => {
                 ReaderClientError::BadResponseStatus { code, message }
             }
             ClientError::RequestError(err) => ReaderClientError::RequestError(err),
@@ -322,6 +715,16 @@ impl From<ClientError> for ReaderClientError {
             }
             ClientError::SerdeError(err) => ReaderClientError::SerdeError(err),
             ClientError::StarknetError(err) => ReaderClientError::StarknetError(err),
+            ClientError::MissingRecording { method, path } => {
+                ReaderClientError::MissingRecording { method, path }
+            }
+            ClientError::ResponseTooLarge { max_size } => {
+                ReaderClientError::ResponseTooLarge { max_size }
+            }
+            ClientError::CircuitBreakerOpen { .. } => ReaderClientError::RetryError {
+                code: RetryErrorCode::CircuitBreakerOpen,
+                message: error.to_string(),
+            },
         }
     }
 }
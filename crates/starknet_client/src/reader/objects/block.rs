@@ -5,11 +5,11 @@ use starknet_api::block::{
     Block as starknet_api_block, BlockHash, BlockNumber, BlockTimestamp, GasPrice,
 };
 use starknet_api::core::ContractAddress;
-use starknet_api::hash::StarkHash;
-use starknet_api::serde_utils::NonPrefixedBytesAsHex;
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::serde_utils::{hex_str_from_bytes, NonPrefixedBytesAsHex};
 #[cfg(doc)]
 use starknet_api::transaction::TransactionOutput as starknet_api_transaction_output;
-use starknet_api::transaction::{TransactionHash, TransactionOffsetInBlock};
+use starknet_api::transaction::{EthAddress, TransactionHash, TransactionOffsetInBlock};
 use starknet_api::StarknetApiError;
 
 use crate::reader::objects::transaction::{
@@ -17,9 +17,7 @@ use crate::reader::objects::transaction::{
 };
 use crate::reader::{ReaderClientError, ReaderClientResult};
 
-#[derive(
-    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
-)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, PartialOrd, Ord)]
 #[serde(try_from = "NonPrefixedBytesAsHex<32_usize>")]
 pub struct GlobalRoot(pub StarkHash);
 
@@ -31,6 +29,17 @@ impl TryFrom<NonPrefixedBytesAsHex<32_usize>> for GlobalRoot {
         Ok(Self(StarkHash::try_from(val)?))
     }
 }
+
+// Serialized the same way it's deserialized, so that the serialized objects can be read back.
+impl Serialize for GlobalRoot {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let bytes: [u8; 32] = self.0.bytes().try_into().expect("A StarkHash has 32 bytes.");
+        serializer.serialize_str(&hex_str_from_bytes::<32, false>(bytes))
+    }
+}
 impl From<GlobalRoot> for starknet_api::core::GlobalRoot {
     fn from(val: GlobalRoot) -> Self {
         Self(val.0)
@@ -248,3 +257,29 @@ impl From<BlockStatus> for starknet_api::block::BlockStatus {
         }
     }
 }
+
+/// The signature of a block as returned by the starknet gateway.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockSignatureData {
+    pub block_number: BlockNumber,
+    pub signature: Vec<StarkFelt>,
+    pub signature_input: BlockSignatureInput,
+}
+
+/// The data signed by the sequencer for a block.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockSignatureInput {
+    pub block_hash: BlockHash,
+    pub state_diff_commitment: StarkHash,
+}
+
+/// The addresses of the Starknet contracts on L1 as returned by the starknet gateway.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct ContractAddresses {
+    #[serde(rename = "Starknet")]
+    pub starknet: EthAddress,
+    #[serde(rename = "GpsStatementVerifier")]
+    pub gps_statement_verifier: EthAddress,
+}
@@ -1,6 +1,7 @@
 pub mod block;
 #[cfg(test)]
 mod block_test;
+pub mod pending_data;
 pub mod state;
 pub mod transaction;
 #[cfg(test)]
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockBody, BlockHash, BlockTimestamp, GasPrice};
+use starknet_api::core::ContractAddress;
+
+use crate::reader::objects::block::{Block, BlockStatus, GlobalRoot};
+use crate::reader::objects::state::StateDiff;
+use crate::reader::objects::transaction::{Transaction, TransactionReceipt};
+use crate::reader::ReaderClientResult;
+
+/// The block that is currently being constructed by the sequencer, as returned by the starknet
+/// gateway. Unlike [`Block`], it has no hash, number or state root yet.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PendingBlock {
+    pub parent_block_hash: BlockHash,
+    pub status: BlockStatus,
+    pub gas_price: GasPrice,
+    #[serde(default)]
+    pub timestamp: BlockTimestamp,
+    #[serde(default)]
+    pub sequencer_address: ContractAddress,
+    pub transactions: Vec<Transaction>,
+    pub transaction_receipts: Vec<TransactionReceipt>,
+    #[serde(default)]
+    pub starknet_version: String,
+}
+
+impl PendingBlock {
+    /// Converts the transactions and receipts of the pending block to a starknet_api
+    /// [`BlockBody`], with the same validations as for accepted blocks.
+    pub fn to_starknet_api_block_body(self) -> ReaderClientResult<BlockBody> {
+        let block = Block {
+            parent_block_hash: self.parent_block_hash,
+            status: self.status,
+            gas_price: self.gas_price,
+            timestamp: self.timestamp,
+            sequencer_address: self.sequencer_address,
+            transactions: self.transactions,
+            transaction_receipts: self.transaction_receipts,
+            starknet_version: self.starknet_version,
+            ..Block::default()
+        };
+        Ok(block.to_starknet_api_block_and_version()?.0.body)
+    }
+}
+
+/// The state update of the pending block, as returned by the starknet gateway.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PendingStateUpdate {
+    pub old_root: GlobalRoot,
+    pub state_diff: StateDiff,
+}
+
+/// The pending block together with its state update.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct PendingData {
+    pub block: PendingBlock,
+    pub state_update: PendingStateUpdate,
+}
@@ -7,7 +7,7 @@ use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::{EntryPoint, EntryPointType, StorageKey};
 
-use crate::reader::GlobalRoot;
+use crate::reader::{Block, GlobalRoot};
 
 /// A state update derived from a single block as returned by the starknet gateway.
 #[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
@@ -18,6 +18,14 @@ pub struct StateUpdate {
     pub state_diff: StateDiff,
 }
 
+/// A state update together with its block, as returned by the starknet gateway when the block is
+/// requested along with the state update.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct StateUpdateWithBlock {
+    pub block: Block,
+    pub state_update: StateUpdate,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
 pub struct StateDiff {
     // IndexMap is serialized as a mapping in json, keeps ordering and is efficiently iterable.
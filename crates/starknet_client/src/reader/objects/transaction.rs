@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::{
     ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector, Nonce,
 };
@@ -297,6 +298,106 @@ impl TryFrom<IntermediateInvokeTransaction> for starknet_api::transaction::Invok
     }
 }
 
+/// The status of a transaction as returned by the starknet gateway.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default,
+)]
+pub enum TransactionStatus {
+    #[serde(rename(deserialize = "NOT_RECEIVED", serialize = "NOT_RECEIVED"))]
+    #[default]
+    NotReceived,
+    #[serde(rename(deserialize = "RECEIVED", serialize = "RECEIVED"))]
+    Received,
+    #[serde(rename(deserialize = "PENDING", serialize = "PENDING"))]
+    Pending,
+    #[serde(rename(deserialize = "REJECTED", serialize = "REJECTED"))]
+    Rejected,
+    #[serde(rename(deserialize = "REVERTED", serialize = "REVERTED"))]
+    Reverted,
+    #[serde(rename(deserialize = "ABORTED", serialize = "ABORTED"))]
+    Aborted,
+    #[serde(rename(deserialize = "ACCEPTED_ON_L2", serialize = "ACCEPTED_ON_L2"))]
+    AcceptedOnL2,
+    #[serde(rename(deserialize = "ACCEPTED_ON_L1", serialize = "ACCEPTED_ON_L1"))]
+    AcceptedOnL1,
+}
+
+/// The finality status of a transaction as returned by the starknet gateway.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default,
+)]
+pub enum TransactionFinalityStatus {
+    #[serde(rename(deserialize = "NOT_RECEIVED", serialize = "NOT_RECEIVED"))]
+    #[default]
+    NotReceived,
+    #[serde(rename(deserialize = "RECEIVED", serialize = "RECEIVED"))]
+    Received,
+    #[serde(rename(deserialize = "ACCEPTED_ON_L2", serialize = "ACCEPTED_ON_L2"))]
+    AcceptedOnL2,
+    #[serde(rename(deserialize = "ACCEPTED_ON_L1", serialize = "ACCEPTED_ON_L1"))]
+    AcceptedOnL1,
+}
+
+/// The location of a transaction and its statuses as returned by the starknet gateway. The block
+/// fields are missing for transactions that aren't in a block yet, and the finality and execution
+/// statuses are missing in old versions of Starknet.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct TransactionBlockInfo {
+    pub status: TransactionStatus,
+    #[serde(default)]
+    pub finality_status: Option<TransactionFinalityStatus>,
+    #[serde(default)]
+    pub execution_status: Option<TransactionExecutionStatus>,
+    #[serde(default)]
+    pub block_hash: Option<BlockHash>,
+    #[serde(default)]
+    pub block_number: Option<BlockNumber>,
+    #[serde(default)]
+    pub transaction_index: Option<TransactionOffsetInBlock>,
+}
+
+/// A transaction with its location and statuses as returned by the starknet gateway.
+// The gateway returns the fields of the block info and the transaction in the same object. It's not
+// flattened with serde since flattening doesn't support arbitrary precision numbers.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TransactionInfo {
+    pub block_info: TransactionBlockInfo,
+    pub transaction: Transaction,
+}
+
+/// A transaction receipt with the location and statuses of its transaction as returned by the
+/// starknet gateway.
+// Same as in TransactionInfo, the fields are returned in the same object.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TransactionReceiptInfo {
+    pub block_info: TransactionBlockInfo,
+    pub receipt: TransactionReceipt,
+}
+
+/// The statuses of a transaction as returned by the starknet gateway.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct TransactionStatusInfo {
+    #[serde(rename = "tx_status")]
+    pub status: TransactionStatus,
+    #[serde(default)]
+    pub finality_status: Option<TransactionFinalityStatus>,
+    #[serde(default)]
+    pub execution_status: Option<TransactionExecutionStatus>,
+    #[serde(default)]
+    pub block_hash: Option<BlockHash>,
+    // Returned only for rejected transactions.
+    #[serde(default)]
+    pub tx_failure_reason: Option<TransactionFailureReason>,
+}
+
+/// The reason a transaction was rejected as returned by the starknet gateway.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+pub struct TransactionFailureReason {
+    pub code: String,
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
 pub struct TransactionReceipt {
     pub transaction_index: TransactionOffsetInBlock,
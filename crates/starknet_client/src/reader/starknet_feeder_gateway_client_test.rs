@@ -1,12 +1,16 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use assert_matches::assert_matches;
 use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 use indexmap::indexmap;
+use metrics_exporter_prometheus::PrometheusBuilder;
 use mockito::mock;
 use pretty_assertions::assert_eq;
+use prometheus_parse::Value::Counter;
 use reqwest::StatusCode;
-use starknet_api::block::BlockNumber;
+use serde_json::json;
+use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce, PatriciaKey};
 use starknet_api::deprecated_contract_class::{
     ContractClass as DeprecatedContractClass, ContractClassAbiEntry,
@@ -15,19 +19,29 @@ use starknet_api::deprecated_contract_class::{
     FunctionAbiEntryWithType, Program, TypedParameter,
 };
 use starknet_api::hash::{StarkFelt, StarkHash};
-use starknet_api::state::{EntryPoint, EntryPointType, FunctionIndex};
-use starknet_api::transaction::{Fee, TransactionHash, TransactionSignature, TransactionVersion};
+use starknet_api::state::{EntryPoint, EntryPointType, FunctionIndex, StorageKey};
+use starknet_api::transaction::{
+    Fee, TransactionExecutionStatus, TransactionHash, TransactionOffsetInBlock,
+    TransactionSignature, TransactionVersion,
+};
 use starknet_api::{patricia_key, stark_felt};
 
 use crate::reader::objects::state::StateUpdate;
 use crate::reader::objects::transaction::IntermediateDeclareTransaction;
 use crate::reader::{
-    Block, ContractClass, GenericContractClass, ReaderClientError, StarknetFeederGatewayClient,
-    StarknetReader, BLOCK_NUMBER_QUERY, CLASS_HASH_QUERY, GET_BLOCK_URL, GET_STATE_UPDATE_URL,
+    Block, BlockSignatureData, ContractClass, GenericContractClass, PendingBlock, PendingData,
+    PendingStateUpdate, ReaderClientError, StarknetFeederGatewayClient, StarknetReader,
+    StateUpdateWithBlock, Transaction, TransactionBlockInfo, TransactionFailureReason,
+    TransactionFinalityStatus, TransactionInfo, TransactionReceipt, TransactionStatus,
+    TransactionStatusInfo, BLOCK_HASH_QUERY, BLOCK_NUMBER_QUERY, CLASS_HASH_QUERY,
+    CONTRACT_ADDRESS_QUERY, GET_BLOCK_URL, GET_STATE_UPDATE_URL, INCLUDE_BLOCK_QUERY, KEY_QUERY,
+    PENDING_BLOCK_NUMBER, TRANSACTION_HASH_QUERY,
 };
+use crate::request_limiter::{RequestLimiter, RequestLimiterConfig};
+use crate::retry::RetryConfig;
 use crate::test_utils::read_resource::read_resource_file;
 use crate::test_utils::retry::get_test_config;
-use crate::RetryErrorCode;
+use crate::{HttpClientConfig, RetryErrorCode, DOWNLOADED_BYTES};
 
 const NODE_VERSION: &str = "NODE VERSION";
 
@@ -309,6 +323,43 @@ async fn get_block() {
     mock_no_block.assert();
     assert!(block.is_none());
 }
+#[tokio::test]
+async fn pending_data() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_block = read_resource_file("reader/pending_block.json");
+    let raw_state_update = read_resource_file("reader/pending_state_update.json");
+    let body = format!(r#"{{"block": {raw_block}, "state_update": {raw_state_update}}}"#);
+    let mock = mock(
+        "GET",
+        &format!(
+            "/feeder_gateway/get_state_update?{BLOCK_NUMBER_QUERY}={PENDING_BLOCK_NUMBER}&\
+             {INCLUDE_BLOCK_QUERY}=true"
+        )[..],
+    )
+    .with_status(200)
+    .with_body(body)
+    .create();
+    let pending_data = starknet_client.pending_data().await.unwrap().unwrap();
+    mock.assert();
+
+    let block: PendingBlock = serde_json::from_str(&raw_block).unwrap();
+    let mut state_update: PendingStateUpdate = serde_json::from_str(&raw_state_update).unwrap();
+    state_update.state_diff.storage_diffs.retain(|_k, v| !v.is_empty());
+    assert_eq!(pending_data, PendingData { block: block.clone(), state_update });
+
+    // The pending block has the same transactions as an accepted block.
+    let body = block.to_starknet_api_block_body().unwrap();
+    let accepted_block: Block =
+        serde_json::from_str(&read_resource_file("reader/block.json")).unwrap();
+    assert_eq!(body, accepted_block.to_starknet_api_block_and_version().unwrap().0.body);
+}
+
 #[tokio::test]
 async fn compiled_class_by_hash() {
     let starknet_client = StarknetFeederGatewayClient::new(
@@ -337,6 +388,82 @@ async fn compiled_class_by_hash() {
     assert_eq!(casm_contract_class, expected_casm_contract_class);
 }
 
+#[tokio::test]
+async fn compiled_class_by_hash_chunked_body() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_casm_contract_class = read_resource_file("reader/casm_contract_class.json");
+    let body = raw_casm_contract_class.clone();
+    // The body is sent in small chunks, so it's deserialized from many chunks.
+    let mock_casm_contract_class = mock(
+        "GET",
+        &format!("/feeder_gateway/get_compiled_class_by_class_hash?{CLASS_HASH_QUERY}=0x8")[..],
+    )
+    .with_status(200)
+    .with_body_from_fn(move |writer| {
+        for chunk in body.as_bytes().chunks(1000) {
+            writer.write_all(chunk)?;
+            writer.flush()?;
+        }
+        Ok(())
+    })
+    .create();
+    let casm_contract_class = starknet_client
+        .compiled_class_by_hash(ClassHash(stark_felt!("0x8")))
+        .await
+        .unwrap()
+        .unwrap();
+    mock_casm_contract_class.assert();
+    let expected_casm_contract_class: CasmContractClass =
+        serde_json::from_str(&raw_casm_contract_class).unwrap();
+    assert_eq!(casm_contract_class, expected_casm_contract_class);
+}
+
+#[tokio::test]
+async fn response_too_large() {
+    const MAX_RESPONSE_SIZE: usize = 1000;
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap()
+    .with_http_client_config(&HttpClientConfig {
+        max_response_size: Some(MAX_RESPONSE_SIZE),
+        ..Default::default()
+    })
+    .unwrap();
+    let body = read_resource_file("reader/casm_contract_class.json");
+    assert!(body.len() > MAX_RESPONSE_SIZE);
+
+    // A response with a content length.
+    let mock_casm_contract_class = mock(
+        "GET",
+        &format!("/feeder_gateway/get_compiled_class_by_class_hash?{CLASS_HASH_QUERY}=0x9")[..],
+    )
+    .with_status(200)
+    .with_body(&body)
+    .create();
+    let err = starknet_client.compiled_class_by_hash(ClassHash(stark_felt!("0x9"))).await;
+    mock_casm_contract_class.assert();
+    assert_matches!(err, Err(ReaderClientError::ResponseTooLarge { max_size: MAX_RESPONSE_SIZE }));
+
+    // A response without a content length, which is only known to be too large when it's read.
+    let mock_block = mock("GET", "/feeder_gateway/get_block?blockNumber=8765")
+        .with_status(200)
+        .with_body_from_fn(move |writer| writer.write_all(body.as_bytes()))
+        .create();
+    let err = starknet_client.block(BlockNumber(8765)).await;
+    mock_block.assert();
+    assert_matches!(err, Err(ReaderClientError::ResponseTooLarge { max_size: MAX_RESPONSE_SIZE }));
+}
+
 #[tokio::test]
 async fn block_unserializable() {
     let starknet_client = StarknetFeederGatewayClient::new(
@@ -383,6 +510,112 @@ async fn retry_error_codes() {
     }
 }
 
+#[tokio::test]
+async fn throttling_decreases_request_concurrency() {
+    let request_limiter = Arc::new(RequestLimiter::new(RequestLimiterConfig::default()));
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        RetryConfig { max_retries: 0, ..get_test_config() },
+    )
+    .unwrap()
+    .with_request_limiter(request_limiter.clone());
+    let mock =
+        mock("GET", &format!("/feeder_gateway/get_state_update?{BLOCK_NUMBER_QUERY}=98765")[..])
+            .with_status(StatusCode::TOO_MANY_REQUESTS.as_u16().into())
+            .create();
+    starknet_client.state_update(BlockNumber(98765)).await.unwrap_err();
+    mock.assert();
+    assert_eq!(
+        request_limiter.concurrency_limit(),
+        RequestLimiterConfig::default().max_concurrent_requests / 2
+    );
+}
+
+#[tokio::test]
+async fn retry_after_header() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        RetryConfig { max_retries: 1, retry_max_delay_millis: 2000, ..get_test_config() },
+    )
+    .unwrap();
+    let mock =
+        mock("GET", &format!("/feeder_gateway/get_state_update?{BLOCK_NUMBER_QUERY}=98766")[..])
+            .with_status(StatusCode::TOO_MANY_REQUESTS.as_u16().into())
+            .with_header("Retry-After", "1")
+            .expect(2)
+            .create();
+    let start = std::time::Instant::now();
+    let error = starknet_client.state_update(BlockNumber(98766)).await.unwrap_err();
+    assert_matches!(
+        error,
+        ReaderClientError::RetryError { code: RetryErrorCode::TooManyRequests, message: _ }
+    );
+    assert!(start.elapsed() >= std::time::Duration::from_secs(1));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn circuit_breaker_stops_requests() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        RetryConfig {
+            max_retries: 0,
+            circuit_breaker_failure_threshold: 2,
+            circuit_breaker_cooldown_millis: 60000,
+            ..get_test_config()
+        },
+    )
+    .unwrap();
+    let mock =
+        mock("GET", &format!("/feeder_gateway/get_state_update?{BLOCK_NUMBER_QUERY}=98767")[..])
+            .with_status(StatusCode::SERVICE_UNAVAILABLE.as_u16().into())
+            .expect(2)
+            .create();
+    for _ in 0..2 {
+        starknet_client.state_update(BlockNumber(98767)).await.unwrap_err();
+    }
+    // The circuit breaker is open, so the request isn't sent.
+    let error = starknet_client.state_update(BlockNumber(98767)).await.unwrap_err();
+    assert_matches!(
+        error,
+        ReaderClientError::RetryError { code: RetryErrorCode::CircuitBreakerOpen, message: _ }
+    );
+    mock.assert();
+}
+
+#[tokio::test]
+async fn downloaded_bytes_metric() {
+    let handle = PrometheusBuilder::new().install_recorder().unwrap();
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let body = read_resource_file("reader/block.json");
+    let mock_block = mock("GET", "/feeder_gateway/get_block?blockNumber=76543")
+        .with_status(200)
+        .with_body(&body)
+        .create();
+    starknet_client.block(BlockNumber(76543)).await.unwrap();
+    mock_block.assert();
+
+    // Other tests may download concurrently and increase the counter as well.
+    let Counter(downloaded_bytes) =
+        test_utils::prometheus_is_contained(handle.render(), DOWNLOADED_BYTES, &[]).unwrap()
+    else {
+        panic!("{DOWNLOADED_BYTES} is not a counter.");
+    };
+    assert!(downloaded_bytes >= body.len() as f64);
+}
+
 #[tokio::test]
 async fn state_update_with_empty_storage_diff() {
     let starknet_client = StarknetFeederGatewayClient::new(
@@ -412,3 +645,337 @@ async fn state_update_with_empty_storage_diff() {
     mock.assert();
     assert!(state_update.state_diff.storage_diffs.is_empty());
 }
+
+#[tokio::test]
+async fn block_by_hash() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_block = read_resource_file("reader/block.json");
+    let expected_block: Block = serde_json::from_str(&raw_block).unwrap();
+    let mock_block = mock(
+        "GET",
+        &format!(
+            "/feeder_gateway/get_block?\
+             {BLOCK_HASH_QUERY}=0x13dba522b9185b699399263c9a2afb409c03038f4bb26a46fb5fb9be3b041f2"
+        )[..],
+    )
+    .with_status(200)
+    .with_body(&raw_block)
+    .create();
+    let block = starknet_client.block_by_hash(expected_block.block_hash).await.unwrap().unwrap();
+    mock_block.assert();
+    assert_eq!(block, expected_block);
+
+    // Non-existing block.
+    let body = r#"{"code": "StarknetErrorCode.BLOCK_NOT_FOUND", "message": "Block hash 0x1 was not found."}"#;
+    let mock_no_block =
+        mock("GET", &format!("/feeder_gateway/get_block?{BLOCK_HASH_QUERY}=0x1")[..])
+            .with_status(400)
+            .with_body(body)
+            .create();
+    let block = starknet_client.block_by_hash(BlockHash(stark_felt!("0x1"))).await.unwrap();
+    mock_no_block.assert();
+    assert!(block.is_none());
+}
+
+#[tokio::test]
+async fn pending_block() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_block = read_resource_file("reader/pending_block.json");
+    let mock_block = mock(
+        "GET",
+        &format!("/feeder_gateway/get_block?{BLOCK_NUMBER_QUERY}={PENDING_BLOCK_NUMBER}")[..],
+    )
+    .with_status(200)
+    .with_body(&raw_block)
+    .create();
+    let block = starknet_client.pending_block().await.unwrap().unwrap();
+    mock_block.assert();
+    let expected_block: PendingBlock = serde_json::from_str(&raw_block).unwrap();
+    assert_eq!(block, expected_block);
+}
+
+#[tokio::test]
+async fn transaction() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    const TX_HASH: &str = "0x706deaf73bc99c4a2026a7252f21e3c8db0946ac2221e2abe12a9f3b78192e0";
+    let raw_transaction_info = read_resource_file("reader/transaction_info.json");
+    let mock_transaction = mock(
+        "GET",
+        &format!("/feeder_gateway/get_transaction?{TRANSACTION_HASH_QUERY}={TX_HASH}")[..],
+    )
+    .with_status(200)
+    .with_body(&raw_transaction_info)
+    .create();
+    let transaction_info =
+        starknet_client.transaction(TransactionHash(stark_felt!(TX_HASH))).await.unwrap().unwrap();
+    mock_transaction.assert();
+    let expected_transaction: Transaction =
+        serde_json::from_str(&read_resource_file("reader/invoke_transaction.json")).unwrap();
+    assert_eq!(
+        transaction_info,
+        TransactionInfo {
+            block_info: TransactionBlockInfo {
+                status: TransactionStatus::AcceptedOnL2,
+                finality_status: Some(TransactionFinalityStatus::AcceptedOnL2),
+                execution_status: Some(TransactionExecutionStatus::Succeeded),
+                block_hash: Some(BlockHash(stark_felt!(
+                    "0x13dba522b9185b699399263c9a2afb409c03038f4bb26a46fb5fb9be3b041f2"
+                ))),
+                block_number: Some(BlockNumber(273466)),
+                transaction_index: Some(TransactionOffsetInBlock(1)),
+            },
+            transaction: expected_transaction,
+        }
+    );
+
+    // Transaction that wasn't received.
+    let body = r#"{"status": "NOT_RECEIVED", "finality_status": "NOT_RECEIVED"}"#;
+    let mock_no_transaction =
+        mock("GET", &format!("/feeder_gateway/get_transaction?{TRANSACTION_HASH_QUERY}=0x1")[..])
+            .with_status(200)
+            .with_body(body)
+            .create();
+    let transaction_info =
+        starknet_client.transaction(TransactionHash(stark_felt!("0x1"))).await.unwrap();
+    mock_no_transaction.assert();
+    assert!(transaction_info.is_none());
+}
+
+#[tokio::test]
+async fn transaction_receipt() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let expected_receipt: TransactionReceipt =
+        serde_json::from_str(&read_resource_file("reader/transaction_receipt.json")).unwrap();
+    let mock_receipt = mock(
+        "GET",
+        &format!(
+            "/feeder_gateway/get_transaction_receipt?{TRANSACTION_HASH_QUERY}=0x4586cb82c15ec15a123ba42279aae105f2304cbc1f992f8360ab1b1eb0f718"
+        )[..],
+    )
+    .with_status(200)
+    .with_body(read_resource_file("reader/transaction_receipt_info.json"))
+    .create();
+    let receipt_info = starknet_client
+        .transaction_receipt(expected_receipt.transaction_hash)
+        .await
+        .unwrap()
+        .unwrap();
+    mock_receipt.assert();
+    assert_eq!(receipt_info.block_info.status, TransactionStatus::AcceptedOnL2);
+    assert_eq!(receipt_info.block_info.block_number, Some(BlockNumber(273466)));
+    assert_eq!(receipt_info.block_info.transaction_index, Some(TransactionOffsetInBlock(1)));
+    assert_eq!(receipt_info.receipt, expected_receipt);
+
+    // Transaction that wasn't received.
+    let body = r#"{"status": "NOT_RECEIVED", "finality_status": "NOT_RECEIVED", "transaction_hash": "0x1", "l2_to_l1_messages": [], "events": []}"#;
+    let mock_no_receipt = mock(
+        "GET",
+        &format!("/feeder_gateway/get_transaction_receipt?{TRANSACTION_HASH_QUERY}=0x1")[..],
+    )
+    .with_status(200)
+    .with_body(body)
+    .create();
+    let receipt_info =
+        starknet_client.transaction_receipt(TransactionHash(stark_felt!("0x1"))).await.unwrap();
+    mock_no_receipt.assert();
+    assert!(receipt_info.is_none());
+}
+
+#[tokio::test]
+async fn transaction_status() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let mock_status = mock(
+        "GET",
+        &format!("/feeder_gateway/get_transaction_status?{TRANSACTION_HASH_QUERY}=0x2")[..],
+    )
+    .with_status(200)
+    .with_body(read_resource_file("reader/transaction_status.json"))
+    .create();
+    let status_info =
+        starknet_client.transaction_status(TransactionHash(stark_felt!("0x2"))).await.unwrap();
+    mock_status.assert();
+    assert_eq!(
+        status_info,
+        TransactionStatusInfo {
+            status: TransactionStatus::Rejected,
+            finality_status: Some(TransactionFinalityStatus::Received),
+            execution_status: None,
+            block_hash: None,
+            tx_failure_reason: Some(TransactionFailureReason {
+                code: "StarknetErrorCode.INVALID_TRANSACTION_NONCE".to_owned(),
+                error_message: Some("Invalid transaction nonce. Expected: 5, got: 4.".to_owned()),
+            }),
+        }
+    );
+}
+
+#[tokio::test]
+async fn block_signature() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_signature = read_resource_file("reader/block_signature.json");
+    let mock_signature =
+        mock("GET", &format!("/feeder_gateway/get_signature?{BLOCK_NUMBER_QUERY}=273466")[..])
+            .with_status(200)
+            .with_body(&raw_signature)
+            .create();
+    let signature = starknet_client.block_signature(BlockNumber(273466)).await.unwrap().unwrap();
+    mock_signature.assert();
+    let expected_signature: BlockSignatureData = serde_json::from_str(&raw_signature).unwrap();
+    assert_eq!(signature, expected_signature);
+    assert_eq!(signature.signature.len(), 2);
+
+    // Non-existing block.
+    let body = r#"{"code": "StarknetErrorCode.BLOCK_NOT_FOUND", "message": "Block number 9999999999 was not found."}"#;
+    let mock_no_block =
+        mock("GET", &format!("/feeder_gateway/get_signature?{BLOCK_NUMBER_QUERY}=9999999999")[..])
+            .with_status(400)
+            .with_body(body)
+            .create();
+    let signature = starknet_client.block_signature(BlockNumber(9999999999)).await.unwrap();
+    mock_no_block.assert();
+    assert!(signature.is_none());
+}
+
+#[tokio::test]
+async fn public_key() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let mock_public_key = mock("GET", "/feeder_gateway/get_public_key")
+        .with_status(200)
+        .with_body(r#""0x52934be54ce926b1e715f15dc2542849a97ecfdf829cd0b7384c64eeeb2264e""#)
+        .create();
+    let public_key = starknet_client.public_key().await.unwrap();
+    mock_public_key.assert();
+    assert_eq!(
+        public_key,
+        stark_felt!("0x52934be54ce926b1e715f15dc2542849a97ecfdf829cd0b7384c64eeeb2264e")
+    );
+}
+
+#[tokio::test]
+async fn contract_addresses() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let mock_addresses = mock("GET", "/feeder_gateway/get_contract_addresses")
+        .with_status(200)
+        .with_body(read_resource_file("reader/contract_addresses.json"))
+        .create();
+    let addresses = starknet_client.contract_addresses().await.unwrap();
+    mock_addresses.assert();
+    assert_eq!(
+        serde_json::to_value(addresses).unwrap(),
+        json!({
+            "Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e",
+            "GpsStatementVerifier": "0x8f97970ac5a9aa8d130d35146f5b59c4aef57963",
+        })
+    );
+}
+
+#[tokio::test]
+async fn state_update_with_block() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    let raw_block = read_resource_file("reader/block.json");
+    let raw_state_update = read_resource_file("reader/block_state_update.json");
+    let body = format!(r#"{{"block": {raw_block}, "state_update": {raw_state_update}}}"#);
+    let mock = mock(
+        "GET",
+        &format!(
+            "/feeder_gateway/get_state_update?{BLOCK_NUMBER_QUERY}=273466&\
+             {INCLUDE_BLOCK_QUERY}=true"
+        )[..],
+    )
+    .with_status(200)
+    .with_body(body)
+    .create();
+    let state_update_with_block =
+        starknet_client.state_update_with_block(BlockNumber(273466)).await.unwrap().unwrap();
+    mock.assert();
+    let block: Block = serde_json::from_str(&raw_block).unwrap();
+    let mut state_update: StateUpdate = serde_json::from_str(&raw_state_update).unwrap();
+    state_update.state_diff.storage_diffs.retain(|_k, v| !v.is_empty());
+    assert_eq!(state_update_with_block, StateUpdateWithBlock { block, state_update });
+}
+
+#[tokio::test]
+async fn storage_at() {
+    let starknet_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap();
+    // The key is sent as a decimal number.
+    let mock_storage = mock(
+        "GET",
+        &format!(
+            "/feeder_gateway/get_storage_at?{CONTRACT_ADDRESS_QUERY}=0x123&{KEY_QUERY}=256&\
+             {BLOCK_NUMBER_QUERY}=10"
+        )[..],
+    )
+    .with_status(200)
+    .with_body(r#""0x5""#)
+    .create();
+    let value = starknet_client
+        .storage_at(
+            ContractAddress(patricia_key!("0x123")),
+            StorageKey(patricia_key!("0x100")),
+            BlockNumber(10),
+        )
+        .await
+        .unwrap();
+    mock_storage.assert();
+    assert_eq!(value, Some(stark_felt!("0x5")));
+}
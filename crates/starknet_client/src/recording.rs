@@ -0,0 +1,156 @@
+//! Recording of the requests of the clients with their responses, and replaying them.
+//!
+//! A client with a recordings directory writes every request it sends and the response it got to
+//! a JSON file of a [`Recording`] in the directory. The files are numbered in the order of the
+//! responses. A client created from a recordings directory sends no requests and serves the
+//! recorded responses instead, so the data the clients got can be reproduced exactly. The requests
+//! are matched by their method, the path and query of their URL, and their body, so the replaying
+//! client should have the same URL path as the recording one, but may have a different host.
+
+#[cfg(test)]
+#[path = "recording_test.rs"]
+mod recording_test;
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use reqwest::Request;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{ClientError, ClientResult, StatusCode};
+
+const RECORDING_FILE_EXTENSION: &str = "json";
+
+/// A request of a client and the response it got.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Recording {
+    pub method: String,
+    /// The path and the query of the request URL.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    pub status: u16,
+    pub response: String,
+}
+
+/// Errors that might be encountered while reading or writing recordings.
+#[derive(thiserror::Error, Debug)]
+pub enum RecordingError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Bad recorded status code {status} in {}.", path.display())]
+    BadStatusCode { status: u16, path: PathBuf },
+}
+
+// The method, the path and the body of a request, which identify its recorded responses.
+pub(crate) type RequestKey = (String, String, Option<String>);
+
+pub(crate) fn request_key(request: &Request) -> RequestKey {
+    let url = request.url();
+    (
+        request.method().to_string(),
+        url[url::Position::BeforePath..].to_owned(),
+        request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned()),
+    )
+}
+
+/// Writes the requests of a client and their responses to a directory.
+pub(crate) struct Recorder {
+    recordings_dir: PathBuf,
+    next_index: AtomicUsize,
+}
+
+impl Recorder {
+    // Recording to a directory with recordings continues their numbering.
+    pub(crate) fn new(recordings_dir: impl Into<PathBuf>) -> Result<Self, RecordingError> {
+        let recordings_dir = recordings_dir.into();
+        fs::create_dir_all(&recordings_dir)?;
+        let n_recordings = recording_files(&recordings_dir)?.len();
+        Ok(Recorder { recordings_dir, next_index: AtomicUsize::new(n_recordings) })
+    }
+
+    // Failing to record a response doesn't fail the request.
+    pub(crate) fn record(&self, request_key: RequestKey, status: StatusCode, response: &str) {
+        let (method, path, body) = request_key;
+        let recording = Recording {
+            method,
+            path,
+            body,
+            status: status.as_u16(),
+            response: response.to_owned(),
+        };
+        let index = self.next_index.fetch_add(1, Ordering::SeqCst);
+        let file_path = self.recordings_dir.join(format!("{index:08}.{RECORDING_FILE_EXTENSION}"));
+        let res = serde_json::to_string_pretty(&recording)
+            .map_err(RecordingError::from)
+            .and_then(|recording| Ok(fs::write(&file_path, recording)?));
+        if let Err(err) = res {
+            warn!(
+                "Failed to record the response to {} in {}: {err}.",
+                recording.path,
+                file_path.display()
+            );
+        }
+    }
+}
+
+/// Serves the recorded responses of requests. A request that was recorded several times gets the
+/// responses in the recorded order, and the last response once they are used up.
+pub(crate) struct Replayer {
+    responses: Mutex<HashMap<RequestKey, VecDeque<(StatusCode, String)>>>,
+}
+
+impl Replayer {
+    pub(crate) fn new(recordings_dir: &Path) -> Result<Self, RecordingError> {
+        let mut responses: HashMap<RequestKey, VecDeque<(StatusCode, String)>> = HashMap::new();
+        for file_path in recording_files(recordings_dir)? {
+            let recording: Recording = serde_json::from_str(&fs::read_to_string(&file_path)?)?;
+            let status = StatusCode::from_u16(recording.status).map_err(|_| {
+                RecordingError::BadStatusCode { status: recording.status, path: file_path.clone() }
+            })?;
+            responses
+                .entry((recording.method, recording.path, recording.body))
+                .or_default()
+                .push_back((status, recording.response));
+        }
+        debug!("Loaded recorded responses of {} requests.", responses.len());
+        Ok(Replayer { responses: Mutex::new(responses) })
+    }
+
+    pub(crate) fn replay(&self, request: &Request) -> ClientResult<(StatusCode, String)> {
+        let key = request_key(request);
+        let mut responses = self.responses.lock().expect("Replayer lock should not be poisoned.");
+        let Some(recorded_responses) = responses.get_mut(&key) else {
+            let (method, path, _body) = key;
+            return Err(ClientError::MissingRecording { method, path });
+        };
+        let response = if recorded_responses.len() > 1 {
+            recorded_responses.pop_front().expect("Should have a value.")
+        } else {
+            recorded_responses.front().expect("Should have a value.").clone()
+        };
+        Ok(response)
+    }
+}
+
+// Returns the paths of the recording files in the directory, sorted by their order.
+fn recording_files(recordings_dir: &Path) -> Result<Vec<PathBuf>, RecordingError> {
+    let mut file_paths = Vec::new();
+    for entry in fs::read_dir(recordings_dir)? {
+        let file_path = entry?.path();
+        if file_path.extension().is_some_and(|extension| extension == RECORDING_FILE_EXTENSION) {
+            file_paths.push(file_path);
+        }
+    }
+    file_paths.sort();
+    Ok(file_paths)
+}
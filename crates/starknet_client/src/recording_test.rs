@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+
+use assert_matches::assert_matches;
+use mockito::mock;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+
+use super::Recording;
+use crate::reader::{ReaderClientError, StarknetFeederGatewayClient, StarknetReader};
+use crate::test_utils::read_resource::read_resource_file;
+use crate::test_utils::retry::get_test_config;
+
+const NODE_VERSION: &str = "NODE VERSION";
+const REPLAY_URL: &str = "https://replay.url";
+
+fn write_recordings(recordings_dir: &Path, recordings: &[Recording]) {
+    for (index, recording) in recordings.iter().enumerate() {
+        fs::write(
+            recordings_dir.join(format!("{index:08}.json")),
+            serde_json::to_string(recording).unwrap(),
+        )
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn record_and_replay() {
+    let recordings_dir = tempfile::tempdir().unwrap();
+    let raw_block = read_resource_file("reader/block.json");
+    let not_found_body =
+        r#"{"code": "StarknetErrorCode.BLOCK_NOT_FOUND", "message": "Block was not found."}"#;
+    let mock_block = mock("GET", "/feeder_gateway/get_block?blockNumber=1234567")
+        .with_status(200)
+        .with_body(&raw_block)
+        .create();
+    let mock_no_block = mock("GET", "/feeder_gateway/get_block?blockNumber=1234568")
+        .with_status(400)
+        .with_body(not_found_body)
+        .create();
+
+    let recording_client = StarknetFeederGatewayClient::new(
+        &mockito::server_url(),
+        None,
+        NODE_VERSION,
+        get_test_config(),
+    )
+    .unwrap()
+    .with_recording(recordings_dir.path())
+    .unwrap();
+    let block = recording_client.block(BlockNumber(1234567)).await.unwrap().unwrap();
+    assert!(recording_client.block(BlockNumber(1234568)).await.unwrap().is_none());
+    mock_block.assert();
+    mock_no_block.assert();
+    assert_eq!(fs::read_dir(recordings_dir.path()).unwrap().count(), 2);
+
+    // The replaying client doesn't send requests, so it may have a different host.
+    let replaying_client = StarknetFeederGatewayClient::from_recordings(
+        REPLAY_URL,
+        recordings_dir.path(),
+        get_test_config(),
+    )
+    .unwrap();
+    assert_eq!(replaying_client.block(BlockNumber(1234567)).await.unwrap().unwrap(), block);
+    assert!(replaying_client.block(BlockNumber(1234568)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn replay_missing_recording() {
+    let recordings_dir = tempfile::tempdir().unwrap();
+    let replaying_client = StarknetFeederGatewayClient::from_recordings(
+        REPLAY_URL,
+        recordings_dir.path(),
+        get_test_config(),
+    )
+    .unwrap();
+    let err = replaying_client.block(BlockNumber(0)).await.unwrap_err();
+    assert_matches!(
+        err,
+        ReaderClientError::MissingRecording { method, path }
+        if method == "GET" && path == "/feeder_gateway/get_block?blockNumber=0"
+    );
+}
+
+#[tokio::test]
+async fn replay_repeated_requests_in_order() {
+    let recordings_dir = tempfile::tempdir().unwrap();
+    let path = "/feeder_gateway/get_block?blockNumber=latest";
+    let mut block: serde_json::Value =
+        serde_json::from_str(&read_resource_file("reader/block.json")).unwrap();
+    let mut recording = |status: u16, block_number: u64| {
+        block["block_number"] = block_number.into();
+        Recording {
+            method: "GET".to_owned(),
+            path: path.to_owned(),
+            body: None,
+            status,
+            response: block.to_string(),
+        }
+    };
+    // The first response is retried, as it was when it was recorded.
+    write_recordings(
+        recordings_dir.path(),
+        &[recording(503, 0), recording(200, 1), recording(200, 2)],
+    );
+
+    let replaying_client = StarknetFeederGatewayClient::from_recordings(
+        REPLAY_URL,
+        recordings_dir.path(),
+        get_test_config(),
+    )
+    .unwrap();
+    for expected_block_number in [1, 2, 2] {
+        let block_number = replaying_client.block_number().await.unwrap();
+        assert_eq!(block_number, Some(BlockNumber(expected_block_number)));
+    }
+}
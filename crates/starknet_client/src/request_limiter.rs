@@ -0,0 +1,218 @@
+#[cfg(test)]
+#[path = "request_limiter_test.rs"]
+mod request_limiter_test;
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use papyrus_config::converters::{deserialize_optional_number, serialize_optional_number};
+use papyrus_config::dumping::{ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, SerializedParam};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// A configuration for the request limiter.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RequestLimiterConfig {
+    /// The maximum number of concurrent requests. The concurrency limit starts at this value.
+    pub max_concurrent_requests: usize,
+    /// The minimum number of concurrent requests the concurrency limit can decrease to.
+    pub min_concurrent_requests: usize,
+    /// The percentage by which the concurrency limit is decreased upon a throttling response.
+    pub decrease_percent: usize,
+    /// The maximum number of requests per second, or None for no rate limit.
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub max_requests_per_second: Option<usize>,
+    /// The maximum number of requests that can be sent at once without waiting for the rate limit.
+    pub burst_size: usize,
+}
+
+impl Default for RequestLimiterConfig {
+    fn default() -> Self {
+        RequestLimiterConfig {
+            max_concurrent_requests: 30,
+            min_concurrent_requests: 1,
+            decrease_percent: 50,
+            max_requests_per_second: Some(50),
+            burst_size: 50,
+        }
+    }
+}
+
+impl SerializeConfig for RequestLimiterConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "max_concurrent_requests",
+                &self.max_concurrent_requests,
+                "Maximum number of concurrent requests, shared by all the request types.",
+            ),
+            ser_param(
+                "min_concurrent_requests",
+                &self.min_concurrent_requests,
+                "Minimum number of concurrent requests after decreasing the concurrency upon \
+                 throttling.",
+            ),
+            ser_param(
+                "decrease_percent",
+                &self.decrease_percent,
+                "Percentage by which the number of concurrent requests is decreased upon a 429 or \
+                 503 response. The number increases back by one after each successful round of \
+                 requests.",
+            ),
+            ser_param(
+                "max_requests_per_second",
+                &serialize_optional_number(&self.max_requests_per_second),
+                "Maximum number of requests per second. If empty, the requests are not rate \
+                 limited.",
+            ),
+            ser_param(
+                "burst_size",
+                &self.burst_size,
+                "Maximum number of requests that can be sent at once without waiting for the rate \
+                 limit.",
+            ),
+        ])
+    }
+}
+
+/// A limiter for the requests sent to a Starknet gateway, shared by all the requests of a client.
+/// Limits the number of concurrent requests with an additive-increase/multiplicative-decrease
+/// (AIMD) adjustment upon throttling responses, and the rate of the requests with a token bucket.
+pub struct RequestLimiter {
+    config: RequestLimiterConfig,
+    state: Mutex<LimiterState>,
+    // Notifies the waiting requests when a request finishes or the concurrency limit increases.
+    notify: Notify,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    concurrency_limit: usize,
+    in_flight: usize,
+    successes_since_increase: usize,
+    // Incremented upon each decrease, so that multiple throttling responses to requests that were
+    // sent concurrently decrease the concurrency limit once.
+    generation: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A permit to send a single request. Releases the concurrency slot when dropped.
+pub(crate) struct RequestPermit<'a> {
+    limiter: &'a RequestLimiter,
+    generation: u64,
+}
+
+impl RequestLimiter {
+    pub fn new(config: RequestLimiterConfig) -> Self {
+        let max_concurrent_requests = config.max_concurrent_requests.max(1);
+        RequestLimiter {
+            config,
+            state: Mutex::new(LimiterState {
+                concurrency_limit: max_concurrent_requests,
+                in_flight: 0,
+                successes_since_increase: 0,
+                generation: 0,
+                tokens: config.burst_size as f64,
+                last_refill: Instant::now(),
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Returns the current concurrency limit.
+    pub fn concurrency_limit(&self) -> usize {
+        self.state.lock().expect("Request limiter lock is poisoned.").concurrency_limit
+    }
+
+    // Waits until the request can be sent according to the concurrency and rate limits.
+    pub(crate) async fn acquire(&self) -> RequestPermit<'_> {
+        loop {
+            // Created before checking the state so that a notification in between isn't missed.
+            let notified = self.notify.notified();
+            let wait_for_token = {
+                let mut state = self.state.lock().expect("Request limiter lock is poisoned.");
+                if state.in_flight < state.concurrency_limit {
+                    match self.take_token(&mut state) {
+                        None => {
+                            state.in_flight += 1;
+                            return RequestPermit { limiter: self, generation: state.generation };
+                        }
+                        Some(wait_duration) => Some(wait_duration),
+                    }
+                } else {
+                    None
+                }
+            };
+            match wait_for_token {
+                Some(wait_duration) => tokio::time::sleep(wait_duration).await,
+                None => notified.await,
+            }
+        }
+    }
+
+    // Refills the token bucket and takes a token. Returns the time to wait for the next token if
+    // the bucket is empty.
+    fn take_token(&self, state: &mut LimiterState) -> Option<Duration> {
+        let max_requests_per_second = self.config.max_requests_per_second?;
+        let rate = max_requests_per_second.max(1) as f64;
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(self.config.burst_size.max(1) as f64);
+        state.last_refill = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return None;
+        }
+        Some(Duration::from_secs_f64((1.0 - state.tokens) / rate))
+    }
+
+    fn on_success(&self) {
+        let mut state = self.state.lock().expect("Request limiter lock is poisoned.");
+        state.successes_since_increase += 1;
+        if state.successes_since_increase >= state.concurrency_limit
+            && state.concurrency_limit < self.config.max_concurrent_requests
+        {
+            state.concurrency_limit += 1;
+            state.successes_since_increase = 0;
+            drop(state);
+            self.notify.notify_waiters();
+        }
+    }
+
+    fn on_throttled(&self, generation: u64) {
+        let mut state = self.state.lock().expect("Request limiter lock is poisoned.");
+        if generation != state.generation {
+            return;
+        }
+        let decreased_limit =
+            state.concurrency_limit * 100_usize.saturating_sub(self.config.decrease_percent) / 100;
+        state.concurrency_limit = decreased_limit.max(self.config.min_concurrent_requests).max(1);
+        state.successes_since_increase = 0;
+        state.generation += 1;
+        debug!("Got throttled, decreasing the concurrency limit to {}.", state.concurrency_limit);
+    }
+}
+
+impl RequestPermit<'_> {
+    /// Records that the request succeeded.
+    pub(crate) fn on_success(&self) {
+        self.limiter.on_success();
+    }
+
+    /// Records that the request got a throttling response.
+    pub(crate) fn on_throttled(&self) {
+        self.limiter.on_throttled(self.generation);
+    }
+}
+
+impl Drop for RequestPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().expect("Request limiter lock is poisoned.").in_flight -= 1;
+        self.limiter.notify.notify_waiters();
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::request_limiter::{RequestLimiter, RequestLimiterConfig};
+
+const NO_RATE_LIMIT_CONFIG: RequestLimiterConfig = RequestLimiterConfig {
+    max_concurrent_requests: 4,
+    min_concurrent_requests: 1,
+    decrease_percent: 50,
+    max_requests_per_second: None,
+    burst_size: 1,
+};
+
+#[tokio::test]
+async fn concurrency_limit() {
+    let request_limiter = RequestLimiter::new(RequestLimiterConfig {
+        max_concurrent_requests: 2,
+        ..NO_RATE_LIMIT_CONFIG
+    });
+    let first_permit = request_limiter.acquire().await;
+    let _second_permit = request_limiter.acquire().await;
+
+    // The third request waits until one of the requests finishes.
+    let third_permit =
+        tokio::time::timeout(Duration::from_millis(50), request_limiter.acquire()).await;
+    assert!(third_permit.is_err());
+    drop(first_permit);
+    tokio::time::timeout(Duration::from_millis(50), request_limiter.acquire()).await.unwrap();
+}
+
+#[tokio::test]
+async fn throttling_decreases_concurrency_limit() {
+    let request_limiter = RequestLimiter::new(NO_RATE_LIMIT_CONFIG);
+    let first_permit = request_limiter.acquire().await;
+    let second_permit = request_limiter.acquire().await;
+
+    // Throttling responses of concurrent requests decrease the limit once.
+    first_permit.on_throttled();
+    second_permit.on_throttled();
+    assert_eq!(request_limiter.concurrency_limit(), 2);
+    drop((first_permit, second_permit));
+
+    // The limit doesn't decrease below the minimum.
+    for _ in 0..3 {
+        request_limiter.acquire().await.on_throttled();
+    }
+    assert_eq!(request_limiter.concurrency_limit(), 1);
+}
+
+#[tokio::test]
+async fn successes_increase_concurrency_limit() {
+    let request_limiter = RequestLimiter::new(NO_RATE_LIMIT_CONFIG);
+    request_limiter.acquire().await.on_throttled();
+    request_limiter.acquire().await.on_throttled();
+    assert_eq!(request_limiter.concurrency_limit(), 1);
+
+    // The limit increases by one after a round of successful requests.
+    request_limiter.acquire().await.on_success();
+    assert_eq!(request_limiter.concurrency_limit(), 2);
+    request_limiter.acquire().await.on_success();
+    assert_eq!(request_limiter.concurrency_limit(), 2);
+    request_limiter.acquire().await.on_success();
+    assert_eq!(request_limiter.concurrency_limit(), 3);
+
+    // The limit doesn't increase above the maximum.
+    for _ in 0..20 {
+        request_limiter.acquire().await.on_success();
+    }
+    assert_eq!(request_limiter.concurrency_limit(), 4);
+}
+
+#[tokio::test]
+async fn rate_limit() {
+    const MAX_REQUESTS_PER_SECOND: usize = 20;
+    const BURST_SIZE: usize = 3;
+    let request_limiter = RequestLimiter::new(RequestLimiterConfig {
+        max_requests_per_second: Some(MAX_REQUESTS_PER_SECOND),
+        burst_size: BURST_SIZE,
+        ..NO_RATE_LIMIT_CONFIG
+    });
+
+    // The burst is sent at once, the rest of the requests wait for the rate limit.
+    let start = Instant::now();
+    for _ in 0..BURST_SIZE {
+        request_limiter.acquire().await;
+    }
+    assert!(start.elapsed() < Duration::from_millis(40));
+    for _ in 0..2 {
+        request_limiter.acquire().await;
+    }
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
@@ -2,20 +2,28 @@
 #[path = "retry_test.rs"]
 mod retry_test;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
-use std::iter::Take;
+use std::sync::Mutex;
 use std::time::Duration;
 
+use metrics::{gauge, increment_counter};
+use papyrus_config::converters::deserialize_optional_map;
 use papyrus_config::dumping::{ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, SerializedParam};
-use serde::{Deserialize, Serialize};
-use tokio_retry::strategy::ExponentialBackoff;
-use tokio_retry::{Action, Condition, RetryIf};
-use tracing::debug;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::time::Instant;
+use tokio_retry::strategy::{jitter, ExponentialBackoff};
+use tokio_retry::{Action, Condition};
+use tracing::{debug, info, warn};
+
+// Name of the metrics.
+const CIRCUIT_BREAKER_OPEN: &str = "starknet_client_circuit_breaker_open";
+const CIRCUIT_BREAKER_OPENINGS: &str = "starknet_client_circuit_breaker_openings";
 
 /// A configuration for the retry mechanism.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct RetryConfig {
     /// The initial waiting time in milliseconds.
     pub retry_base_millis: u64,
@@ -23,6 +31,25 @@ pub struct RetryConfig {
     pub retry_max_delay_millis: u64,
     /// The maximum number of retries.
     pub max_retries: usize,
+    /// If true, each waiting time is a random fraction of the exponential backoff, so that
+    /// requests that failed together aren't retried together.
+    pub jitter: bool,
+    /// The maximum number of retries of the requests to specific endpoints, by the name of the
+    /// endpoint (for example, get_block). Overrides max_retries.
+    #[serde(deserialize_with = "deserialize_endpoint_max_retries")]
+    pub endpoint_max_retries: HashMap<String, usize>,
+    /// The number of consecutive failed requests after which the circuit breaker opens, or 0 for
+    /// a circuit breaker that never opens.
+    pub circuit_breaker_failure_threshold: usize,
+    /// The time in milliseconds the circuit breaker stays open before letting a request through.
+    pub circuit_breaker_cooldown_millis: u64,
+}
+
+impl RetryConfig {
+    /// Returns the maximum number of retries of the requests to the endpoint.
+    pub fn max_retries_for(&self, endpoint: &str) -> usize {
+        self.endpoint_max_retries.get(endpoint).copied().unwrap_or(self.max_retries)
+    }
 }
 
 impl SerializeConfig for RetryConfig {
@@ -44,25 +71,88 @@ impl SerializeConfig for RetryConfig {
                 &self.max_retries,
                 "Maximum number of retries before the node stops retrying.",
             ),
+            ser_param(
+                "jitter",
+                &self.jitter,
+                "If true, the waiting time after a failed request is randomized up to the \
+                 exponential backoff.",
+            ),
+            ser_param(
+                "endpoint_max_retries",
+                &serialize_endpoint_max_retries(&self.endpoint_max_retries),
+                "'endpoint1:retries1 endpoint2:retries2 ...' maximum number of retries of the \
+                 requests to specific endpoints, overriding max_retries. For example, \
+                 'get_block:20 add_transaction:0'.",
+            ),
+            ser_param(
+                "circuit_breaker_failure_threshold",
+                &self.circuit_breaker_failure_threshold,
+                "Number of consecutive failed requests after which the requests fail without \
+                 being sent until the cool-down passes. 0 to never stop sending requests.",
+            ),
+            ser_param(
+                "circuit_breaker_cooldown_millis",
+                &self.circuit_breaker_cooldown_millis,
+                "Time after too many consecutive failed requests until a single request is sent \
+                 to check whether the server recovered.",
+            ),
         ])
     }
 }
 
+fn serialize_endpoint_max_retries(endpoint_max_retries: &HashMap<String, usize>) -> String {
+    // Sorted for a deterministic dump, in the format of serialize_optional_map.
+    let mut endpoint_max_retries = endpoint_max_retries
+        .iter()
+        .map(|(endpoint, max_retries)| format!("{endpoint}:{max_retries}"))
+        .collect::<Vec<_>>();
+    endpoint_max_retries.sort();
+    endpoint_max_retries.join(" ")
+}
+
+fn deserialize_endpoint_max_retries<'de, D>(de: D) -> Result<HashMap<String, usize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_optional_map(de)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(endpoint, max_retries)| {
+            let max_retries = max_retries.parse().map_err(|_| {
+                D::Error::custom(format!(
+                    "Invalid maximum number of retries for endpoint {endpoint}: {max_retries}."
+                ))
+            })?;
+            Ok((endpoint, max_retries))
+        })
+        .collect()
+}
+
 /// A utility for retrying actions with a configurable backoff and error filter. Uses an
-/// [`ExponentialBackoff`] strategy.
+/// [`ExponentialBackoff`] strategy, optionally with jitter. An error may ask for a minimal waiting
+/// time before the next attempt, such as the time in a Retry-After header.
 pub struct Retry {
-    strategy: Take<ExponentialBackoff>,
+    base_millis: u64,
+    max_delay: Duration,
+    max_retries: usize,
+    jitter: bool,
 }
 
 impl Retry {
     pub fn new(config: &RetryConfig) -> Self {
         Retry {
-            strategy: ExponentialBackoff::from_millis(config.retry_base_millis)
-                .max_delay(Duration::from_millis(config.retry_max_delay_millis))
-                .take(config.max_retries),
+            base_millis: config.retry_base_millis,
+            max_delay: Duration::from_millis(config.retry_max_delay_millis),
+            max_retries: config.max_retries,
+            jitter: config.jitter,
         }
     }
 
+    /// Creates a retry with the maximum number of retries of the endpoint.
+    pub fn for_endpoint(config: &RetryConfig, endpoint: &str) -> Self {
+        Retry { max_retries: config.max_retries_for(endpoint), ..Self::new(config) }
+    }
+
     fn log_condition<E, C>(err: &E, condition: &mut C) -> bool
     where
         E: Debug,
@@ -85,18 +175,120 @@ impl Retry {
         self.start_with_condition(action, |_: &_| true).await
     }
 
-    pub async fn start_with_condition<I, E, A, C>(
+    pub async fn start_with_condition<I, E, A, C>(&self, action: A, condition: C) -> Result<I, E>
+    where
+        E: Debug,
+        A: Action<Item = I, Error = E>,
+        C: Condition<E> + Send,
+    {
+        self.start_with_condition_and_min_delay(action, condition, |_: &_| None).await
+    }
+
+    /// Retries the action while the condition holds. After a failure, waits at least the delay
+    /// returned by `min_delay` for the error, up to the maximum waiting time.
+    pub async fn start_with_condition_and_min_delay<I, E, A, C, M>(
         &self,
-        action: A,
+        mut action: A,
         mut condition: C,
+        mut min_delay: M,
     ) -> Result<I, E>
     where
         E: Debug,
         A: Action<Item = I, Error = E>,
         C: Condition<E> + Send,
+        M: FnMut(&E) -> Option<Duration>,
     {
-        let condition: Box<dyn Send + FnMut(&E) -> bool> =
-            Box::new(|err| Self::log_condition(err, &mut condition));
-        RetryIf::spawn(self.strategy.clone(), action, condition).await
+        let mut backoff = ExponentialBackoff::from_millis(self.base_millis)
+            .max_delay(self.max_delay)
+            .take(self.max_retries);
+        loop {
+            let err = match action.run().await {
+                Ok(item) => return Ok(item),
+                Err(err) => err,
+            };
+            if !Self::log_condition(&err, &mut condition) {
+                return Err(err);
+            }
+            let Some(mut delay) = backoff.next() else {
+                return Err(err);
+            };
+            if self.jitter {
+                delay = jitter(delay);
+            }
+            if let Some(min_delay) = min_delay(&err) {
+                delay = delay.max(min_delay.min(self.max_delay));
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// A circuit breaker that stops the requests after too many consecutive failures, so that a
+/// server that is down isn't flooded with retries. Once the cool-down passes, a single request is
+/// let through; the circuit breaker closes if it succeeds and stays open for another cool-down if
+/// it fails. The state of the circuit breaker is reported in the metrics.
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    cooldown: Duration,
+    state: Mutex<CircuitBreakerState>,
+}
+
+#[derive(Default)]
+struct CircuitBreakerState {
+    consecutive_failures: usize,
+    // The time until which the requests are stopped, if the circuit breaker is open.
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &RetryConfig) -> Self {
+        CircuitBreaker {
+            failure_threshold: config.circuit_breaker_failure_threshold,
+            cooldown: Duration::from_millis(config.circuit_breaker_cooldown_millis),
+            state: Mutex::new(CircuitBreakerState::default()),
+        }
+    }
+
+    /// Returns whether a request may be sent, or the remaining time until one may be sent if the
+    /// circuit breaker is open. A request that is let through after the cool-down is the only one
+    /// until the next cool-down passes.
+    pub fn check(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("Circuit breaker lock should not be poisoned.");
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < open_until {
+            return Err(open_until - now);
+        }
+        state.open_until = Some(now + self.cooldown);
+        Ok(())
+    }
+
+    pub fn on_success(&self) {
+        let mut state = self.state.lock().expect("Circuit breaker lock should not be poisoned.");
+        if state.open_until.take().is_some() {
+            info!("Closing the circuit breaker of the Starknet client after a successful request.");
+            gauge!(CIRCUIT_BREAKER_OPEN, 0.0);
+        }
+        state.consecutive_failures = 0;
+    }
+
+    pub fn on_failure(&self) {
+        let mut state = self.state.lock().expect("Circuit breaker lock should not be poisoned.");
+        state.consecutive_failures += 1;
+        if self.failure_threshold == 0 || state.consecutive_failures < self.failure_threshold {
+            return;
+        }
+        if state.open_until.is_none() {
+            warn!(
+                "Opening the circuit breaker of the Starknet client after {} consecutive failed \
+                 requests. Stopping the requests for {:?}.",
+                state.consecutive_failures, self.cooldown
+            );
+            gauge!(CIRCUIT_BREAKER_OPEN, 1.0);
+            increment_counter!(CIRCUIT_BREAKER_OPENINGS);
+        }
+        state.open_until = Some(Instant::now() + self.cooldown);
     }
 }
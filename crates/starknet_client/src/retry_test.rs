@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use pretty_assertions::assert_eq;
 
-use super::Retry;
+use super::{CircuitBreaker, Retry, RetryConfig};
 use crate::test_utils::retry::get_test_config;
 
 struct Worker {
@@ -47,3 +49,70 @@ async fn success_on_third_attempt() {
     Retry::new(&config).start(|| worker.work()).await.unwrap();
     assert_eq!(worker.get_last_attempt(), 3);
 }
+
+#[tokio::test]
+async fn endpoint_max_retries() {
+    let config = RetryConfig {
+        endpoint_max_retries: HashMap::from([("add_transaction".to_owned(), 1)]),
+        ..get_test_config()
+    };
+    let worker = Worker::new(10);
+    Retry::for_endpoint(&config, "add_transaction").start(|| worker.work()).await.unwrap_err();
+    assert_eq!(worker.get_last_attempt(), 2);
+
+    let worker = Worker::new(10);
+    Retry::for_endpoint(&config, "get_block").start(|| worker.work()).await.unwrap_err();
+    assert_eq!(worker.get_last_attempt(), 5);
+}
+
+#[tokio::test]
+async fn min_delay_up_to_max_delay() {
+    let config = RetryConfig { retry_max_delay_millis: 200, ..get_test_config() };
+    let min_delay = Duration::from_millis(100);
+    let worker = Worker::new(1);
+    let start = Instant::now();
+    Retry::new(&config)
+        .start_with_condition_and_min_delay(|| worker.work(), |_: &_| true, |_: &_| Some(min_delay))
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= min_delay);
+
+    // A longer delay is capped by the maximum waiting time.
+    let worker = Worker::new(1);
+    let start = Instant::now();
+    Retry::new(&config)
+        .start_with_condition_and_min_delay(
+            || worker.work(),
+            |_: &_| true,
+            |_: &_| Some(Duration::from_secs(3600)),
+        )
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn circuit_breaker() {
+    let config = RetryConfig {
+        circuit_breaker_failure_threshold: 2,
+        circuit_breaker_cooldown_millis: 50,
+        ..get_test_config()
+    };
+    let circuit_breaker = CircuitBreaker::new(&config);
+    circuit_breaker.on_failure();
+    assert!(circuit_breaker.check().is_ok());
+    // A success resets the consecutive failures.
+    circuit_breaker.on_success();
+    circuit_breaker.on_failure();
+    assert!(circuit_breaker.check().is_ok());
+    circuit_breaker.on_failure();
+    let remaining = circuit_breaker.check().unwrap_err();
+    assert!(remaining <= Duration::from_millis(50));
+
+    // After the cool-down, a single request is let through.
+    tokio::time::sleep(remaining).await;
+    assert!(circuit_breaker.check().is_ok());
+    assert!(circuit_breaker.check().is_err());
+    circuit_breaker.on_success();
+    assert!(circuit_breaker.check().is_ok());
+}
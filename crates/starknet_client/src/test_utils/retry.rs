@@ -1,5 +1,15 @@
+use std::collections::HashMap;
+
 use crate::retry::RetryConfig;
 
 pub fn get_test_config() -> RetryConfig {
-    RetryConfig { retry_base_millis: 3, retry_max_delay_millis: 40, max_retries: 4 }
+    RetryConfig {
+        retry_base_millis: 3,
+        retry_max_delay_millis: 40,
+        max_retries: 4,
+        jitter: false,
+        endpoint_max_retries: HashMap::new(),
+        circuit_breaker_failure_threshold: 0,
+        circuit_breaker_cooldown_millis: 0,
+    }
 }
@@ -8,16 +8,22 @@ pub mod objects;
 mod starknet_gateway_client_test;
 
 use std::collections::HashMap;
+use std::path::Path;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
 use url::Url;
 
-use crate::writer::objects::response::{DeclareResponse, DeployAccountResponse, InvokeResponse};
+use crate::writer::objects::response::{
+    CallContractResponse, DeclareResponse, DeployAccountResponse, FeeEstimation, InvokeResponse,
+    TransactionSimulation,
+};
 use crate::writer::objects::transaction::{
-    DeclareTransaction, DeployAccountTransaction, InvokeTransaction,
+    AccountTransaction, CallContractRequest, DeclareTransaction, DeployAccountTransaction,
+    InvokeTransaction,
 };
-use crate::{ClientCreationError, ClientError, RetryConfig, StarknetClient};
+use crate::{ClientCreationError, ClientError, HttpClientConfig, RetryConfig, StarknetClient};
 
 /// Errors that may be returned from a writer client.
 pub type WriterClientError = ClientError;
@@ -52,15 +58,77 @@ pub trait StarknetWriter {
         &self,
         tx: &DeployAccountTransaction,
     ) -> WriterClientResult<DeployAccountResponse>;
+
+    /// Estimates the fee of a transaction on top of the state after `block_number`, or on top of
+    /// the pending state if `block_number` is [`None`].
+    async fn estimate_fee(
+        &self,
+        tx: &AccountTransaction,
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<FeeEstimation>;
+
+    /// Estimates the fees of transactions executed one after the other on top of the state after
+    /// `block_number`, or on top of the pending state if `block_number` is [`None`].
+    async fn estimate_fee_bulk(
+        &self,
+        txs: &[AccountTransaction],
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<Vec<FeeEstimation>>;
+
+    /// Simulates a transaction on top of the state after `block_number`, or on top of the pending
+    /// state if `block_number` is [`None`]. The validation of the transaction is skipped if
+    /// `skip_validate` is true.
+    async fn simulate_transaction(
+        &self,
+        tx: &AccountTransaction,
+        block_number: Option<BlockNumber>,
+        skip_validate: bool,
+    ) -> WriterClientResult<TransactionSimulation>;
+
+    /// Calls a contract function on top of the state after `block_number`, or on top of the
+    /// pending state if `block_number` is [`None`], without changing the state.
+    async fn call_contract(
+        &self,
+        call: &CallContractRequest,
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<CallContractResponse>;
 }
 
 const ADD_TRANSACTION_URL_SUFFIX: &str = "gateway/add_transaction";
+const ESTIMATE_FEE_URL_SUFFIX: &str = "feeder_gateway/estimate_fee";
+const ESTIMATE_FEE_BULK_URL_SUFFIX: &str = "feeder_gateway/estimate_fee_bulk";
+const SIMULATE_TRANSACTION_URL_SUFFIX: &str = "feeder_gateway/simulate_transaction";
+const CALL_CONTRACT_URL_SUFFIX: &str = "feeder_gateway/call_contract";
+const BLOCK_NUMBER_QUERY: &str = "blockNumber";
+const SKIP_VALIDATE_QUERY: &str = "skipValidate";
+const PENDING_BLOCK_NUMBER: &str = "pending";
+
+struct StarknetGatewayUrls {
+    add_transaction: Url,
+    estimate_fee: Url,
+    estimate_fee_bulk: Url,
+    simulate_transaction: Url,
+    call_contract: Url,
+}
+
+impl StarknetGatewayUrls {
+    fn new(url_str: &str) -> Result<Self, ClientCreationError> {
+        let base_url = Url::parse(url_str)?;
+        Ok(StarknetGatewayUrls {
+            add_transaction: base_url.join(ADD_TRANSACTION_URL_SUFFIX)?,
+            estimate_fee: base_url.join(ESTIMATE_FEE_URL_SUFFIX)?,
+            estimate_fee_bulk: base_url.join(ESTIMATE_FEE_BULK_URL_SUFFIX)?,
+            simulate_transaction: base_url.join(SIMULATE_TRANSACTION_URL_SUFFIX)?,
+            call_contract: base_url.join(CALL_CONTRACT_URL_SUFFIX)?,
+        })
+    }
+}
 
 /// A client for the [`Starknet`] gateway.
 ///
 /// [`Starknet`]: https://starknet.io/
 pub struct StarknetGatewayClient {
-    add_transaction_url: Url,
+    urls: StarknetGatewayUrls,
     client: StarknetClient,
 }
 
@@ -86,6 +154,53 @@ impl StarknetWriter for StarknetGatewayClient {
     ) -> WriterClientResult<DeclareResponse> {
         self.add_transaction(&tx).await
     }
+
+    async fn estimate_fee(
+        &self,
+        tx: &AccountTransaction,
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<FeeEstimation> {
+        let url = with_block_number(self.urls.estimate_fee.clone(), block_number);
+        self.post(url, &tx).await
+    }
+
+    async fn estimate_fee_bulk(
+        &self,
+        txs: &[AccountTransaction],
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<Vec<FeeEstimation>> {
+        let url = with_block_number(self.urls.estimate_fee_bulk.clone(), block_number);
+        self.post(url, &txs).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &AccountTransaction,
+        block_number: Option<BlockNumber>,
+        skip_validate: bool,
+    ) -> WriterClientResult<TransactionSimulation> {
+        let mut url = with_block_number(self.urls.simulate_transaction.clone(), block_number);
+        url.query_pairs_mut().append_pair(SKIP_VALIDATE_QUERY, &skip_validate.to_string());
+        self.post(url, &tx).await
+    }
+
+    async fn call_contract(
+        &self,
+        call: &CallContractRequest,
+        block_number: Option<BlockNumber>,
+    ) -> WriterClientResult<CallContractResponse> {
+        let url = with_block_number(self.urls.call_contract.clone(), block_number);
+        self.post(url, &call).await
+    }
+}
+
+fn with_block_number(mut url: Url, block_number: Option<BlockNumber>) -> Url {
+    let block_number = match block_number {
+        Some(block_number) => block_number.0.to_string(),
+        None => PENDING_BLOCK_NUMBER.to_owned(),
+    };
+    url.query_pairs_mut().append_pair(BLOCK_NUMBER_QUERY, &block_number);
+    url
 }
 
 impl StarknetGatewayClient {
@@ -96,22 +211,43 @@ impl StarknetGatewayClient {
         retry_config: RetryConfig,
     ) -> Result<Self, ClientCreationError> {
         Ok(StarknetGatewayClient {
-            add_transaction_url: Url::parse(starknet_url)?.join(ADD_TRANSACTION_URL_SUFFIX)?,
+            urls: StarknetGatewayUrls::new(starknet_url)?,
             client: StarknetClient::new(http_headers, node_version, retry_config)?,
         })
     }
 
+    /// Records every request of the client and its response to `recordings_dir`. See
+    /// [`recording`](crate::recording).
+    pub fn with_recording(mut self, recordings_dir: &Path) -> Result<Self, ClientCreationError> {
+        self.client.record_to(recordings_dir)?;
+        Ok(self)
+    }
+
+    /// Sends the requests of the client with an HTTP client built from the given configuration.
+    pub fn with_http_client_config(
+        mut self,
+        config: &HttpClientConfig,
+    ) -> Result<Self, ClientCreationError> {
+        self.client.set_http_client_config(config)?;
+        Ok(self)
+    }
+
     async fn add_transaction<Transaction: Serialize, Response: for<'a> Deserialize<'a>>(
         &self,
         tx: &Transaction,
+    ) -> WriterClientResult<Response> {
+        self.post(self.urls.add_transaction.clone(), tx).await
+    }
+
+    async fn post<Request: Serialize, Response: for<'a> Deserialize<'a>>(
+        &self,
+        url: Url,
+        request: &Request,
     ) -> WriterClientResult<Response> {
         let response: String = self
             .client
             .request_with_retry(
-                self.client
-                    .internal_client
-                    .post(self.add_transaction_url.clone())
-                    .body(serde_json::to_string(&tx)?),
+                self.client.internal_client.post(url).body(serde_json::to_string(request)?),
             )
             .await?;
         Ok(serde_json::from_str::<Response>(&response)?)
@@ -3,8 +3,14 @@
 //! [`Starknet`]: https://starknet.io/
 
 use serde::{Deserialize, Serialize};
-use starknet_api::core::{ClassHash, ContractAddress};
-use starknet_api::transaction::TransactionHash;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::{
+    Calldata, EthAddress, EventKey, L2ToL1Payload, TransactionHash, TransactionSignature,
+};
+
+use crate::reader::ExecutionResources;
 
 /// A Starknet error code that reports success.
 #[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
@@ -39,3 +45,94 @@ pub struct InvokeResponse {
     pub code: SuccessfulStarknetErrorCode,
     pub transaction_hash: TransactionHash,
 }
+
+/// The unit of a fee.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Eq, PartialEq)]
+pub enum FeeUnit {
+    #[serde(rename = "wei")]
+    Wei,
+    #[serde(rename = "fri")]
+    Fri,
+}
+
+/// The fee estimation of a transaction, returned by the `estimate_fee` and `estimate_fee_bulk`
+/// feeder gateway endpoints.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FeeEstimation {
+    pub overall_fee: u128,
+    pub gas_price: u128,
+    pub gas_usage: u128,
+    pub unit: FeeUnit,
+}
+
+/// The simulation of a transaction, returned by the `simulate_transaction` feeder gateway
+/// endpoint.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionSimulation {
+    pub trace: TransactionTrace,
+    pub fee_estimation: FeeEstimation,
+}
+
+/// The calls made while executing a transaction.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionTrace {
+    pub validate_invocation: Option<FunctionInvocation>,
+    pub function_invocation: Option<FunctionInvocation>,
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    pub signature: TransactionSignature,
+}
+
+/// A call of a contract function, along with the calls it made.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionInvocation {
+    pub caller_address: ContractAddress,
+    pub contract_address: ContractAddress,
+    pub calldata: Calldata,
+    pub call_type: Option<CallType>,
+    pub class_hash: Option<ClassHash>,
+    pub selector: Option<EntryPointSelector>,
+    pub entry_point_type: Option<EntryPointType>,
+    pub result: Vec<StarkFelt>,
+    pub execution_resources: ExecutionResources,
+    pub internal_calls: Vec<FunctionInvocation>,
+    pub events: Vec<OrderedEvent>,
+    pub messages: Vec<OrderedL2ToL1Message>,
+}
+
+/// The type of a call of a contract function.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Eq, PartialEq)]
+pub enum CallType {
+    #[serde(rename = "CALL")]
+    Call,
+    #[serde(rename = "DELEGATE")]
+    Delegate,
+}
+
+/// An event emitted by a call, with its order among the events of the call.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OrderedEvent {
+    pub order: usize,
+    pub keys: Vec<EventKey>,
+    pub data: Vec<StarkFelt>,
+}
+
+/// A message to L1 sent by a call, with its order among the messages of the call.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OrderedL2ToL1Message {
+    pub order: usize,
+    pub to_address: EthAddress,
+    pub payload: L2ToL1Payload,
+}
+
+/// The response of the `call_contract` feeder gateway endpoint.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CallContractResponse {
+    pub result: Vec<StarkFelt>,
+}
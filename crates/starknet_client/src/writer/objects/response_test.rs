@@ -1,6 +1,9 @@
 use test_utils::validate_load_and_dump;
 
-use crate::writer::objects::response::{DeclareResponse, DeployAccountResponse, InvokeResponse};
+use crate::writer::objects::response::{
+    CallContractResponse, DeclareResponse, DeployAccountResponse, FeeEstimation, InvokeResponse,
+    TransactionSimulation,
+};
 
 #[test]
 fn load_and_dump_deploy_account_same_string() {
@@ -16,3 +19,23 @@ fn load_and_dump_invoke_same_string() {
 fn load_and_dump_declare_same_string() {
     validate_load_and_dump::<DeclareResponse>("writer/declare_response.json");
 }
+
+#[test]
+fn load_and_dump_fee_estimation_same_string() {
+    validate_load_and_dump::<FeeEstimation>("writer/fee_estimation.json");
+}
+
+#[test]
+fn load_and_dump_fee_estimation_bulk_same_string() {
+    validate_load_and_dump::<Vec<FeeEstimation>>("writer/fee_estimation_bulk.json");
+}
+
+#[test]
+fn load_and_dump_transaction_simulation_same_string() {
+    validate_load_and_dump::<TransactionSimulation>("writer/transaction_simulation.json");
+}
+
+#[test]
+fn load_and_dump_call_contract_response_same_string() {
+    validate_load_and_dump::<CallContractResponse>("writer/call_contract_response.json");
+}
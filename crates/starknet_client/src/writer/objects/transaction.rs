@@ -9,7 +9,9 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::core::{
+    ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector, Nonce,
+};
 use starknet_api::deprecated_contract_class::{
     EntryPoint as DeprecatedEntryPoint, EntryPointType as DeprecatedEntryPointType, EventAbiEntry,
     FunctionAbiEntry, StructAbiEntry,
@@ -137,6 +139,27 @@ pub enum DeclareTransaction {
     DeclareV2(DeclareV2Transaction),
 }
 
+/// A transaction of an account, that can be estimated and simulated through the feeder gateway.
+/// It has the same serialization format as in the `add_transaction` HTTP method.
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum AccountTransaction {
+    Invoke(InvokeTransaction),
+    Declare(DeclareTransaction),
+    DeployAccount(DeployAccountTransaction),
+}
+
+/// A call of a contract function that doesn't change the state, in the format the feeder gateway
+/// accepts in the `call_contract` HTTP method.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CallContractRequest {
+    pub contract_address: ContractAddress,
+    pub entry_point_selector: EntryPointSelector,
+    pub calldata: Calldata,
+    pub signature: TransactionSignature,
+}
+
 // The structs that are implemented here are the structs that have deviations from starknet_api.
 
 #[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
@@ -1,7 +1,9 @@
-use test_utils::validate_load_and_dump;
+use assert_matches::assert_matches;
+use test_utils::{read_json_file, validate_load_and_dump};
 
 use crate::writer::objects::transaction::{
-    DeclareV1Transaction, DeclareV2Transaction, DeployAccountTransaction, InvokeTransaction,
+    AccountTransaction, CallContractRequest, DeclareTransaction, DeclareV1Transaction,
+    DeclareV2Transaction, DeployAccountTransaction, InvokeTransaction,
 };
 
 #[test]
@@ -23,3 +25,25 @@ fn load_and_dump_declare_v1_same_string() {
 fn load_and_dump_declare_v2_same_string() {
     validate_load_and_dump::<DeclareV2Transaction>("writer/declare_v2.json");
 }
+
+#[test]
+fn load_and_dump_call_contract_same_string() {
+    validate_load_and_dump::<CallContractRequest>("writer/call_contract.json");
+}
+
+#[test]
+fn load_account_transaction() {
+    let load = |resource_file| {
+        serde_json::from_value::<AccountTransaction>(read_json_file(resource_file)).unwrap()
+    };
+    assert_matches!(load("writer/invoke.json"), AccountTransaction::Invoke(_));
+    assert_matches!(
+        load("writer/declare_v1.json"),
+        AccountTransaction::Declare(DeclareTransaction::DeclareV1(_))
+    );
+    assert_matches!(
+        load("writer/declare_v2.json"),
+        AccountTransaction::Declare(DeclareTransaction::DeclareV2(_))
+    );
+    assert_matches!(load("writer/deploy_account.json"), AccountTransaction::DeployAccount(_));
+}
@@ -2,10 +2,16 @@ use std::fmt::Debug;
 use std::future::Future;
 
 use mockito::{mock, Matcher};
+use pretty_assertions::assert_eq;
 use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
 use test_utils::read_json_file;
 
 use crate::test_utils::retry::get_test_config;
+use crate::writer::objects::response::{
+    CallContractResponse, FeeEstimation, TransactionSimulation,
+};
+use crate::writer::objects::transaction::{AccountTransaction, CallContractRequest};
 use crate::writer::{StarknetGatewayClient, StarknetWriter, WriterClientError};
 
 const NODE_VERSION: &str = "NODE VERSION";
@@ -75,3 +81,95 @@ async fn add_deploy_account_transaction() {
     )
     .await;
 }
+
+// Checks that the request is posted to the path with the body of the request resource file, and
+// that the response is deserialized from the response resource file.
+async fn test_feeder_request<
+    Request: for<'a> Deserialize<'a>,
+    Response: for<'a> Deserialize<'a> + Debug + Eq,
+    F: FnOnce(StarknetGatewayClient, Request) -> Fut,
+    Fut: Future<Output = Result<Response, WriterClientError>>,
+>(
+    path: &str,
+    resource_file_request_path: &str,
+    resource_file_response_path: &str,
+    request_function: F,
+) {
+    let client =
+        StarknetGatewayClient::new(&mockito::server_url(), None, NODE_VERSION, get_test_config())
+            .unwrap();
+    let request_json_value = read_json_file(resource_file_request_path);
+    let request = serde_json::from_value::<Request>(request_json_value.clone()).unwrap();
+    let response_json_value = read_json_file(resource_file_response_path);
+    let mock_request = mock("POST", path)
+        .match_body(Matcher::Json(request_json_value))
+        .with_status(200)
+        .with_body(serde_json::to_string(&response_json_value).unwrap())
+        .create();
+    let expected_response = serde_json::from_value::<Response>(response_json_value).unwrap();
+    assert_eq!(expected_response, request_function(client, request).await.unwrap());
+    mock_request.assert();
+}
+
+#[tokio::test]
+async fn estimate_fee() {
+    test_feeder_request(
+        "/feeder_gateway/estimate_fee?blockNumber=pending",
+        "writer/invoke.json",
+        "writer/fee_estimation.json",
+        |client, tx: AccountTransaction| async move { client.estimate_fee(&tx, None).await },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn estimate_fee_bulk() {
+    let client =
+        StarknetGatewayClient::new(&mockito::server_url(), None, NODE_VERSION, get_test_config())
+            .unwrap();
+    let txs = ["writer/invoke.json", "writer/deploy_account.json"]
+        .map(read_json_file);
+    let response_json_value = read_json_file("writer/fee_estimation_bulk.json");
+    let mock_request = mock("POST", "/feeder_gateway/estimate_fee_bulk?blockNumber=10")
+        .match_body(Matcher::Json(serde_json::Value::Array(txs.to_vec())))
+        .with_status(200)
+        .with_body(serde_json::to_string(&response_json_value).unwrap())
+        .create();
+    let txs = txs.map(|tx| serde_json::from_value::<AccountTransaction>(tx).unwrap());
+    let expected_response =
+        serde_json::from_value::<Vec<FeeEstimation>>(response_json_value).unwrap();
+    assert_eq!(
+        expected_response,
+        client.estimate_fee_bulk(&txs, Some(BlockNumber(10))).await.unwrap()
+    );
+    mock_request.assert();
+}
+
+#[tokio::test]
+async fn simulate_transaction() {
+    test_feeder_request(
+        "/feeder_gateway/simulate_transaction?blockNumber=10&skipValidate=true",
+        "writer/invoke.json",
+        "writer/transaction_simulation.json",
+        |client, tx: AccountTransaction| async move {
+            let simulation: TransactionSimulation =
+                client.simulate_transaction(&tx, Some(BlockNumber(10)), true).await?;
+            Ok(simulation)
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn call_contract() {
+    test_feeder_request(
+        "/feeder_gateway/call_contract?blockNumber=pending",
+        "writer/call_contract.json",
+        "writer/call_contract_response.json",
+        |client, call: CallContractRequest| async move {
+            let response: CallContractResponse = client.call_contract(&call, None).await?;
+            Ok(response)
+        },
+    )
+    .await;
+}